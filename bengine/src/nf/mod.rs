@@ -1,2 +1,2 @@
 mod palette;
-pub use palette::Palette;
+pub use palette::{ColorFinder, Palette};
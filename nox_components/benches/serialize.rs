@@ -0,0 +1,73 @@
+// Compares the RON world serializer with the binary one, including the deflate
+// step that saved games apply. Run with `cargo bench -p nox_components`.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use legion::*;
+use nox_components::*;
+
+const COLONY_SIZE: usize = 5000;
+
+fn build_colony() -> World {
+    let mut world = World::default();
+    for i in 0..COLONY_SIZE {
+        world.push((
+            IdentityTag::new(),
+            Position::with_tile_idx(i * 7, 0, (1, 1, 1)),
+            Name {
+                name: format!("Colonist {}", i),
+            },
+            Description {
+                desc: "A test subject with an unremarkable past".to_string(),
+            },
+            Health::new(10),
+            Attributes {
+                str: 10,
+                dex: 11,
+                con: 12,
+                int: 13,
+                wis: 14,
+                cha: 15,
+            },
+            Initiative::new(),
+            Tag("benchmark".to_string()),
+        ));
+    }
+    world
+}
+
+fn compress(bytes: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec(bytes, 6)
+}
+
+fn serialization(c: &mut Criterion) {
+    let world = build_colony();
+    let ron_saved = compress(serialize_world(&world).as_bytes());
+    let binary_saved = compress(&serialize_world_binary(&world).unwrap());
+    println!(
+        "{} entities: RON {} bytes compressed, binary {} bytes compressed",
+        COLONY_SIZE,
+        ron_saved.len(),
+        binary_saved.len()
+    );
+
+    c.bench_function("save ron", |b| {
+        b.iter(|| compress(serialize_world(black_box(&world)).as_bytes()))
+    });
+    c.bench_function("save binary", |b| {
+        b.iter(|| compress(&serialize_world_binary(black_box(&world)).unwrap()))
+    });
+    c.bench_function("load ron", |b| {
+        b.iter(|| {
+            let raw = miniz_oxide::inflate::decompress_to_vec(black_box(&ron_saved)).unwrap();
+            deserialize_world(String::from_utf8(raw).unwrap()).unwrap()
+        })
+    });
+    c.bench_function("load binary", |b| {
+        b.iter(|| {
+            let raw = miniz_oxide::inflate::decompress_to_vec(black_box(&binary_saved)).unwrap();
+            deserialize_world_binary(&raw).unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, serialization);
+criterion_main!(benches);
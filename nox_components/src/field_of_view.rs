@@ -1,18 +1,18 @@
 use crate::prelude::*;
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct FieldOfView {
     pub is_dirty: bool,
     pub radius: usize,
-    pub visible_tiles: HashSet<usize>,
+    pub visible_tiles: BTreeSet<usize>,
 }
 
 impl FieldOfView {
     pub fn new(radius: usize) -> Self {
         Self {
             radius,
-            visible_tiles: HashSet::new(),
+            visible_tiles: BTreeSet::new(),
             is_dirty: true,
         }
     }
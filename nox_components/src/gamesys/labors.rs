@@ -0,0 +1,118 @@
+use crate::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A kind of work a settler can be set to do.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Labor {
+    Mining,
+    Lumber,
+    Hauling,
+    Construction,
+    /// Reactions needing a workshop skill from the raws, e.g. "Masonry".
+    Workshop(String),
+    Farming,
+}
+
+impl Labor {
+    /// Every labor there is: one for each workshop skill used by a reaction in
+    /// the raws, as well as the built-in ones.
+    pub fn all() -> Vec<Labor> {
+        let skills: BTreeSet<String> = nox_raws::RAWS
+            .read()
+            .reactions
+            .reactions
+            .iter()
+            .map(|r| r.skill.clone())
+            .collect();
+
+        let mut labors = vec![
+            Labor::Mining,
+            Labor::Lumber,
+            Labor::Hauling,
+            Labor::Construction,
+        ];
+        for skill in skills {
+            let labor = Labor::of_skill(&skill);
+            if !labors.contains(&labor) {
+                labors.push(labor);
+            }
+        }
+        labors.push(Labor::Farming);
+        labors
+    }
+
+    /// The labor a reaction's skill falls under.
+    pub fn of_skill(skill: &str) -> Labor {
+        match skill {
+            "Construction" => Labor::Construction,
+            _ => Labor::Workshop(skill.to_string()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Labor::Mining => "Mining",
+            Labor::Lumber => "Lumber",
+            Labor::Hauling => "Hauling",
+            Labor::Construction => "Construction",
+            Labor::Workshop(skill) => skill,
+            Labor::Farming => "Farming",
+        }
+    }
+
+    /// The tool a settler needs to carry to do this work.
+    pub fn tool(&self) -> Option<ToolType> {
+        match self {
+            Labor::Mining => Some(ToolType::Digging),
+            Labor::Lumber => Some(ToolType::Chopping),
+            Labor::Farming => Some(ToolType::Farming),
+            _ => None,
+        }
+    }
+
+    /// Labors that need a tool are off until a settler is assigned to them;
+    /// everyone pitches in with everything else.
+    fn default_priority(&self) -> u8 {
+        match self.tool() {
+            Some(_) => 0,
+            None => 1,
+        }
+    }
+}
+
+/// How keen a settler is on each labor: 0 means they won't do it at all, and
+/// among the rest, higher priorities are done first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Labors(pub BTreeMap<Labor, u8>);
+
+impl Labors {
+    pub const MAX_PRIORITY: u8 = 3;
+
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Labors that haven't been set, such as a workshop skill added to the
+    /// raws since, take their default.
+    pub fn priority(&self, labor: &Labor) -> u8 {
+        self.0
+            .get(labor)
+            .cloned()
+            .unwrap_or_else(|| labor.default_priority())
+    }
+
+    pub fn set_priority(&mut self, labor: Labor, priority: u8) {
+        self.0.insert(labor, u8::min(priority, Self::MAX_PRIORITY));
+    }
+
+    pub fn is_enabled(&self, labor: &Labor) -> bool {
+        self.priority(labor) > 0
+    }
+
+    /// Whether the settler's labors call for carrying a tool of this kind.
+    pub fn needs_tool(&self, usage: ToolType) -> bool {
+        [Labor::Mining, Labor::Lumber, Labor::Farming]
+            .iter()
+            .any(|labor| labor.tool() == Some(usage) && self.is_enabled(labor))
+    }
+}
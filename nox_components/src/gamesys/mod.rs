@@ -6,3 +6,5 @@ mod skills;
 pub use skills::*;
 mod health;
 pub use health::*;
+mod labors;
+pub use labors::*;
@@ -1,10 +1,10 @@
 use crate::prelude::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub struct Skills(pub HashMap<Skill, i32>);
+pub struct Skills(pub BTreeMap<Skill, i32>);
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Skill {
     Lumberjack,
     Mining,
@@ -12,7 +12,7 @@ pub enum Skill {
 
 impl Skills {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self(BTreeMap::new())
     }
 
     pub fn get_skill(&self, skill: Skill) -> i32 {
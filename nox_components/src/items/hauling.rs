@@ -5,3 +5,9 @@ pub struct RequestHaul {
     pub destination: usize,
     pub in_progress: Option<usize>,
 }
+
+/// Whether someone has set off with an item to put it in a stockpile, so that
+/// it can't be used for anything else until they have.
+pub fn is_being_hauled(haul: Option<&RequestHaul>) -> bool {
+    haul.map(|rh| rh.in_progress.is_some()).unwrap_or(false)
+}
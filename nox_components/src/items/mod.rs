@@ -12,3 +12,7 @@ mod blueprint;
 pub use blueprint::*;
 mod reaction_job;
 pub use reaction_job::*;
+mod reaction_order;
+pub use reaction_order::*;
+mod stockpile;
+pub use stockpile::*;
@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+/// The work orders queued at a workshop, which take turns.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReactionOrders(pub Vec<ReactionOrder>);
+
+/// A standing request for a workshop to carry out one of its reactions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReactionOrder {
+    pub reaction_tag: String,
+    pub goal: OrderGoal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum OrderGoal {
+    /// Carry out the reaction this many more times.
+    Make(i32),
+    /// Keep carrying it out for as long as there are inputs.
+    Repeat,
+    /// Carry it out whenever there are fewer than this many of its first
+    /// output about.
+    KeepInStock(i32),
+}
+
+impl OrderGoal {
+    pub fn describe(&self) -> String {
+        match self {
+            OrderGoal::Make(n) => format!("Make {}", n),
+            OrderGoal::Repeat => "Repeat".to_string(),
+            OrderGoal::KeepInStock(n) => format!("Keep {} in stock", n),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::prelude::*;
+use nox_raws::ItemDefType;
+use std::collections::HashSet;
+
+/// An area that loose items matching its filter are hauled to. Each tile holds
+/// one item.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Stockpile {
+    pub tiles: Vec<usize>,
+    pub filter: StockpileFilter,
+}
+
+/// What a stockpile takes. An item has to pass each of the lists; an empty list
+/// lets everything through.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct StockpileFilter {
+    pub tags: Vec<String>,
+    pub item_types: Vec<ItemDefType>,
+    pub materials: Vec<usize>,
+}
+
+impl StockpileFilter {
+    /// Whether an item with `tag`, of the given `item_types` (as listed in its
+    /// raws), and made of `material` is taken.
+    pub fn accepts(&self, tag: &str, item_types: &[ItemDefType], material: usize) -> bool {
+        if !self.tags.is_empty() && !self.tags.iter().any(|t| t == tag) {
+            return false;
+        }
+        if !self.materials.is_empty() && !self.materials.contains(&material) {
+            return false;
+        }
+        if !self.item_types.is_empty() && !item_types.iter().any(|it| self.item_types.contains(it))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Every tile that is part of a stockpile, so that items already put away can
+/// be told apart from ones lying about.
+pub fn stockpiled_tiles<'a>(stockpiles: impl Iterator<Item = &'a Stockpile>) -> HashSet<usize> {
+    stockpiles.flat_map(|sp| sp.tiles.iter().cloned()).collect()
+}
@@ -26,7 +26,9 @@ pub use plants::*;
 pub mod spawner;
 
 mod serialize;
-pub use serialize::{deserialize_world, serialize_world};
+pub use serialize::{
+    deserialize_world, deserialize_world_binary, serialize_world, serialize_world_binary,
+};
 
 pub mod prelude {
     pub use crate::*;
@@ -1,4 +1,5 @@
 use crate::*;
+use legion::serialize::{Canon, EntityName};
 use legion::*;
 
 // I didn't like repeating myself in boilerplate, so this macro
@@ -12,7 +13,11 @@ macro_rules! register_component_types {
 }
 
 fn registry() -> Registry<String> {
-    let mut registry = Registry::default();
+    registry_with(Canon::default())
+}
+
+fn registry_with(canon: Canon) -> Registry<String> {
+    let mut registry = Registry::new(canon);
 
     register_component_types!(
         registry,
@@ -55,22 +60,69 @@ fn registry() -> Registry<String> {
         RequestHaul,
         Blueprint,
         ReactionJob,
-        Construction
+        Construction,
+        ReactionOrders,
+        Stockpile
     );
 
     registry
 }
 
+/// Legion names entities with random UUIDs when it serializes them. Naming
+/// them after their `IdentityTag` instead means that saving the same world
+/// twice produces the same bytes.
+fn identity_registry(world: &World) -> Registry<String> {
+    let mut canon = Canon::default();
+    <(Entity, &IdentityTag)>::query()
+        .iter(world)
+        .for_each(|(entity, id)| {
+            let mut name: EntityName = [0; 16];
+            name[..8].copy_from_slice(&(id.0 as u64).to_le_bytes());
+            // A duplicated tag just falls back to a random name
+            let _ = canon.canonize(*entity, name);
+        });
+    registry_with(canon)
+}
+
+fn bincode_options() -> impl bincode::Options {
+    use bincode::Options;
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
+
+/// Compact binary form of the world, using the same component registry as the
+/// RON serializer. This is what goes into saved games.
+pub fn serialize_world_binary(world: &World) -> Result<Vec<u8>, String> {
+    use bincode::Options;
+    bincode_options()
+        .serialize(&world.as_serializable(component::<IdentityTag>(), &identity_registry(world)))
+        .map_err(|e| e.to_string())
+}
+
+pub fn deserialize_world_binary(raw: &[u8]) -> Result<World, String> {
+    use serde::de::DeserializeSeed;
+    let reg = registry();
+    let de = reg.as_deserialize();
+    let mut deserializer = bincode::de::Deserializer::from_slice(raw, bincode_options());
+    let world = de
+        .deserialize(&mut deserializer)
+        .map_err(|e| e.to_string())?;
+    identity::rebuild_identity(&world);
+    Ok(world)
+}
+
 pub fn serialize_world(world: &World) -> String {
-    ron::to_string(&world.as_serializable(component::<IdentityTag>(), &registry())).unwrap()
+    ron::to_string(&world.as_serializable(component::<IdentityTag>(), &identity_registry(world)))
+        .unwrap()
 }
 
-pub fn deserialize_world(raw: String) -> World {
+pub fn deserialize_world(raw: String) -> Result<World, String> {
     use serde::de::DeserializeSeed;
     let reg = registry();
     let de = reg.as_deserialize();
-    let mut ronnie = ron::Deserializer::from_str(&raw).unwrap();
-    let world = de.deserialize(&mut ronnie).unwrap();
+    let mut ronnie = ron::Deserializer::from_str(&raw).map_err(|e| e.to_string())?;
+    let world = de.deserialize(&mut ronnie).map_err(|e| e.to_string())?;
     identity::rebuild_identity(&world);
-    world
+    Ok(world)
 }
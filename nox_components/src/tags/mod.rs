@@ -22,10 +22,9 @@ pub struct Item {}
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct Sentient {}
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Settler {
-    pub miner: bool,
-    pub lumberjack: bool,
+    pub labors: Labors,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
@@ -4,4 +4,5 @@ use crate::prelude::*;
 pub enum WorkOrder {
     None,
     MoveRandomly,
+    Rally,
 }
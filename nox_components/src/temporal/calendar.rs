@@ -12,6 +12,14 @@ pub struct Calendar {
 }
 
 impl Calendar {
+    /// Minutes elapsed since the start of year zero; handy for measuring intervals.
+    pub fn total_minutes(&self) -> u64 {
+        let months = self.year as u64 * 12 + self.month as u64;
+        let days = months * 31 + self.day as u64;
+        let hours = days * 24 + self.hour as u64;
+        hours * 60 + self.minute as u64
+    }
+
     pub fn get_date_time(&self) -> String {
         format!(
             "{:02} {} {} {:02}:{:02}",
@@ -44,6 +44,39 @@ pub enum JobType {
     },
 }
 
+impl JobType {
+    /// The path being followed, if the job is at a travelling step.
+    pub fn path_mut(&mut self) -> Option<&mut Vec<usize>> {
+        match self {
+            JobType::CollectTool {
+                step: CollectToolSteps::TravelToTool { path },
+                ..
+            } => Some(path),
+            JobType::ConstructBuilding {
+                step: BuildingSteps::TravelToBuilding { path },
+                ..
+            } => Some(path),
+            JobType::Construct {
+                step: ConstructionSteps::TravelToBuilding { path },
+                ..
+            } => Some(path),
+            JobType::Haul {
+                step: HaulSteps::TravelToItem { path },
+                ..
+            }
+            | JobType::Haul {
+                step: HaulSteps::TravelToDestination { path },
+                ..
+            } => Some(path),
+            JobType::Reaction {
+                step: ReactionSteps::TravelToReaction { path },
+                ..
+            } => Some(path),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum CollectToolSteps {
     TravelToTool { path: Vec<usize> },
@@ -0,0 +1,257 @@
+// Path finding benchmarks, on a generated region of rolling hills with ramps
+// between the levels and shallow ponds in the hollows. The flat A* is compared
+// with the one it replaced, which is kept below. Run with
+// `cargo bench -p nox_planet`.
+use bengine::noise::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nox_planet::pathfinding::{a_star_search, build_navigation};
+use nox_planet::{rebuild_flags, RampDirection, Region, TileType};
+use nox_spatial::*;
+
+const SEED: u64 = 1234;
+const BASE_Z: usize = 120;
+
+struct Terrain {
+    region: Region,
+    heights: Vec<usize>,
+}
+
+impl Terrain {
+    fn generate() -> Self {
+        let mut noise = FastNoise::seeded(SEED);
+        noise.set_noise_type(NoiseType::SimplexFractal);
+        noise.set_fractal_octaves(4);
+        noise.set_frequency(0.02);
+
+        let mut heights = vec![BASE_Z; REGION_WIDTH * REGION_HEIGHT];
+        for y in 1..REGION_HEIGHT - 1 {
+            for x in 1..REGION_WIDTH - 1 {
+                let n = noise.get_noise(x as f32, y as f32);
+                heights[(y * REGION_WIDTH) + x] = (BASE_Z as f32 + n * 8.0) as usize;
+            }
+        }
+
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Empty);
+        for y in 0..REGION_HEIGHT {
+            for x in 0..REGION_WIDTH {
+                let h = heights[(y * REGION_WIDTH) + x];
+                for z in 0..h {
+                    region.set_tile_type(mapidx(x, y, z), TileType::Solid);
+                }
+                region.set_tile_type(mapidx(x, y, h), TileType::Floor);
+                if h < BASE_Z - 4 {
+                    region.set_water_level(mapidx(x, y, h), 2);
+                }
+            }
+        }
+
+        // Ramps up to any neighbour one level higher
+        for y in 1..REGION_HEIGHT - 1 {
+            for x in 1..REGION_WIDTH - 1 {
+                let h = heights[(y * REGION_WIDTH) + x];
+                let higher = |nx: usize, ny: usize| heights[(ny * REGION_WIDTH) + nx] == h + 1;
+                let direction = if higher(x, y - 1) {
+                    RampDirection::NorthSouth
+                } else if higher(x, y + 1) {
+                    RampDirection::SouthNorth
+                } else if higher(x + 1, y) {
+                    RampDirection::WestEast
+                } else if higher(x - 1, y) {
+                    RampDirection::EastWest
+                } else {
+                    continue;
+                };
+                region.set_tile_type(mapidx(x, y, h), TileType::Ramp { direction });
+            }
+        }
+
+        rebuild_flags(&mut region);
+        Self { region, heights }
+    }
+
+    fn surface(&self, x: usize, y: usize) -> usize {
+        mapidx(x, y, self.heights[(y * REGION_WIDTH) + x])
+    }
+}
+
+/// The flat A* before it had search buffers: closed tiles and parents in a
+/// `HashMap`, and the path built by inserting at the front.
+mod hashmap_a_star {
+    use nox_planet::{Region, DIAGONAL_MOVEMENT, ROAD_COST};
+    use nox_spatial::idxmap;
+    use std::cmp::Ordering;
+    use std::collections::{BinaryHeap, HashMap};
+
+    const MAX_ASTAR_STEPS: usize = 65536;
+
+    #[derive(Copy, Clone)]
+    struct Node {
+        idx: usize,
+        f: f32,
+        g: f32,
+    }
+
+    impl PartialEq for Node {
+        fn eq(&self, other: &Self) -> bool {
+            self.f == other.f
+        }
+    }
+
+    impl Eq for Node {}
+
+    impl Ord for Node {
+        fn cmp(&self, b: &Self) -> Ordering {
+            b.f.partial_cmp(&self.f).unwrap()
+        }
+    }
+
+    impl PartialOrd for Node {
+        fn partial_cmp(&self, b: &Self) -> Option<Ordering> {
+            Some(self.cmp(b))
+        }
+    }
+
+    fn distance(idx1: usize, idx2: usize) -> f32 {
+        let (sx, sy, sz) = idxmap(idx1);
+        let (ex, ey, ez) = idxmap(idx2);
+        let dx = sx as f32 - ex as f32;
+        let dy = sy as f32 - ey as f32;
+        let dz = sz as f32 - ez as f32;
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    fn estimate(idx1: usize, idx2: usize) -> f32 {
+        let (sx, sy, sz) = idxmap(idx1);
+        let (ex, ey, ez) = idxmap(idx2);
+        let dx = (sx as i32 - ex as i32).abs() as f32;
+        let dy = (sy as i32 - ey as i32).abs() as f32;
+        let dz = (sz as i32 - ez as i32).abs() as f32;
+        let flat = if DIAGONAL_MOVEMENT {
+            f32::max(dx, dy) + (std::f32::consts::SQRT_2 - 1.0) * f32::min(dx, dy)
+        } else {
+            dx + dy
+        };
+        (flat + dz) * ROAD_COST
+    }
+
+    /// Returns the path's steps, if there is one.
+    pub fn search(start: usize, end: usize, map: &Region) -> Option<Vec<usize>> {
+        let mut open_list = BinaryHeap::new();
+        open_list.push(Node {
+            idx: start,
+            f: 0.0,
+            g: 0.0,
+        });
+        let mut parents: HashMap<usize, (usize, f32)> = HashMap::new();
+        let mut step_counter = 0;
+        let (_, _, ez) = idxmap(end);
+
+        while let Some(q) = open_list.pop() {
+            if step_counter >= MAX_ASTAR_STEPS {
+                break;
+            }
+            step_counter += 1;
+            if matches!(parents.get(&q.idx), Some(e) if q.g > e.1) {
+                continue;
+            }
+            if distance(q.idx, end) < 1.4 && idxmap(q.idx).2 == ez {
+                if q.idx != end {
+                    parents.insert(end, (q.idx, q.g));
+                }
+                let mut steps = vec![end];
+                let mut current = end;
+                while current != start {
+                    let parent = parents[&current];
+                    steps.insert(0, parent.0);
+                    current = parent.0;
+                }
+                return Some(steps);
+            }
+
+            for (idx, cost) in map.get_available_exits(q.idx).iter() {
+                let g = cost + q.g;
+                if *idx == start || matches!(parents.get(idx), Some(e) if e.1 <= g) {
+                    continue;
+                }
+                open_list.push(Node {
+                    idx: *idx,
+                    f: estimate(*idx, end) + g,
+                    g,
+                });
+                parents.insert(*idx, (q.idx, g));
+            }
+        }
+        None
+    }
+}
+
+/// A trip across the region: its name, and where it starts and ends.
+type Trip = (&'static str, (usize, usize), (usize, usize));
+
+const TRIPS: [Trip; 3] = [
+    ("short", (100, 100), (112, 108)),
+    ("medium", (40, 60), (110, 90)),
+    ("long", (10, 10), (240, 240)),
+];
+
+fn describe(steps: Option<usize>) -> String {
+    steps.map_or("no path".to_string(), |n| format!("{} steps", n))
+}
+
+fn pathfinding(c: &mut Criterion) {
+    let mut terrain = Terrain::generate();
+
+    // Without the navigation graph, every search is a flat A*
+    let flat = terrain.region.clone();
+    build_navigation(&mut terrain.region);
+    let hierarchical = &terrain.region;
+
+    let mut trips: Vec<(String, usize, usize)> = TRIPS
+        .iter()
+        .map(|(name, (sx, sy), (ex, ey))| {
+            (
+                name.to_string(),
+                terrain.surface(*sx, *sy),
+                terrain.surface(*ex, *ey),
+            )
+        })
+        .collect();
+    // Deep underground, so the search has to give up
+    trips.push((
+        "unreachable".to_string(),
+        terrain.surface(100, 100),
+        mapidx(128usize, 128, 10),
+    ));
+
+    for (name, start, end) in trips.iter() {
+        // The old search counted queue entries it skipped towards its limit,
+        // so it gives up on trips that the new one finishes.
+        let before = hashmap_a_star::search(*start, *end, &flat).map(|steps| steps.len());
+        let after = Some(a_star_search(*start, *end, &flat))
+            .filter(|path| path.success)
+            .map(|path| path.steps.len());
+        println!(
+            "{} path: hashmap {}, buffers {}",
+            name,
+            describe(before),
+            describe(after)
+        );
+
+        let mut group = c.benchmark_group(format!("path {}", name));
+        group.sample_size(20);
+        group.bench_function("flat (hashmap)", |b| {
+            b.iter(|| hashmap_a_star::search(black_box(*start), black_box(*end), &flat))
+        });
+        group.bench_function("flat (buffers)", |b| {
+            b.iter(|| a_star_search(black_box(*start), black_box(*end), &flat))
+        });
+        group.bench_function("hierarchical", |b| {
+            b.iter(|| a_star_search(black_box(*start), black_box(*end), hierarchical))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, pathfinding);
+criterion_main!(benches);
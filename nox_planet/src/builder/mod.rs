@@ -52,6 +52,18 @@ lazy_static! {
 }
 
 pub fn start_building_planet(params: PlanetParams) {
+    setup_builder(params);
+    std::thread::spawn(threaded_builder);
+}
+
+/// Builds a planet and its starting region on the current thread, saving it
+/// into a new slot. Returns the slot name, if the save succeeded.
+pub fn build_planet_blocking(params: PlanetParams) -> Option<String> {
+    setup_builder(params);
+    threaded_builder()
+}
+
+fn setup_builder(params: PlanetParams) {
     let mut lock = PLANET_BUILD.lock();
     lock.planet.rng_seed = params.world_seed as u64;
     lock.planet.water_divisor = params.water_level;
@@ -60,11 +72,10 @@ pub fn start_building_planet(params: PlanetParams) {
     lock.planet.strict_beamdown = params.strict_beamdown;
     lock.planet.lacunarity = params.bumpiness;
     lock.params = params;
-    std::mem::drop(lock);
-    std::thread::spawn(threaded_builder);
+    lock.done = false;
 }
 
-fn threaded_builder() {
+fn threaded_builder() -> Option<String> {
     planet_noise::zero_fill();
     planet_noise::planetary_noise();
     planet_categories::planet_type_allocation();
@@ -85,22 +96,43 @@ fn threaded_builder() {
     let world = super::region::builder(&mut region, &clone_planet, crash);
 
     // Save
-    save_world(region, world);
+    let slot = save_world(region, world);
 
     // It's all done
     set_worldgen_status("Done");
     PLANET_BUILD.lock().done = true;
+    slot
 }
 
-fn save_world(region: Region, world: legion::World) {
-    use super::SavedGame;
+fn save_world(region: Region, world: legion::World) -> Option<String> {
+    use super::{SaveHeader, SavedGame};
     set_worldgen_status("Saving the world. To disk, sadly.");
     let pclone = PLANET_BUILD.lock().planet.clone();
-    super::save_world(SavedGame {
-        planet: pclone,
-        current_region: region,
-        ecs_text: nox_components::serialize_world(&world),
-    });
+    let slot = super::unused_slot_name(&format!("world-{}", pclone.rng_seed));
+    let header = SaveHeader::new(&slot, &pclone, &region, &world);
+    let result = nox_components::serialize_world_binary(&world)
+        .map_err(super::SaveError::Encode)
+        .and_then(|ecs_data| {
+            super::save_world(
+                header,
+                SavedGame {
+                    planet: pclone,
+                    current_region: region,
+                    ecs_data,
+                    play_state_text: None,
+                },
+            )
+        });
+    match result {
+        Ok(()) => {
+            super::set_active_slot(&slot);
+            Some(slot)
+        }
+        Err(e) => {
+            println!("Unable to save the new world: {}", e);
+            None
+        }
+    }
 }
 
 fn find_crash_site() -> Point {
@@ -0,0 +1,242 @@
+//! Settlers keep walking the same trips: to the stockpile, the workshops, the
+//! tree line. `PathCache` remembers the most recently used paths so those trips
+//! don't have to be searched for again, and forgets any path as soon as a tile
+//! along it (or next to it) changes.
+use super::NavigationPath;
+use crate::{navigation_affected_by, Region};
+use std::collections::{BTreeMap, HashMap};
+
+/// How many paths are kept by default.
+pub const PATH_CACHE_SIZE: usize = 1024;
+
+type Route = (usize, usize);
+
+struct CachedPath {
+    path: NavigationPath,
+    last_used: u64,
+}
+
+/// A least-recently-used cache of successful paths, keyed by (start, goal).
+pub struct PathCache {
+    capacity: usize,
+    paths: HashMap<Route, CachedPath>,
+    /// Routes by when they were last used, oldest first.
+    by_age: BTreeMap<u64, Route>,
+    /// The routes passing through each tile.
+    by_tile: HashMap<usize, Vec<Route>>,
+    clock: u64,
+}
+
+impl Default for PathCache {
+    fn default() -> Self {
+        Self::new(PATH_CACHE_SIZE)
+    }
+}
+
+impl PathCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            paths: HashMap::new(),
+            by_age: BTreeMap::new(),
+            by_tile: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// A cached path from `start` to `goal`, if there is one.
+    pub fn get(&mut self, start: usize, goal: usize) -> Option<NavigationPath> {
+        self.clock += 1;
+        let cached = self.paths.get_mut(&(start, goal))?;
+        self.by_age.remove(&cached.last_used);
+        self.by_age.insert(self.clock, (start, goal));
+        cached.last_used = self.clock;
+        Some(cached.path.clone())
+    }
+
+    /// Remembers a path, making room by forgetting the least recently used one
+    /// if need be. Failed searches aren't kept.
+    pub fn insert(&mut self, start: usize, goal: usize, path: &NavigationPath) {
+        if !path.success || self.capacity == 0 {
+            return;
+        }
+        self.remove(&(start, goal));
+        while self.paths.len() >= self.capacity {
+            let oldest = *self.by_age.values().next().unwrap();
+            self.remove(&oldest);
+        }
+
+        self.clock += 1;
+        for idx in path.steps.iter() {
+            let routes = self.by_tile.entry(*idx).or_insert_with(Vec::new);
+            if !routes.contains(&(start, goal)) {
+                routes.push((start, goal));
+            }
+        }
+        self.by_age.insert(self.clock, (start, goal));
+        self.paths.insert(
+            (start, goal),
+            CachedPath {
+                path: path.clone(),
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Forgets every path that goes through any of `tiles`, or through a tile
+    /// whose navigation flags were worked out again because of them.
+    pub fn invalidate(&mut self, tiles: &[usize]) {
+        for idx in navigation_affected_by(tiles).iter() {
+            if let Some(routes) = self.by_tile.remove(idx) {
+                for route in routes.iter() {
+                    self.remove(route);
+                }
+            }
+        }
+    }
+
+    /// Forgets the path from `start` to `goal`, e.g. because it turned out to
+    /// be blocked.
+    pub fn forget(&mut self, start: usize, goal: usize) {
+        self.remove(&(start, goal));
+    }
+
+    pub fn clear(&mut self) {
+        self.paths.clear();
+        self.by_age.clear();
+        self.by_tile.clear();
+    }
+
+    fn remove(&mut self, route: &Route) {
+        if let Some(cached) = self.paths.remove(route) {
+            self.by_age.remove(&cached.last_used);
+            for idx in cached.path.steps.iter() {
+                if let Some(routes) = self.by_tile.get_mut(idx) {
+                    routes.retain(|r| r != route);
+                    if routes.is_empty() {
+                        self.by_tile.remove(idx);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks that what is left of a path can still be walked from `position`,
+    /// in case the map has changed since it was planned. `steps` may start with
+    /// `position` itself. The last step is where the path was headed, which
+    /// may only be reachable by standing next to it, so it isn't checked.
+    pub fn is_still_walkable(region: &Region, position: usize, steps: &[usize]) -> bool {
+        let steps = match steps.split_last() {
+            Some((_, walked)) => walked,
+            None => return true,
+        };
+        let mut current = position;
+        for next in steps.iter() {
+            if *next != current && !region.can_step(current, *next) {
+                return false;
+            }
+            current = *next;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::a_star_search;
+    use crate::{rebuild_flags, update_flags, TileType};
+    use nox_spatial::mapidx;
+
+    fn at(x: usize, y: usize, z: usize) -> usize {
+        mapidx(x, y, z)
+    }
+
+    const Z: usize = 100;
+
+    fn corridor() -> Region {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for x in 10..=30 {
+            region.set_tile_type(at(x, 10, Z), TileType::Floor);
+        }
+        rebuild_flags(&mut region);
+        region
+    }
+
+    #[test]
+    fn least_recently_used_paths_are_dropped() {
+        let region = corridor();
+        let mut cache = PathCache::new(2);
+        for start in [10usize, 11, 12].iter() {
+            let start = at(*start, 10, Z);
+            let end = at(30, 10, Z);
+            cache.insert(start, end, &a_star_search(start, end, &region));
+            // Keep the first one fresh
+            cache.get(at(10, 10, Z), end);
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(at(10, 10, Z), at(30, 10, Z)).is_some());
+        assert!(cache.get(at(11, 10, Z), at(30, 10, Z)).is_none());
+        assert!(cache.get(at(12, 10, Z), at(30, 10, Z)).is_some());
+    }
+
+    #[test]
+    fn changed_tiles_invalidate_paths() {
+        let mut region = corridor();
+        let mut cache = PathCache::default();
+        let (start, end) = (at(10, 10, Z), at(30, 10, Z));
+        let path = a_star_search(start, end, &region);
+        cache.insert(start, end, &path);
+        cache.insert(
+            at(25, 10, Z),
+            end,
+            &a_star_search(at(25, 10, Z), end, &region),
+        );
+
+        // Wall off the corridor part way along
+        let wall = at(20, 10, Z);
+        region.set_tile_type(wall, TileType::Wall);
+        update_flags(&mut region, &[wall]);
+        cache.invalidate(&[wall]);
+
+        assert!(cache.get(start, end).is_none());
+        assert!(cache.get(at(25, 10, Z), end).is_some());
+        assert!(PathCache::is_still_walkable(
+            &region,
+            at(25, 10, Z),
+            &path.steps[15..]
+        ));
+        assert!(!PathCache::is_still_walkable(
+            &region,
+            at(12, 10, Z),
+            &path.steps[2..]
+        ));
+    }
+
+    #[test]
+    fn changes_next_to_a_path_invalidate_it() {
+        let mut region = corridor();
+        let mut cache = PathCache::default();
+        let (start, end) = (at(10, 10, Z), at(30, 10, Z));
+        cache.insert(start, end, &a_star_search(start, end, &region));
+
+        // Opening up the side of the corridor changes the exits of the tiles
+        // along it, though the path doesn't go through the new tile
+        let alcove = at(20, 11, Z);
+        region.set_tile_type(alcove, TileType::Floor);
+        update_flags(&mut region, &[alcove]);
+        cache.invalidate(&[alcove]);
+
+        assert!(cache.get(start, end).is_none());
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+//! Connected components of the walkable parts of a region, so that jobs can be
+//! checked for reachability without a path search. Each navigation chunk labels
+//! its own walkable areas, and the labels are joined up across chunk borders;
+//! a change to a tile only relabels the chunks around it.
+//!
+//! Exits are treated as if they worked both ways, so a one-way staircase joins
+//! the levels it connects. Connectivity can therefore say that something is
+//! reachable when it isn't, but never the other way round.
+use super::hierarchy::{
+    chunk_of, chunk_origin, local_idx, CHUNK_COUNT, CHUNK_TILES, NAV_CHUNK_SIZE,
+};
+use crate::Region;
+use nox_spatial::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Flags that make a tile part of the walkable graph.
+const WALKABLE: u16 = Region::CAN_STAND_HERE
+    | Region::CAN_GO_NORTH
+    | Region::CAN_GO_SOUTH
+    | Region::CAN_GO_EAST
+    | Region::CAN_GO_WEST
+    | Region::CAN_GO_UP
+    | Region::CAN_GO_DOWN;
+
+#[derive(Clone, Default)]
+struct ChunkLabels {
+    /// The local label of each tile in the chunk, or 0 if it isn't walkable.
+    /// Empty if nothing in the chunk is.
+    labels: Vec<u16>,
+    count: usize,
+    /// Exits into other chunks, as (local label, tile they lead to).
+    links: Vec<(u16, usize)>,
+}
+
+impl ChunkLabels {
+    fn label(&self, idx: usize) -> u16 {
+        if self.labels.is_empty() {
+            0
+        } else {
+            self.labels[local_idx(idx)]
+        }
+    }
+}
+
+/// Connected component labels for a region. Like the navigation graph, they
+/// aren't saved; they are built when a game is loaded.
+#[derive(Clone, Default)]
+pub struct Connectivity {
+    chunks: Vec<ChunkLabels>,
+    /// Where each chunk's labels start in `components`.
+    offsets: Vec<usize>,
+    /// The region-wide component of every chunk label.
+    components: Vec<u32>,
+}
+
+impl Connectivity {
+    pub fn is_built(&self) -> bool {
+        !self.chunks.is_empty()
+    }
+
+    /// The component a tile belongs to, if it is walkable. Ids change whenever
+    /// the map does, so only compare them with each other.
+    pub fn component(&self, idx: usize) -> Option<u32> {
+        let chunk = chunk_of(idx);
+        match self.chunks.get(chunk)?.label(idx) {
+            0 => None,
+            label => Some(self.components[self.offsets[chunk] + label as usize - 1]),
+        }
+    }
+
+    /// Whether a walker at `start` could get to `end`, or next to it (which is
+    /// as close as paths go). Always true if connectivity hasn't been built.
+    pub fn are_connected(&self, start: usize, end: usize) -> bool {
+        if !self.is_built() {
+            return true;
+        }
+        let component = match self.component(start) {
+            Some(component) => component,
+            None => return false,
+        };
+        let (x, y, z) = idxmap(end);
+        for ny in y.saturating_sub(1)..=usize::min(y + 1, REGION_HEIGHT - 1) {
+            for nx in x.saturating_sub(1)..=usize::min(x + 1, REGION_WIDTH - 1) {
+                if self.component(mapidx(nx, ny, z)) == Some(component) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Joins the chunk labels into region-wide components.
+    fn connect(&mut self) {
+        self.offsets.clear();
+        let mut total = 0;
+        for chunk in self.chunks.iter() {
+            self.offsets.push(total);
+            total += chunk.count;
+        }
+
+        let mut parents: Vec<usize> = (0..total).collect();
+        for (chunk, labels) in self.chunks.iter().enumerate() {
+            for (label, tile) in labels.links.iter() {
+                let other = chunk_of(*tile);
+                let other_label = self.chunks[other].label(*tile);
+                if other_label != 0 {
+                    union(
+                        &mut parents,
+                        self.offsets[chunk] + *label as usize - 1,
+                        self.offsets[other] + other_label as usize - 1,
+                    );
+                }
+            }
+        }
+        self.components = (0..total).map(|i| find(&mut parents, i) as u32).collect();
+    }
+}
+
+pub(super) fn build_connectivity(region: &mut Region) {
+    let mut scratch = vec![0; CHUNK_TILES];
+    let mut connectivity = Connectivity {
+        chunks: (0..CHUNK_COUNT)
+            .map(|chunk| label_chunk(region, chunk, &mut scratch))
+            .collect(),
+        ..Default::default()
+    };
+    connectivity.connect();
+    region.connectivity = Arc::new(connectivity);
+}
+
+pub(super) fn update_connectivity(region: &mut Region, changed: &HashSet<usize>) {
+    if !region.connectivity.is_built() {
+        return;
+    }
+    let mut connectivity = std::mem::take(&mut region.connectivity);
+    let labels = Arc::make_mut(&mut connectivity);
+    let mut scratch = vec![0; CHUNK_TILES];
+    for chunk in changed.iter() {
+        labels.chunks[*chunk] = label_chunk(region, *chunk, &mut scratch);
+    }
+    labels.connect();
+    region.connectivity = connectivity;
+}
+
+/// Labels the walkable areas within a chunk, and notes where they lead out of
+/// it.
+fn label_chunk(region: &Region, chunk: usize, parents: &mut [usize]) -> ChunkLabels {
+    let (ox, oy, oz) = chunk_origin(chunk);
+    let mut walkable = Vec::new();
+    for z in oz..oz + NAV_CHUNK_SIZE {
+        for y in oy..oy + NAV_CHUNK_SIZE {
+            for x in ox..ox + NAV_CHUNK_SIZE {
+                let idx = mapidx(x, y, z);
+                if region.flags.get(idx) & WALKABLE != 0 {
+                    parents[local_idx(idx)] = local_idx(idx);
+                    walkable.push(idx);
+                }
+            }
+        }
+    }
+    if walkable.is_empty() {
+        return ChunkLabels::default();
+    }
+
+    let mut leaving = Vec::new();
+    for idx in walkable.iter() {
+        for (exit, _) in region.get_available_exits(*idx) {
+            if chunk_of(exit) == chunk {
+                union(parents, local_idx(*idx), local_idx(exit));
+            } else {
+                leaving.push((*idx, exit));
+            }
+        }
+    }
+
+    let mut result = ChunkLabels {
+        labels: vec![0; CHUNK_TILES],
+        count: 0,
+        links: Vec::with_capacity(leaving.len()),
+    };
+    for idx in walkable.iter() {
+        let root = find(parents, local_idx(*idx));
+        if result.labels[root] == 0 {
+            result.count += 1;
+            result.labels[root] = result.count as u16;
+        }
+        result.labels[local_idx(*idx)] = result.labels[root];
+    }
+    for (idx, exit) in leaving {
+        result.links.push((result.labels[local_idx(idx)], exit));
+    }
+    result.links.sort_unstable();
+    result.links.dedup();
+    result
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    if a != b {
+        parents[a.max(b)] = a.min(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{build_navigation, update_navigation};
+    use crate::{rebuild_flags, update_flags, TileType};
+
+    fn at(x: usize, y: usize, z: usize) -> usize {
+        mapidx(x, y, z)
+    }
+
+    const Z: usize = 100;
+
+    /// A room across a chunk border, split in two by a wall.
+    fn two_rooms() -> Region {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for y in 10..20 {
+            for x in 20..50 {
+                region.set_tile_type(at(x, y, Z), TileType::Floor);
+            }
+        }
+        for y in 10..20 {
+            region.set_tile_type(at(40, y, Z), TileType::Wall);
+        }
+        rebuild_flags(&mut region);
+        build_navigation(&mut region);
+        region
+    }
+
+    #[test]
+    fn walls_separate_components() {
+        let region = two_rooms();
+        assert!(region.are_connected(at(21, 11, Z), at(30, 18, Z)));
+        assert!(!region.are_connected(at(45, 11, Z), at(35, 18, Z)));
+        assert!(!region.are_connected(at(21, 11, Z), at(45, 18, Z)));
+        // Next to a target is close enough
+        assert!(region.are_connected(at(21, 11, Z), at(40, 15, Z)));
+    }
+
+    #[test]
+    fn digging_and_building_update_components() {
+        let mut region = two_rooms();
+        let door = at(40, 15, Z);
+        region.set_tile_type(door, TileType::Floor);
+        update_flags(&mut region, &[door]);
+        update_navigation(&mut region, &[door]);
+        assert!(region.are_connected(at(21, 11, Z), at(45, 18, Z)));
+
+        // Split the west room in two
+        for y in 10..20 {
+            region.set_tile_type(at(25, y, Z), TileType::Wall);
+        }
+        let wall: Vec<usize> = (10..20).map(|y| at(25, y, Z)).collect();
+        update_flags(&mut region, &wall);
+        update_navigation(&mut region, &wall);
+        assert!(!region.are_connected(at(21, 11, Z), at(45, 18, Z)));
+        assert!(region.are_connected(at(30, 11, Z), at(45, 18, Z)));
+    }
+}
@@ -0,0 +1,314 @@
+//! Dijkstra maps: how far every tile is from the nearest of a set of goals. The
+//! map is worked out once, however many walkers are headed for the goals; each
+//! of them then just steps downhill from wherever they are, without searching
+//! for a path of their own.
+//!
+//! Distances are kept by navigation chunk, and a chunk is only allocated once
+//! the flood reaches it. Maps with a radius, or in regions that are mostly
+//! solid rock, take a fraction of the memory of a full-size array.
+use super::hierarchy::{chunk_of, CHUNK_COUNT, CHUNK_TILES};
+use super::{Node, NAV_CHUNK_SIZE};
+use crate::Region;
+use nox_spatial::idxmap;
+use std::collections::{BinaryHeap, HashSet};
+
+pub struct DijkstraMap {
+    /// Tiles this far or further from every goal are left unreachable.
+    radius: f32,
+    goals: HashSet<usize>,
+    /// Distances by navigation chunk; `None` where nothing has been reached.
+    chunks: Vec<Option<Box<[f32]>>>,
+    /// Set when the map has never been built, or the terrain has changed since.
+    stale: bool,
+}
+
+impl Default for DijkstraMap {
+    fn default() -> Self {
+        Self::with_radius(f32::MAX)
+    }
+}
+
+impl DijkstraMap {
+    /// A map covering the whole region.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A map that gives up on tiles `radius` or more from the nearest goal.
+    pub fn with_radius(radius: f32) -> Self {
+        Self {
+            radius,
+            goals: HashSet::new(),
+            chunks: Vec::new(),
+            stale: true,
+        }
+    }
+
+    pub fn goals(&self) -> &HashSet<usize> {
+        &self.goals
+    }
+
+    /// Marks the map as needing a full rebuild, because the terrain changed.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Brings the map up to date with a new set of goals. If only a few have
+    /// come or gone since last time, and the terrain hasn't changed, only the
+    /// tiles they affect are worked out again.
+    pub fn set_goals(&mut self, region: &Region, goals: &[usize]) {
+        let goals: HashSet<usize> = goals.iter().cloned().collect();
+        let removed: Vec<usize> = self.goals.difference(&goals).cloned().collect();
+        let added: Vec<usize> = goals.difference(&self.goals).cloned().collect();
+
+        if self.stale || (removed.len() + added.len()) * 2 > self.goals.len() {
+            self.build(region, goals);
+            return;
+        }
+        for goal in removed {
+            self.remove_goal(region, goal);
+        }
+        for goal in added {
+            self.add_goal(region, goal);
+        }
+    }
+
+    /// Works the whole map out afresh, from `goals`.
+    pub fn build(&mut self, region: &Region, goals: HashSet<usize>) {
+        self.chunks.clear();
+        self.goals = goals;
+        self.stale = false;
+
+        let mut open_list = BinaryHeap::new();
+        for goal in self.goals.iter() {
+            open_list.push(Node {
+                idx: *goal,
+                f: 0.0,
+                g: 0.0,
+            });
+        }
+        for goal in self.goals.clone() {
+            self.set_distance(goal, 0.0);
+        }
+        self.flood(region, open_list);
+    }
+
+    /// Adds a goal; only the tiles that are now closer to a goal change.
+    pub fn add_goal(&mut self, region: &Region, goal: usize) {
+        if self.stale {
+            let mut goals = self.goals.clone();
+            goals.insert(goal);
+            self.build(region, goals);
+            return;
+        }
+        if !self.goals.insert(goal) {
+            return;
+        }
+        self.set_distance(goal, 0.0);
+        let mut open_list = BinaryHeap::new();
+        open_list.push(Node {
+            idx: goal,
+            f: 0.0,
+            g: 0.0,
+        });
+        self.flood(region, open_list);
+    }
+
+    /// Removes a goal. The tiles that were reached by way of it are cleared,
+    /// and flooded again from the edge of the cleared area.
+    pub fn remove_goal(&mut self, region: &Region, goal: usize) {
+        if self.stale {
+            let mut goals = self.goals.clone();
+            goals.remove(&goal);
+            self.build(region, goals);
+            return;
+        }
+        if !self.goals.remove(&goal) {
+            return;
+        }
+
+        // Everything whose shortest way to a goal might run through this one
+        let mut cleared = HashSet::new();
+        let mut stack = vec![goal];
+        cleared.insert(goal);
+        while let Some(idx) = stack.pop() {
+            let depth = self.distance(idx);
+            for (next, _) in region.get_available_exits(idx) {
+                let next_depth = self.distance(next);
+                if next_depth < f32::MAX
+                    && next_depth == depth + region.step_cost(next, idx)
+                    && cleared.insert(next)
+                {
+                    stack.push(next);
+                }
+            }
+        }
+        for idx in cleared.iter() {
+            self.set_distance(*idx, f32::MAX);
+        }
+
+        let mut open_list = BinaryHeap::new();
+        for idx in cleared.iter() {
+            for (next, _) in region.get_available_exits(*idx) {
+                let depth = self.distance(next);
+                if depth < f32::MAX {
+                    open_list.push(Node {
+                        idx: next,
+                        f: depth,
+                        g: depth,
+                    });
+                }
+            }
+        }
+        self.flood(region, open_list);
+    }
+
+    fn flood(&mut self, region: &Region, mut open_list: BinaryHeap<Node>) {
+        while let Some(q) = open_list.pop() {
+            // Stale entry; a shorter way here has been found since
+            if q.g > self.distance(q.idx) {
+                continue;
+            }
+            for (next, _) in region.get_available_exits(q.idx) {
+                // Walkers head the other way, towards the goals
+                let depth = q.g + region.step_cost(next, q.idx);
+                if depth < self.distance(next) && depth < self.radius {
+                    self.set_distance(next, depth);
+                    open_list.push(Node {
+                        idx: next,
+                        f: depth,
+                        g: depth,
+                    });
+                }
+            }
+        }
+    }
+
+    /// How far `idx` is from the nearest goal, or `f32::MAX` if none can be
+    /// reached from it.
+    #[inline]
+    pub fn distance(&self, idx: usize) -> f32 {
+        let (chunk, offset) = slot(idx);
+        match self.chunks.get(chunk) {
+            Some(Some(distances)) => distances[offset],
+            _ => f32::MAX,
+        }
+    }
+
+    fn set_distance(&mut self, idx: usize, depth: f32) {
+        if self.chunks.is_empty() {
+            self.chunks = (0..CHUNK_COUNT).map(|_| None).collect();
+        }
+        let (chunk, offset) = slot(idx);
+        let distances = self.chunks[chunk]
+            .get_or_insert_with(|| vec![f32::MAX; CHUNK_TILES].into_boxed_slice());
+        distances[offset] = depth;
+    }
+
+    /// The step from `position` that leads most directly towards a goal,
+    /// counting the cost of the step itself.
+    pub fn find_lowest_exit(&self, position: usize, region: &Region) -> Option<usize> {
+        region
+            .get_available_exits(position)
+            .iter()
+            .filter(|(exit, _)| self.distance(*exit) < f32::MAX)
+            .min_by(|a, b| {
+                (self.distance(a.0) + a.1)
+                    .partial_cmp(&(self.distance(b.0) + b.1))
+                    .unwrap()
+            })
+            .map(|(exit, _)| *exit)
+    }
+}
+
+/// Which chunk a tile is in, and where in the chunk.
+#[inline]
+fn slot(idx: usize) -> (usize, usize) {
+    let (x, y, z) = idxmap(idx);
+    let offset = ((z % NAV_CHUNK_SIZE) * NAV_CHUNK_SIZE * NAV_CHUNK_SIZE)
+        + ((y % NAV_CHUNK_SIZE) * NAV_CHUNK_SIZE)
+        + (x % NAV_CHUNK_SIZE);
+    (chunk_of(idx), offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rebuild_flags, TileType};
+    use nox_spatial::mapidx;
+
+    fn at(x: usize, y: usize, z: usize) -> usize {
+        mapidx(x, y, z)
+    }
+
+    const Z: usize = 100;
+
+    fn corridor() -> Region {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for x in 10..=40 {
+            region.set_tile_type(at(x, 10, Z), TileType::Floor);
+        }
+        rebuild_flags(&mut region);
+        region
+    }
+
+    #[test]
+    fn walkers_follow_the_map_to_the_nearest_goal() {
+        let region = corridor();
+        let mut map = DijkstraMap::new();
+        assert_eq!(map.distance(at(20, 10, Z)), f32::MAX);
+        map.set_goals(&region, &[at(10, 10, Z), at(40, 10, Z)]);
+        assert_eq!(map.distance(at(10, 10, Z)), 0.0);
+        assert_eq!(map.distance(at(15, 10, Z)), 5.0);
+        assert_eq!(map.distance(at(20, 11, Z)), f32::MAX);
+
+        // Everyone walks to whichever end is closer
+        let mut position = at(30, 10, Z);
+        while map.distance(position) > 0.0 {
+            position = map.find_lowest_exit(position, &region).unwrap();
+        }
+        assert_eq!(position, at(40, 10, Z));
+
+        // Too far away to bother with
+        let mut map = DijkstraMap::with_radius(10.0);
+        map.set_goals(&region, &[at(10, 10, Z)]);
+        assert!(map.distance(at(19, 10, Z)) < f32::MAX);
+        assert_eq!(map.distance(at(20, 10, Z)), f32::MAX);
+        assert_eq!(map.find_lowest_exit(at(30, 10, Z), &region), None);
+        assert_eq!(map.chunks.iter().filter(|c| c.is_some()).count(), 1);
+    }
+
+    #[test]
+    fn adding_and_removing_goals_matches_a_full_rebuild() {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for y in 20..=60 {
+            for x in 20..=60 {
+                region.set_tile_type(at(x, y, Z), TileType::Floor);
+            }
+        }
+        for y in 20..=50 {
+            region.set_tile_type(at(40, y, Z), TileType::Wall);
+        }
+        rebuild_flags(&mut region);
+
+        let mut goals = vec![at(25, 25, Z), at(55, 25, Z), at(30, 58, Z), at(50, 50, Z)];
+        let mut map = DijkstraMap::new();
+        map.set_goals(&region, &goals);
+
+        goals.retain(|g| *g != at(25, 25, Z));
+        map.remove_goal(&region, at(25, 25, Z));
+        goals.push(at(45, 30, Z));
+        map.add_goal(&region, at(45, 30, Z));
+
+        let mut rebuilt = DijkstraMap::new();
+        rebuilt.set_goals(&region, &goals);
+        for y in 20..=60 {
+            for x in 20..=60 {
+                let idx = at(x, y, Z);
+                assert_eq!(map.distance(idx), rebuilt.distance(idx), "at {},{}", x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,749 @@
+//! Hierarchical (HPA*) pathfinding. The region is divided into chunks, the
+//! same size as the render chunks. Where walkers can cross from one chunk into
+//! the next, a portal is placed on each side of the border; and the cost of
+//! walking between every pair of portals inside a chunk is remembered. Long
+//! searches run over the (much smaller) portal graph, and are then refined into
+//! tiles one chunk at a time.
+use super::{NavigationPath, Node};
+use crate::Region;
+use nox_spatial::*;
+use std::cell::RefCell;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+
+/// Width, height and depth of a navigation chunk.
+pub const NAV_CHUNK_SIZE: usize = 32;
+const CHUNKS_X: usize = REGION_WIDTH / NAV_CHUNK_SIZE;
+const CHUNKS_Y: usize = REGION_HEIGHT / NAV_CHUNK_SIZE;
+const CHUNKS_Z: usize = REGION_DEPTH / NAV_CHUNK_SIZE;
+pub(super) const CHUNK_COUNT: usize = CHUNKS_X * CHUNKS_Y * CHUNKS_Z;
+pub(super) const CHUNK_TILES: usize = NAV_CHUNK_SIZE * NAV_CHUNK_SIZE * NAV_CHUNK_SIZE;
+
+/// Border crossings are grouped into runs of side-by-side tiles. Short runs
+/// get a portal in the middle; longer ones get one at each end, so that paths
+/// don't have to detour through the middle of a wide opening.
+const LONG_RUN: usize = 8;
+
+/// Marks the start and end of a hierarchical search in the portal graph.
+const START: usize = usize::MAX;
+const GOAL: usize = usize::MAX - 1;
+
+#[derive(Clone, Default)]
+struct NavChunk {
+    /// Portal graph edges that leave this chunk, as (from, to, cost).
+    crossings: Vec<(usize, usize, f32)>,
+    /// Tiles that paths enter or leave this chunk through.
+    portals: Vec<usize>,
+    /// For each portal, the cost of reaching the other portals without
+    /// leaving the chunk.
+    routes: HashMap<usize, Vec<(usize, f32)>>,
+}
+
+/// The portal graph for a region. It isn't saved; it is built when a game is
+/// loaded and kept up to date as tiles change.
+#[derive(Clone, Default)]
+pub struct NavGraph {
+    chunks: Vec<NavChunk>,
+}
+
+impl NavGraph {
+    pub fn is_built(&self) -> bool {
+        !self.chunks.is_empty()
+    }
+
+    /// Portal graph edges leaving a portal.
+    fn edges(&self, portal: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let chunk = &self.chunks[chunk_of(portal)];
+        let routes = chunk.routes.get(&portal).into_iter().flatten().cloned();
+        let crossings = chunk
+            .crossings
+            .iter()
+            .filter(move |(from, _, _)| *from == portal)
+            .map(|(_, to, cost)| (*to, *cost));
+        routes.chain(crossings)
+    }
+
+    /// Works out the portals of a chunk from its own and its neighbours'
+    /// crossings, and the routes between them. Unless `force` is set, the
+    /// routes are left alone if the portals haven't changed.
+    fn refresh_portals(
+        &mut self,
+        region: &Region,
+        chunk: usize,
+        force: bool,
+        search: &mut LocalSearch,
+    ) {
+        let mut portals: Vec<usize> = self.chunks[chunk]
+            .crossings
+            .iter()
+            .map(|(from, _, _)| *from)
+            .collect();
+        for neighbour in neighbouring_chunks(chunk) {
+            portals.extend(
+                self.chunks[neighbour]
+                    .crossings
+                    .iter()
+                    .filter(|(_, to, _)| chunk_of(*to) == chunk)
+                    .map(|(_, to, _)| *to),
+            );
+        }
+        portals.sort_unstable();
+        portals.dedup();
+
+        if !force && portals == self.chunks[chunk].portals {
+            return;
+        }
+
+        let mut routes = HashMap::with_capacity(portals.len());
+        for portal in portals.iter() {
+            search.run(region, chunk, &[*portal], false, None);
+            let reachable: Vec<(usize, f32)> = portals
+                .iter()
+                .filter(|other| *other != portal)
+                .filter_map(|other| search.cost(*other).map(|cost| (*other, cost)))
+                .collect();
+            routes.insert(*portal, reachable);
+        }
+
+        let nav_chunk = &mut self.chunks[chunk];
+        nav_chunk.portals = portals;
+        nav_chunk.routes = routes;
+    }
+}
+
+/// Builds the portal graph and connectivity labels for the whole region. Call
+/// it once the region's flags are set, e.g. after loading a game.
+pub fn build_navigation(region: &mut Region) {
+    let mut graph = NavGraph {
+        chunks: vec![NavChunk::default(); CHUNK_COUNT],
+    };
+    for chunk in 0..CHUNK_COUNT {
+        graph.chunks[chunk].crossings = find_crossings(region, chunk);
+    }
+    let mut search = LocalSearch::new();
+    for chunk in 0..CHUNK_COUNT {
+        graph.refresh_portals(region, chunk, true, &mut search);
+    }
+    region.navigation = Arc::new(graph);
+    super::connectivity::build_connectivity(region);
+}
+
+/// Updates the portal graph and connectivity labels after the flags around
+/// `dirty` tiles have been recalculated. Does nothing if the graph hasn't been built.
+pub fn update_navigation(region: &mut Region, dirty: &[usize]) {
+    if !region.navigation.is_built() {
+        return;
+    }
+
+    // Updating flags can change tiles up to two away from a dirty one
+    let mut changed = HashSet::new();
+    for idx in dirty.iter() {
+        let (x, y, z) = idxmap(*idx);
+        let (x1, y1, z1) = (
+            x.saturating_sub(2),
+            y.saturating_sub(2),
+            z.saturating_sub(2),
+        );
+        let x2 = usize::min(x + 2, REGION_WIDTH - 1);
+        let y2 = usize::min(y + 2, REGION_HEIGHT - 1);
+        let z2 = usize::min(z + 2, REGION_DEPTH - 1);
+        for cz in z1 / NAV_CHUNK_SIZE..=z2 / NAV_CHUNK_SIZE {
+            for cy in y1 / NAV_CHUNK_SIZE..=y2 / NAV_CHUNK_SIZE {
+                for cx in x1 / NAV_CHUNK_SIZE..=x2 / NAV_CHUNK_SIZE {
+                    changed.insert(chunk_idx(cx, cy, cz));
+                }
+            }
+        }
+    }
+
+    let mut navigation = std::mem::take(&mut region.navigation);
+    let graph = Arc::make_mut(&mut navigation);
+    for chunk in changed.iter() {
+        graph.chunks[*chunk].crossings = find_crossings(region, *chunk);
+    }
+    // Neighbours only need new routes if their portals moved
+    let mut search = LocalSearch::new();
+    let mut neighbours = HashSet::new();
+    for chunk in changed.iter() {
+        graph.refresh_portals(region, *chunk, true, &mut search);
+        neighbours.extend(neighbouring_chunks(*chunk).filter(|n| !changed.contains(n)));
+    }
+    for chunk in neighbours.iter() {
+        graph.refresh_portals(region, *chunk, false, &mut search);
+    }
+    region.navigation = navigation;
+    super::connectivity::update_connectivity(region, &changed);
+}
+
+thread_local! {
+    /// As with `AStar`, each thread keeps its own chunk search buffers.
+    static SEARCHES: RefCell<Option<Searches>> = RefCell::new(None);
+}
+
+/// The chunk searches a hierarchical search needs: out from the start, back
+/// from the goal, and for refining the portal route.
+struct Searches {
+    from_start: LocalSearch,
+    to_goal: LocalSearch,
+    refine: LocalSearch,
+}
+
+impl Searches {
+    fn new() -> Self {
+        Self {
+            from_start: LocalSearch::new(),
+            to_goal: LocalSearch::new(),
+            refine: LocalSearch::new(),
+        }
+    }
+}
+
+/// Finds a path using the portal graph. Only the start and goal chunks are
+/// searched tile by tile up front; the rest of the path is refined from the
+/// portal route afterwards.
+pub(super) fn hierarchical_search(start: usize, end: usize, region: &Region) -> NavigationPath {
+    SEARCHES.with(|searches| {
+        let mut searches = searches.borrow_mut();
+        let searches = searches.get_or_insert_with(Searches::new);
+        search_portals(start, end, region, searches)
+    })
+}
+
+fn search_portals(
+    start: usize,
+    end: usize,
+    region: &Region,
+    searches: &mut Searches,
+) -> NavigationPath {
+    let graph = &region.navigation;
+    let start_chunk = chunk_of(start);
+    let end_chunk = chunk_of(end);
+    let Searches {
+        from_start,
+        to_goal,
+        refine,
+    } = searches;
+
+    // How far it is from the start to each portal of its chunk
+    from_start.run(region, start_chunk, &[start], false, None);
+
+    // ...and from each portal of the goal's chunk to anywhere close enough to
+    // the goal, which is what the tile search accepts as arriving.
+    let (ex, ey, ez) = idxmap(end);
+    let mut goals = Vec::with_capacity(9);
+    for y in ey.saturating_sub(1)..=usize::min(ey + 1, REGION_HEIGHT - 1) {
+        for x in ex.saturating_sub(1)..=usize::min(ex + 1, REGION_WIDTH - 1) {
+            let idx = mapidx(x, y, ez);
+            if chunk_of(idx) == end_chunk {
+                goals.push(idx);
+            }
+        }
+    }
+    to_goal.run(region, end_chunk, &goals, true, None);
+
+    // A* over the portal graph
+    let mut open_list = BinaryHeap::new();
+    let mut best: HashMap<usize, f32> = HashMap::new();
+    let mut parents: HashMap<usize, usize> = HashMap::new();
+
+    // If the goal shares the start's chunk, walking straight there without
+    // leaving the chunk is a candidate too; it may even be the only way, if
+    // the chunk has no portals.
+    let mut direct = None;
+    if start_chunk == end_chunk {
+        direct = goals
+            .iter()
+            .filter_map(|goal| from_start.cost(*goal).map(|cost| (*goal, cost)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((_, cost)) = direct {
+            best.insert(GOAL, cost);
+            parents.insert(GOAL, START);
+            open_list.push(Node {
+                idx: GOAL,
+                f: cost,
+                g: cost,
+            });
+        }
+    }
+    for portal in graph.chunks[start_chunk].portals.iter() {
+        if let Some(cost) = from_start.cost(*portal) {
+            best.insert(*portal, cost);
+            parents.insert(*portal, START);
+            open_list.push(Node {
+                idx: *portal,
+                f: cost + region.get_pathing_estimate(*portal, end),
+                g: cost,
+            });
+        }
+    }
+
+    let mut found = false;
+    while let Some(q) = open_list.pop() {
+        if q.idx == GOAL {
+            found = true;
+            break;
+        }
+        if best.get(&q.idx).map_or(false, |g| q.g > *g) {
+            continue;
+        }
+
+        if chunk_of(q.idx) == end_chunk {
+            if let Some(cost) = to_goal.cost(q.idx) {
+                let g = q.g + cost;
+                if best.get(&GOAL).map_or(true, |old| g < *old) {
+                    best.insert(GOAL, g);
+                    parents.insert(GOAL, q.idx);
+                    open_list.push(Node { idx: GOAL, f: g, g });
+                }
+            }
+        }
+
+        for (next, cost) in graph.edges(q.idx) {
+            let g = q.g + cost;
+            if best.get(&next).map_or(true, |old| g < *old) {
+                best.insert(next, g);
+                parents.insert(next, q.idx);
+                open_list.push(Node {
+                    idx: next,
+                    f: g + region.get_pathing_estimate(next, end),
+                    g,
+                });
+            }
+        }
+    }
+
+    if !found {
+        return NavigationPath::new();
+    }
+
+    let mut portals = Vec::new();
+    let mut current = parents[&GOAL];
+    while current != START {
+        portals.push(current);
+        current = parents[&current];
+    }
+    portals.reverse();
+
+    let mut steps = if portals.is_empty() {
+        // The direct route won
+        from_start.path_to(direct.unwrap().0)
+    } else {
+        // Refine the portal route into tiles
+        let mut steps = from_start.path_to(portals[0]);
+        for pair in portals.windows(2) {
+            let chunk = chunk_of(pair[0]);
+            if chunk != chunk_of(pair[1]) {
+                steps.push(pair[1]);
+            } else {
+                refine.run(region, chunk, &[pair[0]], false, Some(pair[1]));
+                steps.extend(refine.path_to(pair[1]).iter().skip(1));
+            }
+        }
+        steps.extend(to_goal.path_from(*portals.last().unwrap()).iter().skip(1));
+        steps
+    };
+    if *steps.last().unwrap() != end {
+        steps.push(end);
+    }
+
+    NavigationPath {
+        destination: end,
+        success: true,
+        steps,
+    }
+}
+
+#[inline]
+fn chunk_idx(cx: usize, cy: usize, cz: usize) -> usize {
+    (cz * CHUNKS_Y * CHUNKS_X) + (cy * CHUNKS_X) + cx
+}
+
+#[inline]
+pub(super) fn chunk_of(idx: usize) -> usize {
+    let (x, y, z) = idxmap(idx);
+    chunk_idx(x / NAV_CHUNK_SIZE, y / NAV_CHUNK_SIZE, z / NAV_CHUNK_SIZE)
+}
+
+pub(super) fn chunk_origin(chunk: usize) -> (usize, usize, usize) {
+    let cx = chunk % CHUNKS_X;
+    let cy = (chunk / CHUNKS_X) % CHUNKS_Y;
+    let cz = chunk / (CHUNKS_X * CHUNKS_Y);
+    (
+        cx * NAV_CHUNK_SIZE,
+        cy * NAV_CHUNK_SIZE,
+        cz * NAV_CHUNK_SIZE,
+    )
+}
+
+/// Index of a tile within its chunk.
+#[inline]
+pub(super) fn local_idx(idx: usize) -> usize {
+    let (x, y, z) = idxmap(idx);
+    ((z % NAV_CHUNK_SIZE) * NAV_CHUNK_SIZE * NAV_CHUNK_SIZE)
+        + ((y % NAV_CHUNK_SIZE) * NAV_CHUNK_SIZE)
+        + (x % NAV_CHUNK_SIZE)
+}
+
+fn neighbouring_chunks(chunk: usize) -> impl Iterator<Item = usize> {
+    let cx = chunk % CHUNKS_X;
+    let cy = (chunk / CHUNKS_X) % CHUNKS_Y;
+    let cz = chunk / (CHUNKS_X * CHUNKS_Y);
+    let mut neighbours = Vec::with_capacity(6);
+    if cx > 0 {
+        neighbours.push(chunk_idx(cx - 1, cy, cz));
+    }
+    if cx < CHUNKS_X - 1 {
+        neighbours.push(chunk_idx(cx + 1, cy, cz));
+    }
+    if cy > 0 {
+        neighbours.push(chunk_idx(cx, cy - 1, cz));
+    }
+    if cy < CHUNKS_Y - 1 {
+        neighbours.push(chunk_idx(cx, cy + 1, cz));
+    }
+    if cz > 0 {
+        neighbours.push(chunk_idx(cx, cy, cz - 1));
+    }
+    if cz < CHUNKS_Z - 1 {
+        neighbours.push(chunk_idx(cx, cy, cz + 1));
+    }
+    neighbours.into_iter()
+}
+
+/// Finds the exits from a chunk's border tiles into its neighbours, and
+/// picks the ones to use as portal graph edges.
+fn find_crossings(region: &Region, chunk: usize) -> Vec<(usize, usize, f32)> {
+    let (ox, oy, oz) = chunk_origin(chunk);
+    let last = NAV_CHUNK_SIZE - 1;
+
+    // Group the exits by direction and by the row of the border they are on
+    let mut rows: HashMap<((i32, i32, i32), usize, usize), Vec<(usize, usize, usize, f32)>> =
+        HashMap::new();
+    for z in oz..oz + NAV_CHUNK_SIZE {
+        for y in oy..oy + NAV_CHUNK_SIZE {
+            for x in ox..ox + NAV_CHUNK_SIZE {
+                let on_border = x == ox
+                    || x == ox + last
+                    || y == oy
+                    || y == oy + last
+                    || z == oz
+                    || z == oz + last;
+                if !on_border {
+                    continue;
+                }
+                let idx = mapidx(x, y, z);
+                for (exit, cost) in region.get_available_exits(idx) {
+                    if chunk_of(exit) == chunk {
+                        continue;
+                    }
+                    let (nx, ny, nz) = idxmap(exit);
+                    let direction = (
+                        nx as i32 - x as i32,
+                        ny as i32 - y as i32,
+                        nz as i32 - z as i32,
+                    );
+                    // Side by side east/west exits run along y, north/south
+                    // ones along x. Diagonal steps are left out: corners
+                    // can't be cut, so there is always a straight crossing
+                    // beside them. Anything else stands alone.
+                    let (row, position) = match direction {
+                        (_, 0, 0) => ((x, z), y),
+                        (0, _, 0) => ((y, z), x),
+                        (_, _, 0) => continue,
+                        _ => ((idx, 0), 0),
+                    };
+                    rows.entry((direction, row.0, row.1))
+                        .or_insert_with(Vec::new)
+                        .push((position, idx, exit, cost));
+                }
+            }
+        }
+    }
+
+    let mut crossings = Vec::new();
+    for row in rows.values_mut() {
+        row.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut run_start = 0;
+        for i in 1..=row.len() {
+            if i == row.len() || row[i].0 != row[i - 1].0 + 1 {
+                let run = &row[run_start..i];
+                if run.len() > LONG_RUN {
+                    crossings.push((run[0].1, run[0].2, run[0].3));
+                    let end = run[run.len() - 1];
+                    crossings.push((end.1, end.2, end.3));
+                } else {
+                    let middle = run[run.len() / 2];
+                    crossings.push((middle.1, middle.2, middle.3));
+                }
+                run_start = i;
+            }
+        }
+    }
+    crossings.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+    crossings
+}
+
+/// Dijkstra search that stays inside a single chunk. Searches either outward
+/// from the sources, or (`reverse`) backwards from them, to find how to reach
+/// them.
+struct LocalSearch {
+    chunk: usize,
+    reverse: bool,
+    cost: Vec<f32>,
+    /// The tile each tile was reached from (or leads to, when reversed).
+    parent: Vec<usize>,
+    touched: Vec<usize>,
+}
+
+impl LocalSearch {
+    fn new() -> Self {
+        Self {
+            chunk: 0,
+            reverse: false,
+            cost: vec![f32::MAX; CHUNK_TILES],
+            parent: vec![usize::MAX; CHUNK_TILES],
+            touched: Vec::new(),
+        }
+    }
+
+    fn run(
+        &mut self,
+        region: &Region,
+        chunk: usize,
+        sources: &[usize],
+        reverse: bool,
+        target: Option<usize>,
+    ) {
+        for local in self.touched.drain(..) {
+            self.cost[local] = f32::MAX;
+            self.parent[local] = usize::MAX;
+        }
+        self.chunk = chunk;
+        self.reverse = reverse;
+
+        let mut open_list = BinaryHeap::new();
+        for source in sources.iter() {
+            let local = local_idx(*source);
+            self.cost[local] = 0.0;
+            self.parent[local] = *source;
+            self.touched.push(local);
+            open_list.push(Node {
+                idx: *source,
+                f: 0.0,
+                g: 0.0,
+            });
+        }
+
+        while let Some(q) = open_list.pop() {
+            if Some(q.idx) == target {
+                return;
+            }
+            if q.g > self.cost[local_idx(q.idx)] {
+                continue;
+            }
+            let next_steps = if reverse {
+                entrances(region, q.idx)
+            } else {
+                region.get_available_exits(q.idx).into_iter().collect()
+            };
+            for (next, step_cost) in next_steps {
+                if chunk_of(next) != chunk {
+                    continue;
+                }
+                let local = local_idx(next);
+                let g = q.g + step_cost;
+                if g < self.cost[local] {
+                    if self.cost[local] == f32::MAX {
+                        self.touched.push(local);
+                    }
+                    self.cost[local] = g;
+                    self.parent[local] = q.idx;
+                    open_list.push(Node { idx: next, f: g, g });
+                }
+            }
+        }
+    }
+
+    fn cost(&self, idx: usize) -> Option<f32> {
+        if chunk_of(idx) != self.chunk {
+            return None;
+        }
+        let cost = self.cost[local_idx(idx)];
+        if cost < f32::MAX {
+            Some(cost)
+        } else {
+            None
+        }
+    }
+
+    /// The tiles from a source to `idx`, for a forward search.
+    fn path_to(&self, idx: usize) -> Vec<usize> {
+        let mut path = self.trace(idx);
+        path.reverse();
+        path
+    }
+
+    /// The tiles from `idx` to a source, for a reverse search.
+    fn path_from(&self, idx: usize) -> Vec<usize> {
+        self.trace(idx)
+    }
+
+    fn trace(&self, idx: usize) -> Vec<usize> {
+        let mut path = vec![idx];
+        let mut current = idx;
+        while self.parent[local_idx(current)] != current {
+            current = self.parent[local_idx(current)];
+            path.push(current);
+        }
+        path
+    }
+}
+
+/// The tiles that have an exit leading into `idx`, and what it costs.
+fn entrances(region: &Region, idx: usize) -> Vec<(usize, f32)> {
+    let (x, y, z) = idxmap(idx);
+    let mut result = Vec::new();
+    for nz in z.saturating_sub(1)..=usize::min(z + 1, REGION_DEPTH - 1) {
+        for ny in y.saturating_sub(1)..=usize::min(y + 1, REGION_HEIGHT - 1) {
+            for nx in x.saturating_sub(1)..=usize::min(x + 1, REGION_WIDTH - 1) {
+                let neighbour = mapidx(nx, ny, nz);
+                if neighbour == idx {
+                    continue;
+                }
+                if let Some((_, cost)) = region
+                    .get_available_exits(neighbour)
+                    .iter()
+                    .find(|(exit, _)| *exit == idx)
+                {
+                    result.push((neighbour, *cost));
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rebuild_flags, update_flags, TileType};
+
+    fn at(x: usize, y: usize, z: usize) -> usize {
+        mapidx(x, y, z)
+    }
+
+    const Z: usize = 100;
+
+    /// A floor across the whole region at level Z, with walls at x = 100
+    /// from y = 1 to 254; the only ways through are at each edge.
+    fn walled_plain() -> Region {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for y in 1..REGION_HEIGHT - 1 {
+            for x in 1..REGION_WIDTH - 1 {
+                region.set_tile_type(at(x, y, Z), TileType::Floor);
+            }
+        }
+        for y in 2..REGION_HEIGHT - 2 {
+            region.set_tile_type(at(100, y, Z), TileType::Wall);
+        }
+        rebuild_flags(&mut region);
+        build_navigation(&mut region);
+        region
+    }
+
+    fn assert_walkable(region: &Region, path: &NavigationPath, start: usize, end: usize) {
+        assert!(path.success);
+        assert_eq!(path.steps[0], start);
+        assert_eq!(*path.steps.last().unwrap(), end);
+        // Like the tile search, arriving next to the end counts, and the end
+        // itself is tacked on as the last step.
+        let arrived = path.steps[path.steps.len() - 2];
+        assert!(region.get_pathing_distance(arrived, end) < 1.5);
+        for pair in path.steps[..path.steps.len() - 1].windows(2) {
+            assert!(
+                region
+                    .get_available_exits(pair[0])
+                    .iter()
+                    .any(|(exit, _)| *exit == pair[1]),
+                "No exit from {:?} to {:?}",
+                idxmap(pair[0]),
+                idxmap(pair[1])
+            );
+        }
+    }
+
+    #[test]
+    fn paths_go_around_walls_between_chunks() {
+        let region = walled_plain();
+        let start = at(20, 128, Z);
+        let end = at(230, 130, Z);
+        let path = hierarchical_search(start, end, &region);
+        assert_walkable(&region, &path, start, end);
+
+        // It has to go through one of the gaps at the ends of the wall
+        assert!(path.steps.iter().any(|idx| {
+            let (x, y, _) = idxmap(*idx);
+            x == 100 && (y == 1 || y == REGION_HEIGHT - 2)
+        }));
+    }
+
+    #[test]
+    fn dug_tiles_update_the_graph() {
+        let mut region = walled_plain();
+        let start = at(90, 128, Z);
+        let end = at(110, 128, Z);
+        let around = hierarchical_search(start, end, &region);
+        assert_walkable(&region, &around, start, end);
+
+        region.set_tile_type(at(100, 128, Z), TileType::Floor);
+        update_flags(&mut region, &[at(100, 128, Z)]);
+        update_navigation(&mut region, &[at(100, 128, Z)]);
+        let through = hierarchical_search(start, end, &region);
+        assert_walkable(&region, &through, start, end);
+        assert!(through.steps.len() < around.steps.len());
+        assert!(through.steps.contains(&at(100, 128, Z)));
+    }
+
+    #[test]
+    fn goals_in_the_same_chunk_are_walked_to_directly() {
+        let region = walled_plain();
+        let start = at(34, 34, Z);
+        let end = at(61, 60, Z);
+        let path = hierarchical_search(start, end, &region);
+        assert_walkable(&region, &path, start, end);
+        let flat = super::super::AStar::with(|astar| astar.search(start, end, &region));
+        assert_eq!(path.steps.len(), flat.steps.len());
+    }
+
+    #[test]
+    fn rooms_without_portals_are_searched_inside() {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for y in 33..63 {
+            for x in 33..63 {
+                region.set_tile_type(at(x, y, Z), TileType::Floor);
+            }
+        }
+        rebuild_flags(&mut region);
+        build_navigation(&mut region);
+
+        let start = at(33, 33, Z);
+        let end = at(62, 62, Z);
+        assert!(region.get_pathing_distance(start, end) > NAV_CHUNK_SIZE as f32);
+        let path = super::super::a_star_search(start, end, &region);
+        assert_walkable(&region, &path, start, end);
+    }
+
+    #[test]
+    fn unreachable_goals_fail() {
+        let mut region = walled_plain();
+        for y in [1, REGION_HEIGHT - 2].iter() {
+            region.set_tile_type(at(100, *y, Z), TileType::Wall);
+            update_flags(&mut region, &[at(100, *y, Z)]);
+            update_navigation(&mut region, &[at(100, *y, Z)]);
+        }
+        let path = hierarchical_search(at(20, 128, Z), at(230, 130, Z), &region);
+        assert!(!path.success);
+    }
+}
@@ -1,17 +1,35 @@
-use nox_spatial::idxmap;
+use nox_spatial::{idxmap, REGION_TILES_COUNT};
 
 use crate::Region;
+use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::BinaryHeap;
+mod cache;
+pub use cache::{PathCache, PATH_CACHE_SIZE};
+mod connectivity;
+pub use connectivity::Connectivity;
+mod dijkstra_map;
+pub use dijkstra_map::DijkstraMap;
+mod hierarchy;
+pub use hierarchy::{build_navigation, update_navigation, NavGraph, NAV_CHUNK_SIZE};
 
 /// Bail out if the A* search exceeds this many steps.
 const MAX_ASTAR_STEPS: usize = 65536;
 
 /// Request an A-Star search. The start and end are specified as index numbers (compatible with your
 /// BaseMap implementation), and it requires access to your map so as to call distance and exit determinations.
+///
+/// Once the region's navigation graph is built, long trips (and short ones the plain search gives
+/// up on) are planned over chunk portals instead; see `hierarchy`.
 pub fn a_star_search(start: usize, end: usize, map: &Region) -> NavigationPath {
-    let mut astar = AStar::new(start, end);
-    astar.search(map)
+    let built = map.navigation.is_built();
+    if !built || map.get_pathing_distance(start, end) < NAV_CHUNK_SIZE as f32 {
+        let path = AStar::with(|astar| astar.search(start, end, map));
+        if path.success || !built {
+            return path;
+        }
+    }
+    hierarchy::hierarchical_search(start, end, map)
 }
 
 /// Holds the result of an A-Star navigation query.
@@ -25,10 +43,9 @@ pub struct NavigationPath {
     pub steps: Vec<usize>,
 }
 
-#[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
 /// Node is an internal step inside the A-Star path (not exposed/public). Idx is the current cell,
-/// f is the total cost, g the neighbor cost, and h the heuristic cost. (We're not using h)
+/// f is the total cost (g plus the heuristic), and g the cost of getting there.
 /// See: https://en.wikipedia.org/wiki/A*_search_algorithm
 struct Node {
     idx: usize,
@@ -38,21 +55,23 @@ struct Node {
 
 impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
-        self.f == other.f
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl Eq for Node {}
 
 impl Ord for Node {
+    /// Reversed, so that the binary heap pops the lowest f first. Ties go to
+    /// the node furthest along, which is usually closer to the goal.
     fn cmp(&self, b: &Self) -> Ordering {
-        b.f.partial_cmp(&self.f).unwrap()
+        b.f.total_cmp(&self.f).then(self.g.total_cmp(&b.g))
     }
 }
 
 impl PartialOrd for Node {
     fn partial_cmp(&self, b: &Self) -> Option<Ordering> {
-        b.f.partial_cmp(&self.f)
+        Some(self.cmp(b))
     }
 }
 
@@ -67,118 +86,247 @@ impl NavigationPath {
     }
 }
 
-/// Private structure for calculating an A-Star navigation path.
+/// Where each part of an `AStar` tile entry is.
+const STAMP: usize = 0;
+const G: usize = 1;
+const PARENT: usize = 2;
+
+thread_local! {
+    /// Each thread keeps its own search buffers, so searches can run side by side.
+    static ASTAR: RefCell<Option<AStar>> = RefCell::new(None);
+}
+
+/// Private structure for calculating an A-Star navigation path. It has an
+/// entry for every tile in the region, and is reused from one search to the
+/// next: each search stamps the tiles it visits with a new generation number,
+/// so nothing needs clearing in between. The buffer starts out zeroed, so the
+/// operating system only hands over memory for the parts of the region that
+/// searches actually visit.
 struct AStar {
-    start: usize,
-    end: usize,
+    /// Tiles stamped with `generation` are open, `generation + 1` closed; any
+    /// lower stamp means the tile hasn't been seen by this search.
+    generation: u32,
+    /// For each tile, side by side so that a visit touches one cache line:
+    /// its stamp, the cost of the best way found to it so far (as `f32`
+    /// bits), and the tile that way came from.
+    tiles: Vec<[u32; 3]>,
     open_list: BinaryHeap<Node>,
-    closed_list: HashMap<usize, f32>,
-    parents: HashMap<usize, (usize, f32)>, // (index, cost)
-    step_counter: usize,
 }
 
 impl AStar {
-    /// Creates a new path, with specified starting and ending indices.
-    fn new(start: usize, end: usize) -> AStar {
-        let mut open_list: BinaryHeap<Node> = BinaryHeap::new();
-        open_list.push(Node {
-            idx: start,
-            f: 0.0,
-            g: 0.0,
-        });
-
+    fn new() -> AStar {
         AStar {
-            start,
-            end,
-            open_list,
-            parents: HashMap::new(),
-            closed_list: HashMap::new(),
-            step_counter: 0,
+            generation: 0,
+            tiles: vec![[0; 3]; REGION_TILES_COUNT],
+            open_list: BinaryHeap::new(),
         }
     }
 
-    /// Wrapper to the BaseMap's distance function.
-    fn distance_to_end(&self, idx: usize, map: &Region) -> f32 {
-        map.get_pathing_distance(idx, self.end)
+    /// Runs `f` with this thread's search buffers, creating them the first time.
+    fn with<F: FnOnce(&mut AStar) -> NavigationPath>(f: F) -> NavigationPath {
+        ASTAR.with(|astar| f(astar.borrow_mut().get_or_insert_with(AStar::new)))
     }
 
-    /// Adds a successor; if we're at the end, marks success.
-    fn add_successor(&mut self, q: Node, idx: usize, cost: f32, map: &Region) {
-        let distance = self.distance_to_end(idx, map);
-        let s = Node {
-            idx,
-            f: distance + cost,
-            g: cost,
-        };
-
-        // If a node with the same position as successor is in the open list with a lower f, skip add
-        let mut should_add = true;
-        if let Some(e) = self.parents.get(&idx) {
-            if e.1 < s.f {
-                should_add = false;
-            }
+    /// Starts a new generation, forgetting the previous search.
+    fn reset(&mut self) {
+        if self.generation >= u32::MAX - 2 {
+            self.tiles.iter_mut().for_each(|t| t[STAMP] = 0);
+            self.generation = 0;
         }
+        self.generation += 2;
+        self.open_list.clear();
+    }
 
-        // If a node with the same position as successor is in the closed list, with a lower f, skip add
-        if should_add && self.closed_list.contains_key(&idx) && self.closed_list[&idx] < s.f {
-            should_add = false;
-        }
+    #[inline]
+    fn is_open(&self, idx: usize) -> bool {
+        self.tiles[idx][STAMP] == self.generation
+    }
 
-        if should_add {
-            self.open_list.push(s);
-            self.parents.insert(idx, (q.idx, q.f));
-        }
+    #[inline]
+    fn is_closed(&self, idx: usize) -> bool {
+        self.tiles[idx][STAMP] == self.generation + 1
+    }
+
+    #[inline]
+    fn g(&self, idx: usize) -> f32 {
+        f32::from_bits(self.tiles[idx][G])
+    }
+
+    #[inline]
+    fn parent(&self, idx: usize) -> usize {
+        self.tiles[idx][PARENT] as usize
+    }
+
+    /// Records a (better) way to reach `idx`, and queues it up.
+    #[inline]
+    fn open(&mut self, idx: usize, parent: usize, g: f32, h: f32) {
+        self.tiles[idx] = [self.generation, g.to_bits(), parent as u32];
+        self.open_list.push(Node { idx, f: g + h, g });
     }
 
     /// Helper function to unwrap a path once we've found the end-point.
-    fn found_it(&self) -> NavigationPath {
-        let mut result = NavigationPath::new();
-        result.success = true;
-        result.destination = self.end;
-
-        result.steps.push(self.end);
-        let mut current = self.end;
-        while current != self.start {
-            let parent = self.parents[&current];
-            result.steps.insert(0, parent.0);
-            current = parent.0;
+    fn found_it(&self, start: usize, last: usize, end: usize) -> NavigationPath {
+        let mut steps = Vec::new();
+        let mut current = last;
+        while current != start {
+            steps.push(current);
+            current = self.parent(current);
+        }
+        steps.push(start);
+        steps.reverse();
+        if last != end {
+            steps.push(end);
         }
 
-        result
+        NavigationPath {
+            destination: end,
+            success: true,
+            steps,
+        }
     }
 
     #[inline]
-    fn close_enough(&self, q: &Node, map: &Region) -> bool {
-        let d = self.distance_to_end(q.idx, map);
-        let (_, _, ez) = idxmap(q.idx);
-        let (_, _, sz) = idxmap(self.end);
+    fn close_enough(idx: usize, end: usize, map: &Region) -> bool {
+        let d = map.get_pathing_distance(idx, end);
+        let (_, _, ez) = idxmap(idx);
+        let (_, _, sz) = idxmap(end);
         d < 1.4 && ez == sz
     }
 
     /// Performs an A-Star search
-    fn search(&mut self, map: &Region) -> NavigationPath {
-        let result = NavigationPath::new();
-        while !self.open_list.is_empty() && self.step_counter < MAX_ASTAR_STEPS {
-            self.step_counter += 1;
-
-            // Pop Q off of the list
-            let q = self.open_list.pop().unwrap();
-            if self.close_enough(&q, map) {
-                self.parents.insert(self.end, (q.idx, 0.0));
-                let success = self.found_it();
-                return success;
+    fn search(&mut self, start: usize, end: usize, map: &Region) -> NavigationPath {
+        self.reset();
+        self.open(start, start, 0.0, map.get_pathing_estimate(start, end));
+
+        let mut step_counter = 0;
+        while let Some(q) = self.open_list.pop() {
+            // Tiles can be queued more than once, if a cheaper way to them
+            // turns up; only the first (cheapest) one counts.
+            if self.is_closed(q.idx) {
+                continue;
             }
+            self.tiles[q.idx][STAMP] = self.generation + 1;
 
-            // Generate successors
-            map.get_available_exits(q.idx)
-                .iter()
-                .for_each(|s| self.add_successor(q, s.0, s.1 + q.f, map));
+            if Self::close_enough(q.idx, end, map) {
+                return self.found_it(start, q.idx, end);
+            }
+            step_counter += 1;
+            if step_counter >= MAX_ASTAR_STEPS {
+                break;
+            }
+
+            for (next, cost) in map.get_available_exits(q.idx) {
+                if self.is_closed(next) {
+                    continue;
+                }
+                let g = q.g + cost;
+                if !self.is_open(next) || g < self.g(next) {
+                    self.open(next, q.idx, g, map.get_pathing_estimate(next, end));
+                }
+            }
+        }
+        NavigationPath::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rebuild_flags, TileType};
+    use nox_spatial::mapidx;
+
+    fn at(x: usize, y: usize, z: usize) -> usize {
+        mapidx(x, y, z)
+    }
+
+    const Z: usize = 100;
+
+    /// An open cave floor from (10,10) to (40,20).
+    fn cave() -> Region {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for y in 10..=20 {
+            for x in 10..=40 {
+                region.set_tile_type(at(x, y, Z), TileType::Floor);
+            }
+        }
+        region
+    }
 
-            if self.closed_list.contains_key(&q.idx) {
-                self.closed_list.remove(&q.idx);
+    #[test]
+    fn paths_prefer_roads() {
+        let mut region = cave();
+        rebuild_flags(&mut region);
+        for x in 10..=40 {
+            region.set_flag(at(x, 13, Z), Region::CONSTRUCTED);
+        }
+        let path = a_star_search(at(10, 15, Z), at(40, 15, Z), &region);
+        assert!(path.success);
+        let on_road = path
+            .steps
+            .iter()
+            .filter(|idx| idxmap(**idx).1 == 13)
+            .count();
+        assert!(on_road > 20);
+    }
+
+    #[test]
+    fn paths_avoid_swimming() {
+        let mut region = cave();
+        // A river across the cave, with a dry crossing at the far end
+        for y in 11..=20 {
+            for x in 24..=26 {
+                region.set_water_level(at(x, y, Z), 10);
             }
-            self.closed_list.insert(q.idx, q.f);
         }
-        result
+        rebuild_flags(&mut region);
+        let path = a_star_search(at(10, 15, Z), at(40, 15, Z), &region);
+        assert!(path.success);
+        assert!(path.steps.contains(&at(25, 10, Z)));
+        assert!(path.steps.iter().all(|idx| region.water_level(*idx) == 0));
+    }
+
+    #[test]
+    fn diagonals_shorten_paths_but_dont_cut_corners() {
+        let mut region = cave();
+        region.set_tile_type(at(12, 11, Z), TileType::Wall);
+        rebuild_flags(&mut region);
+        assert!(region.flag(at(13, 13, Z), Region::CAN_GO_NORTH_EAST));
+        assert!(!region.flag(at(11, 11, Z), Region::CAN_GO_NORTH_EAST));
+        assert!(!region.flag(at(11, 10, Z), Region::CAN_GO_SOUTH_EAST));
+
+        let path = a_star_search(at(14, 12, Z), at(24, 20, Z), &region);
+        assert!(path.success);
+        assert!(path.steps.len() <= 12);
+    }
+
+    #[test]
+    fn ramps_are_walked_as_slopes() {
+        let mut region = cave();
+        // A ramp at the east end of the cave, up to a ledge on the level above
+        region.set_tile_type(
+            at(40, 15, Z),
+            TileType::Ramp {
+                direction: crate::RampDirection::WestEast,
+            },
+        );
+        region.set_tile_type(at(40, 15, Z + 1), TileType::Empty);
+        for x in 41..=45 {
+            region.set_tile_type(at(x, 15, Z + 1), TileType::Floor);
+        }
+        rebuild_flags(&mut region);
+
+        let up = a_star_search(at(30, 15, Z), at(44, 15, Z + 1), &region);
+        assert!(up.success);
+        let ramp = up
+            .steps
+            .iter()
+            .position(|idx| *idx == at(40, 15, Z))
+            .unwrap();
+        assert_eq!(up.steps[ramp + 1], at(41, 15, Z + 1));
+
+        let down = a_star_search(at(44, 15, Z + 1), at(30, 15, Z), &region);
+        assert!(down.success);
+        assert!(down.steps.contains(&at(40, 15, Z)));
     }
 }
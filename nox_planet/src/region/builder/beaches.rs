@@ -8,13 +8,13 @@ pub fn build_beaches(region: &mut Region) {
         for x in 1..REGION_WIDTH - 1 {
             let z = ground_z(region, x, y);
             let idx = mapidx(x, y, z);
-            if region.is_floor(idx) && region.water_level[idx] == 0 {
-                if region.water_level[mapidx(x, y - 1, z - 1)] > 0
-                    || region.water_level[mapidx(x, y + 1, z - 1)] > 0
-                    || region.water_level[mapidx(x - 1, y, z - 1)] > 0
-                    || region.water_level[mapidx(x + 1, y, z - 1)] > 0
+            if region.is_floor(idx) && region.water_level(idx) == 0 {
+                if region.water_level(mapidx(x, y - 1, z - 1)) > 0
+                    || region.water_level(mapidx(x, y + 1, z - 1)) > 0
+                    || region.water_level(mapidx(x - 1, y, z - 1)) > 0
+                    || region.water_level(mapidx(x + 1, y, z - 1)) > 0
                 {
-                    region.material_idx[idx] = yellow_sand;
+                    region.set_material(idx, yellow_sand);
                     // TODO: Clear vegetation
                 }
             }
@@ -26,7 +26,7 @@ pub fn build_escape_pod(region: &mut Region, crash_site: &Point, ecs: &mut World
                 let glyph = layer.get(x, y);
                 if let Some(glyph) = glyph {
                     if glyph.ch != 32 {
-                        region.revealed[tile_idx] = true;
+                        region.reveal(tile_idx);
                         match glyph.ch {
                             219 => add_construction(region, mx, my, mz, "ship_wall", true, ecs),
                             87 => add_construction(region, mx, my, mz, "ship_window", true, ecs),
@@ -77,8 +77,8 @@ fn add_construction(
 ) {
     let plasteel = get_material_by_tag("Plasteel").unwrap();
     let idx = mapidx(x, y, z);
-    region.tile_types[idx] = TileType::Floor;
-    region.material_idx[idx] = plasteel;
+    region.set_tile_type(idx, TileType::Floor);
+    region.set_material(idx, plasteel);
     region.set_flag(idx, Region::CONSTRUCTED);
     if solid {
         region.set_flag(idx, Region::SOLID);
@@ -97,30 +97,33 @@ fn add_construction(
 
     match name {
         "ship_wall" => {
-            region.tile_types[idx] = TileType::Solid;
+            region.set_tile_type(idx, TileType::Solid);
         }
         "ship_window" => {
-            region.tile_types[idx] = TileType::Solid;
+            region.set_tile_type(idx, TileType::Solid);
         }
         "ship_floor" => {}
-        "ship_updown" => {
-            region.tile_types[idx] = TileType::Stairs {
+        "ship_updown" => region.set_tile_type(
+            idx,
+            TileType::Stairs {
                 direction: StairsType::UpDown,
-            }
-        }
-        "ship_up" => {
-            region.tile_types[idx] = TileType::Stairs {
+            },
+        ),
+        "ship_up" => region.set_tile_type(
+            idx,
+            TileType::Stairs {
                 direction: StairsType::Up,
-            }
-        }
-        "ship_down" => {
-            region.tile_types[idx] = TileType::Stairs {
+            },
+        ),
+        "ship_down" => region.set_tile_type(
+            idx,
+            TileType::Stairs {
                 direction: StairsType::Down,
-            }
-        }
+            },
+        ),
         "solar_panel" => {
             add_building(region, "solar_panel", x, y, z, ecs);
-            region.tile_types[idx] = TileType::Window;
+            region.set_tile_type(idx, TileType::Window);
         }
         "battery" => {
             add_building(region, "battery", x, y, z, ecs);
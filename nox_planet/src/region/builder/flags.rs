@@ -1,35 +1,33 @@
-use crate::{Region, StairsType, TileType};
+use crate::{Region, StairsType, TileType, DIAGONAL_MOVEMENT};
 use nox_spatial::*;
+use std::collections::HashSet;
+
+/// The exit flags, which are always worked out together.
+const NAVIGATION_FLAGS: u16 = Region::CAN_GO_NORTH
+    | Region::CAN_GO_SOUTH
+    | Region::CAN_GO_EAST
+    | Region::CAN_GO_WEST
+    | Region::CAN_GO_UP
+    | Region::CAN_GO_DOWN
+    | Region::CAN_GO_NORTH_EAST
+    | Region::CAN_GO_NORTH_WEST
+    | Region::CAN_GO_SOUTH_EAST
+    | Region::CAN_GO_SOUTH_WEST
+    | Region::CAN_GO_UP_RAMP
+    | Region::CAN_GO_DOWN_RAMP;
 
 pub fn set_flags(region: &mut Region) {
     // Set the solid flag
-    for (idx, tt) in region.tile_types.clone().iter().enumerate() {
-        match tt {
-            TileType::SemiMoltenRock => region.set_flag(idx, Region::SOLID),
-            TileType::Solid => region.set_flag(idx, Region::SOLID),
-            TileType::Wall => region.set_flag(idx, Region::SOLID),
-            TileType::Window => region.set_flag(idx, Region::SOLID),
-            _ => {}
+    for idx in 0..REGION_TILES_COUNT {
+        if is_solid(region.tile_type(idx)) {
+            region.set_flag(idx, Region::SOLID);
         }
     }
 
     // Figure out which tiles are outdoors
     for y in 0..REGION_HEIGHT {
         for x in 0..REGION_WIDTH {
-            let mut blocked = false;
-            for z in (0..REGION_DEPTH).rev() {
-                let idx = mapidx(x, y, z);
-                if !blocked {
-                    region.set_flag(idx, Region::OUTSIDE);
-                }
-                match region.tile_types[idx] {
-                    TileType::Solid
-                    | TileType::SemiMoltenRock
-                    | TileType::Wall
-                    | TileType::Window => blocked = true,
-                    _ => {}
-                }
-            }
+            update_outside(region, x, y);
         }
     }
 
@@ -37,61 +35,8 @@ pub fn set_flags(region: &mut Region) {
     for z in 0..REGION_DEPTH {
         for y in 0..REGION_HEIGHT {
             for x in 0..REGION_WIDTH {
-                let idx = mapidx(x, y, z);
-                let mut can_stand = false;
-                if !region.flag(idx, Region::SOLID) {
-                    match region.tile_types[idx] {
-                        TileType::Floor { .. } => {
-                            can_stand = true;
-                        }
-                        TileType::Stairs { direction: StairsType::Up } => {
-                            can_stand = true;
-                            let up = mapidx(x, y, z + 1);
-                            if region.tile_types[up] == TileType::Empty { region.set_flag(up, Region::CAN_STAND_HERE); }
-                        }
-                        TileType::Stairs { direction: StairsType::Down } => {
-                            can_stand = true;
-                            let down = mapidx(x, y, z - 1);
-                            if region.tile_types[down] == TileType::Empty { region.set_flag(down, Region::CAN_STAND_HERE); }
-                        }
-                        TileType::Stairs { direction: StairsType::UpDown } => {
-                            can_stand = true;
-                            let down = mapidx(x, y, z - 1);
-                            if region.tile_types[down] == TileType::Empty { region.set_flag(down, Region::CAN_STAND_HERE); }
-                            let up = mapidx(x, y, z + 1);
-                            if region.tile_types[up] == TileType::Empty { region.set_flag(up, Region::CAN_STAND_HERE); }
-                        }
-                        TileType::Empty => {
-                            let down = mapidx(x, y, z - 1);
-                            if region.tile_types[down] == TileType::Solid {
-                                can_stand = true;
-                            }
-                        }
-                        TileType::Ramp { .. } => {
-                            can_stand = true;
-                            let up = mapidx(x, y, z + 1);
-                            region.set_flag(up, Region::CAN_STAND_HERE);
-                        }
-                        _ => {}
-                    }
-                    if z < REGION_DEPTH - 1 {
-                        let down_idx = mapidx(x, y, z + 1);
-                        match region.tile_types[down_idx] {
-                            TileType::Solid | TileType::Ramp { .. } => {
-                                can_stand = true;
-                            }
-                            TileType::Stairs { direction } => match direction {
-                                StairsType::Up | StairsType::UpDown => {
-                                    can_stand = true;
-                                }
-                                _ => {}
-                            },
-                            _ => {}
-                        }
-                    }
-                }
-                if can_stand {
-                    region.set_flag(idx, Region::CAN_STAND_HERE);
+                if can_stand(region, x, y, z) {
+                    region.set_flag(mapidx(x, y, z), Region::CAN_STAND_HERE);
                 }
             }
         }
@@ -101,191 +46,383 @@ pub fn set_flags(region: &mut Region) {
     for y in 0..REGION_HEIGHT {
         for x in 0..REGION_WIDTH {
             for z in 0..REGION_DEPTH {
-                let idx = mapidx(x, y, z);
-                if region.flag(idx, Region::CAN_STAND_HERE) {
-                    if x > 0 && valid_exit(region, x - 1, y, z) {
-                        region.set_flag(idx, Region::CAN_GO_WEST)
-                    }
-                    if x < REGION_WIDTH - 1 && valid_exit(region, x + 1, y, z) {
-                        region.set_flag(idx, Region::CAN_GO_EAST)
-                    }
-                    if y > 0 && valid_exit(region, x, y - 1, z) {
-                        region.set_flag(idx, Region::CAN_GO_NORTH)
-                    }
-                    if y < REGION_HEIGHT - 1 && valid_exit(region, x, y + 1, z) {
-                        region.set_flag(idx, Region::CAN_GO_SOUTH)
-                    }
-
-                    match region.tile_types[idx] {
-                        TileType::Ramp { .. } => {
-                            let up = mapidx(x, y, z + 1);
-                            region.set_flag(idx, Region::CAN_GO_UP);
-                            region.set_flag(up, Region::CAN_GO_DOWN);
-                            region.set_flag(idx, Region::CAN_STAND_HERE);
-                        }
-
-                        TileType::Stairs {
-                            direction: StairsType::Up,
-                        } => {
-                            if valid_exit(region, x, y, z + 1) {
-                                region.set_flag(idx, Region::CAN_GO_UP)
-                            }
-                        }
-                        TileType::Stairs {
-                            direction: StairsType::Down,
-                        } => {
-                            if valid_exit(region, x, y, z - 1) {
-                                region.set_flag(idx, Region::CAN_GO_DOWN)
-                            }
-                        }
-                        TileType::Stairs {
-                            direction: StairsType::UpDown,
-                        } => {
-                            if valid_exit(region, x, y, z + 1) {
-                                region.set_flag(idx, Region::CAN_GO_UP)
-                            }
-                            if valid_exit(region, x, y, z - 1) {
-                                region.set_flag(idx, Region::CAN_GO_DOWN)
-                            }
-                        }
-                        _ => {}
-                    }
+                let exits = exits(region, x, y, z);
+                if exits != 0 {
+                    region.set_flag(mapidx(x, y, z), exits);
                 }
             }
         }
     }
 }
 
-fn valid_exit(region: &Region, x: usize, y: usize, z: usize) -> bool {
-    if x > 0 && x < REGION_WIDTH && y > 0 && y < REGION_HEIGHT && z > 0 && z < REGION_DEPTH {
-        let idx = mapidx(x, y, z);
-        region.flag(idx, Region::CAN_STAND_HERE)
-    } else {
-        false
+/// Recalculates the flags affected by changes to the `dirty` tiles: their own,
+/// their neighbours', and the outdoor flags of the columns they are in. Much
+/// cheaper than `set_flags` when only a few tiles have changed.
+pub fn update_flags(region: &mut Region, dirty: &[usize]) {
+    let dirty: HashSet<usize> = dirty.iter().cloned().collect();
+
+    // Solidity only depends on the tile itself
+    let mut columns = HashSet::new();
+    for idx in dirty.iter() {
+        if is_solid(region.tile_type(*idx)) {
+            region.set_flag(*idx, Region::SOLID);
+        } else {
+            region.clear_flag(*idx, Region::SOLID);
+        }
+        let (x, y, _) = idxmap(*idx);
+        columns.insert((x, y));
+    }
+
+    // A tile changing can let light further down its column, or block it
+    for (x, y) in columns.iter() {
+        update_outside(region, *x, *y);
+    }
+
+    let standing = standing_affected_by(&dirty);
+    for idx in standing.iter() {
+        let (x, y, z) = idxmap(*idx);
+        if can_stand(region, x, y, z) {
+            region.set_flag(*idx, Region::CAN_STAND_HERE);
+        } else {
+            region.clear_flag(*idx, Region::CAN_STAND_HERE);
+        }
+    }
+
+    for idx in exits_affected_by(&standing).iter() {
+        let (x, y, z) = idxmap(*idx);
+        region.clear_flag(*idx, NAVIGATION_FLAGS);
+        let exits = exits(region, x, y, z);
+        if exits != 0 {
+            region.set_flag(*idx, exits);
+        }
     }
 }
 
-/*pub fn localized_flags(region: &mut Region, idx: usize) {
-    let (x, y, z) = idxmap(idx);
-
-    region.clear_flag(idx, Region::CAN_GO_DOWN);
-    region.clear_flag(idx, Region::CAN_GO_UP);
-    region.clear_flag(idx, Region::CAN_GO_NORTH);
-    region.clear_flag(idx, Region::CAN_GO_SOUTH);
-    region.clear_flag(idx, Region::CAN_GO_EAST);
-    region.clear_flag(idx, Region::CAN_GO_WEST);
-    region.clear_flag(idx, Region::CAN_STAND_HERE);
-    region.clear_flag(idx, Region::SOLID);
-    region.clear_flag(idx, Region::OUTSIDE);
-    region.clear_flag(idx, Region::CONSTRUCTED);
-
-    // Solidity
-    match region.tile_types[idx] {
-        TileType::SemiMoltenRock => region.set_flag(idx, Region::SOLID),
-        TileType::Solid => region.set_flag(idx, Region::SOLID),
-        TileType::Wall => region.set_flag(idx, Region::SOLID),
-        TileType::Window => region.set_flag(idx, Region::SOLID),
-        _ => {}
+/// Every tile whose standing or exit flags `update_flags` may change when the
+/// `dirty` tiles do, so that anything relying on them can be told.
+pub fn navigation_affected_by(dirty: &[usize]) -> HashSet<usize> {
+    let dirty: HashSet<usize> = dirty.iter().cloned().collect();
+    exits_affected_by(&standing_affected_by(&dirty))
+}
+
+/// Standing depends on the tiles directly above and below.
+fn standing_affected_by(dirty: &HashSet<usize>) -> HashSet<usize> {
+    neighbourhood(dirty, &[(0, 0, -1), (0, 0, 0), (0, 0, 1)])
+}
+
+/// Exits depend on whether the neighbours can be stood in.
+fn exits_affected_by(standing: &HashSet<usize>) -> HashSet<usize> {
+    let mut offsets = Vec::with_capacity(27);
+    for z in -1..=1 {
+        for y in -1..=1 {
+            for x in -1..=1 {
+                offsets.push((x, y, z));
+            }
+        }
     }
+    neighbourhood(standing, &offsets)
+}
+
+/// Works out the standing and exit flags again from scratch, leaving the others
+/// alone. For regions whose flags were set by an older version of the rules.
+pub(crate) fn rebuild_navigation_flags(region: &mut Region) {
+    for idx in 0..REGION_TILES_COUNT {
+        if region.flags.get(idx) & (NAVIGATION_FLAGS | Region::CAN_STAND_HERE) != 0 {
+            region.clear_flag(idx, NAVIGATION_FLAGS | Region::CAN_STAND_HERE);
+        }
+    }
+    set_flags(region);
+}
+
+/// The tiles at each of `offsets` from the `tiles`, that are inside the region.
+fn neighbourhood(tiles: &HashSet<usize>, offsets: &[(i32, i32, i32)]) -> HashSet<usize> {
+    let mut result = HashSet::with_capacity(tiles.len() * offsets.len());
+    for idx in tiles.iter() {
+        let (x, y, z) = idxmap(*idx);
+        for (dx, dy, dz) in offsets.iter() {
+            let (nx, ny, nz) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+            if nx >= 0
+                && ny >= 0
+                && nz >= 0
+                && nx < REGION_WIDTH as i32
+                && ny < REGION_HEIGHT as i32
+                && nz < REGION_DEPTH as i32
+            {
+                result.insert(mapidx(nx as usize, ny as usize, nz as usize));
+            }
+        }
+    }
+    result
+}
 
-    // Outdoor lighting
+fn is_solid(tile_type: TileType) -> bool {
+    matches!(
+        tile_type,
+        TileType::SemiMoltenRock | TileType::Solid | TileType::Wall | TileType::Window
+    )
+}
+
+/// Tiles are outside from the top of the map down to (and including) the
+/// first solid tile.
+fn update_outside(region: &mut Region, x: usize, y: usize) {
     let mut blocked = false;
     for z in (0..REGION_DEPTH).rev() {
         let idx = mapidx(x, y, z);
-        if !blocked {
+        if blocked {
+            region.clear_flag(idx, Region::OUTSIDE);
+        } else {
             region.set_flag(idx, Region::OUTSIDE);
         }
-        match region.tile_types[idx] {
-            TileType::Solid | TileType::SemiMoltenRock | TileType::Wall | TileType::Window => {
-                blocked = true
-            }
-            _ => {}
+        if is_solid(region.tile_type(idx)) {
+            blocked = true;
         }
     }
+}
+
+fn can_stand(region: &Region, x: usize, y: usize, z: usize) -> bool {
+    let tile_type = region.tile_type(mapidx(x, y, z));
+    let below = if z > 0 {
+        Some(region.tile_type(mapidx(x, y, z - 1)))
+    } else {
+        None
+    };
+    let above = if z < REGION_DEPTH - 1 {
+        Some(region.tile_type(mapidx(x, y, z + 1)))
+    } else {
+        None
+    };
+
+    // The tops of staircases
+    match below {
+        Some(TileType::Stairs {
+            direction: StairsType::Up,
+        })
+        | Some(TileType::Stairs {
+            direction: StairsType::UpDown,
+        }) if tile_type == TileType::Empty => return true,
+        _ => {}
+    }
+    match above {
+        Some(TileType::Stairs {
+            direction: StairsType::Down,
+        })
+        | Some(TileType::Stairs {
+            direction: StairsType::UpDown,
+        }) if tile_type == TileType::Empty => return true,
+        _ => {}
+    }
+
+    if is_solid(tile_type) {
+        return false;
+    }
+    match tile_type {
+        TileType::Floor { .. } | TileType::Stairs { .. } | TileType::Ramp { .. } => return true,
+        TileType::Empty if below == Some(TileType::Solid) => return true,
+        _ => {}
+    }
+    // Standing underneath solid ground
+    matches!(
+        above,
+        Some(TileType::Solid)
+            | Some(TileType::Ramp { .. })
+            | Some(TileType::Stairs {
+                direction: StairsType::Up,
+            })
+            | Some(TileType::Stairs {
+                direction: StairsType::UpDown,
+            })
+    )
+}
+
+/// The CAN_GO flags for a tile. Relies on CAN_STAND_HERE already being set.
+fn exits(region: &Region, x: usize, y: usize, z: usize) -> u16 {
+    let idx = mapidx(x, y, z);
+    let mut exits = 0;
+
+    if !region.flag(idx, Region::CAN_STAND_HERE) {
+        return exits;
+    }
+    if x > 0 && valid_exit(region, x - 1, y, z) {
+        exits |= Region::CAN_GO_WEST;
+    }
+    if x < REGION_WIDTH - 1 && valid_exit(region, x + 1, y, z) {
+        exits |= Region::CAN_GO_EAST;
+    }
+    if y > 0 && valid_exit(region, x, y - 1, z) {
+        exits |= Region::CAN_GO_NORTH;
+    }
+    if y < REGION_HEIGHT - 1 && valid_exit(region, x, y + 1, z) {
+        exits |= Region::CAN_GO_SOUTH;
+    }
 
-    // Standing
-    let mut can_stand = false;
-    if !region.flag(idx, Region::SOLID) {
-        match region.tile_types[idx] {
-            TileType::Floor { .. } | TileType::Stairs { .. } => {
-                can_stand = true;
+    // Diagonals, as long as they don't cut a corner
+    if DIAGONAL_MOVEMENT {
+        let diagonals = [
+            (1, -1, Region::CAN_GO_NORTH_EAST),
+            (-1, -1, Region::CAN_GO_NORTH_WEST),
+            (1, 1, Region::CAN_GO_SOUTH_EAST),
+            (-1, 1, Region::CAN_GO_SOUTH_WEST),
+        ];
+        for (dx, dy, flag) in diagonals.iter() {
+            let north_south = if *dy < 0 {
+                Region::CAN_GO_NORTH
+            } else {
+                Region::CAN_GO_SOUTH
+            };
+            let east_west = if *dx < 0 {
+                Region::CAN_GO_WEST
+            } else {
+                Region::CAN_GO_EAST
+            };
+            let (nx, ny) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+            if exits & north_south != 0 && exits & east_west != 0 && valid_exit(region, nx, ny, z) {
+                exits |= flag;
             }
-            TileType::Ramp { .. } => {
-                can_stand = true;
-                let up = mapidx(x, y, z + 1);
-                region.set_flag(up, Region::CAN_STAND_HERE);
+        }
+    }
+
+    // Ramps are slopes: their high end leads onto the level above, and back
+    if z < REGION_DEPTH - 1 {
+        if let TileType::Ramp { direction } = region.tile_type(idx) {
+            let (dx, dy) = direction.uphill();
+            if valid_exit(
+                region,
+                (x as i32 + dx) as usize,
+                (y as i32 + dy) as usize,
+                z + 1,
+            ) {
+                exits |= Region::CAN_GO_UP_RAMP;
             }
-            _ => {}
         }
-        if z < REGION_DEPTH - 1 {
-            let down_idx = mapidx(x, y, z + 1);
-            match region.tile_types[down_idx] {
-                TileType::Solid | TileType::Ramp { .. } => {
-                    can_stand = true;
+    }
+    if z > 0 && x > 0 && y > 0 && x < REGION_WIDTH - 1 && y < REGION_HEIGHT - 1 {
+        for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)].iter() {
+            let ramp = mapidx((x as i32 + dx) as usize, (y as i32 + dy) as usize, z - 1);
+            if let TileType::Ramp { direction } = region.tile_type(ramp) {
+                if direction.uphill() == (-dx, -dy) && region.flag(ramp, Region::CAN_STAND_HERE) {
+                    exits |= Region::CAN_GO_DOWN_RAMP;
                 }
-                TileType::Stairs { direction } => match direction {
-                    StairsType::Up | StairsType::UpDown => {
-                        can_stand = true;
-                    }
-                    _ => {}
-                },
-                _ => {}
             }
         }
     }
-    if can_stand {
-        region.set_flag(idx, Region::CAN_STAND_HERE);
-    }
 
-    // Navigation
-    if region.flag(idx, Region::CAN_STAND_HERE) {
-        if valid_exit(region, x - 1, y, z) {
-            region.set_flag(idx, Region::CAN_GO_WEST)
-        }
-        if valid_exit(region, x + 1, y, z) {
-            region.set_flag(idx, Region::CAN_GO_EAST)
+    match region.tile_type(idx) {
+        TileType::Stairs {
+            direction: StairsType::Up,
+        } => {
+            if valid_exit(region, x, y, z + 1) {
+                exits |= Region::CAN_GO_UP;
+            }
         }
-        if valid_exit(region, x, y - 1, z) {
-            region.set_flag(idx, Region::CAN_GO_NORTH)
+        TileType::Stairs {
+            direction: StairsType::Down,
+        } => {
+            if valid_exit(region, x, y, z - 1) {
+                exits |= Region::CAN_GO_DOWN;
+            }
         }
-        if valid_exit(region, x - 1, y + 1, z) {
-            region.set_flag(idx, Region::CAN_GO_SOUTH)
+        TileType::Stairs {
+            direction: StairsType::UpDown,
+        } => {
+            if valid_exit(region, x, y, z + 1) {
+                exits |= Region::CAN_GO_UP;
+            }
+            if valid_exit(region, x, y, z - 1) {
+                exits |= Region::CAN_GO_DOWN;
+            }
         }
+        _ => {}
+    }
+    exits
+}
 
-        match region.tile_types[idx] {
-            TileType::Ramp { .. } => {
-                let up = mapidx(x, y, z + 1);
-                region.set_flag(idx, Region::CAN_GO_UP);
-                region.set_flag(up, Region::CAN_GO_DOWN);
-                region.set_flag(idx, Region::CAN_STAND_HERE);
-            }
+fn valid_exit(region: &Region, x: usize, y: usize, z: usize) -> bool {
+    if x > 0 && x < REGION_WIDTH && y > 0 && y < REGION_HEIGHT && z > 0 && z < REGION_DEPTH {
+        let idx = mapidx(x, y, z);
+        region.flag(idx, Region::CAN_STAND_HERE)
+    } else {
+        false
+    }
+}
 
-            TileType::Stairs {
-                direction: StairsType::Up,
-            } => {
-                if valid_exit(region, x, y, z + 1) {
-                    region.set_flag(idx, Region::CAN_GO_UP)
-                }
-            }
-            TileType::Stairs {
-                direction: StairsType::Down,
-            } => {
-                if valid_exit(region, x, y, z - 1) {
-                    region.set_flag(idx, Region::CAN_GO_DOWN)
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: usize, y: usize, z: usize) -> usize {
+        mapidx(x, y, z)
+    }
+
+    fn cave() -> Region {
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        for y in 10..20 {
+            for x in 10..20 {
+                region.set_tile_type(at(x, y, 100), TileType::Floor);
+                region.set_tile_type(at(x, y, 101), TileType::Empty);
             }
+        }
+        region.set_tile_type(
+            at(12, 12, 100),
             TileType::Stairs {
-                direction: StairsType::UpDown,
-            } => {
-                if valid_exit(region, x, y, z + 1) {
-                    region.set_flag(idx, Region::CAN_GO_UP)
-                }
-                if valid_exit(region, x, y, z - 1) {
-                    region.set_flag(idx, Region::CAN_GO_DOWN)
-                }
-            }
-            _ => {}
+                direction: StairsType::Up,
+            },
+        );
+        set_flags(&mut region);
+        region
+    }
+
+    #[test]
+    fn update_matches_a_full_rebuild() {
+        let mut updated = cave();
+        let changes = [
+            (at(20, 15, 100), TileType::Floor),
+            (at(21, 15, 100), TileType::Floor),
+            (
+                at(12, 12, 101),
+                TileType::Stairs {
+                    direction: StairsType::UpDown,
+                },
+            ),
+            (at(12, 12, 102), TileType::Empty),
+            (at(15, 15, 101), TileType::Solid),
+            (
+                at(16, 16, 100),
+                TileType::Ramp {
+                    direction: crate::RampDirection::NorthSouth,
+                },
+            ),
+        ];
+        for (idx, tile_type) in changes.iter() {
+            updated.set_tile_type(*idx, *tile_type);
+        }
+        let dirty: Vec<usize> = changes.iter().map(|(idx, _)| *idx).collect();
+        update_flags(&mut updated, &dirty);
+
+        let mut rebuilt = updated.clone();
+        rebuilt.reset_all_flags();
+        set_flags(&mut rebuilt);
+
+        for idx in 0..REGION_TILES_COUNT {
+            assert_eq!(
+                updated.flags.get(idx),
+                rebuilt.flags.get(idx),
+                "Flags differ at {:?}",
+                idxmap(idx)
+            );
         }
     }
+
+    #[test]
+    fn update_keeps_constructed_flag() {
+        let mut region = cave();
+        let wall = at(14, 14, 100);
+        region.set_tile_type(wall, TileType::Wall);
+        region.set_flag(wall, Region::CONSTRUCTED);
+        update_flags(&mut region, &[wall]);
+
+        assert!(region.flag(wall, Region::CONSTRUCTED));
+        assert!(region.flag(wall, Region::SOLID));
+        assert!(!region.flag(wall, Region::CAN_STAND_HERE));
+    }
 }
-*/
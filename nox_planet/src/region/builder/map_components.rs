@@ -9,16 +9,15 @@ pub fn transform_terrain_to_ecs(region: &mut Region, ecs: &mut World) {
     let stairs_updown = RAWS.read().vox.get_model_idx("stairs_updown");
 
     region
-        .tile_types
-        .iter()
-        .enumerate()
-        .filter(|(_, tt)| {
+        .find_tiles(|tt| {
             match tt {
                 TileType::Stairs { .. } => true,
                 //TileType::Ramp{..} => true,
                 _ => false,
             }
         })
+        .iter()
+        .map(|idx| (*idx, region.tile_type(*idx)))
         .for_each(|(idx, tt)| {
             let model_id = match tt {
                 TileType::Stairs { direction } => match direction {
@@ -29,7 +28,7 @@ pub fn transform_terrain_to_ecs(region: &mut Region, ecs: &mut World) {
                 _ => 0,
             };
 
-            let tint = *RAWS.read().matmap.get(region.material_idx[idx]);
+            let tint = *RAWS.read().matmap.get(region.material(idx));
 
             ecs.push((
                 Terrain {},
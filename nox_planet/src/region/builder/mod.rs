@@ -18,8 +18,10 @@ mod water_features;
 use legion::*;
 pub use primitive::Primitive;
 mod flags;
-pub use flags::set_flags as rebuild_flags;
+pub(crate) use flags::rebuild_navigation_flags;
+pub use flags::{navigation_affected_by, set_flags as rebuild_flags, update_flags};
 use nox_raws::RAWS;
+pub use settlers::spawn_settler;
 
 pub fn builder(region: &mut Region, planet: &Planet, crash_site: Point) -> World {
     set_worldgen_status("Locating biome information");
@@ -21,8 +21,8 @@ pub fn grow_plants(
         for x in 0..REGION_WIDTH {
             let z = ground_z(region, x, y);
             let idx = mapidx(x, y, z);
-            if region.is_floor(idx) && region.water_level[idx] < 2 {
-                let soil_quality = match rlock.materials.materials[region.material_idx[idx]].layer {
+            if region.is_floor(idx) && region.water_level(idx) < 2 {
+                let soil_quality = match rlock.materials.materials[region.material(idx)].layer {
                     MaterialLayer::Sand => 1,
                     MaterialLayer::Soil { quality } => quality,
                     _ => 1,
@@ -8,29 +8,41 @@ pub fn build_ramps(region: &mut Region) {
             let idx = mapidx(x, y, z);
             if region.is_floor(idx) {
                 if region.is_floor(mapidx(x, y - 1, z + 1)) {
-                    region.tile_types[idx] = TileType::Ramp {
-                        direction: RampDirection::NorthSouth,
-                    };
-                    region.material_idx[idx] =
-                        region.material_idx[idx - (REGION_HEIGHT * REGION_HEIGHT)];
+                    region.set_tile_type(
+                        idx,
+                        TileType::Ramp {
+                            direction: RampDirection::NorthSouth,
+                        },
+                    );
+                    region
+                        .set_material(idx, region.material(idx - (REGION_HEIGHT * REGION_HEIGHT)));
                 } else if region.is_floor(mapidx(x, y + 1, z + 1)) {
-                    region.tile_types[idx] = TileType::Ramp {
-                        direction: RampDirection::SouthNorth,
-                    };
-                    region.material_idx[idx] =
-                        region.material_idx[idx - (REGION_HEIGHT * REGION_HEIGHT)];
+                    region.set_tile_type(
+                        idx,
+                        TileType::Ramp {
+                            direction: RampDirection::SouthNorth,
+                        },
+                    );
+                    region
+                        .set_material(idx, region.material(idx - (REGION_HEIGHT * REGION_HEIGHT)));
                 } else if region.is_floor(mapidx(x + 1, y, z + 1)) {
-                    region.tile_types[idx] = TileType::Ramp {
-                        direction: RampDirection::WestEast,
-                    };
-                    region.material_idx[idx] =
-                        region.material_idx[idx - (REGION_HEIGHT * REGION_HEIGHT)];
+                    region.set_tile_type(
+                        idx,
+                        TileType::Ramp {
+                            direction: RampDirection::WestEast,
+                        },
+                    );
+                    region
+                        .set_material(idx, region.material(idx - (REGION_HEIGHT * REGION_HEIGHT)));
                 } else if region.is_floor(mapidx(x - 1, y, z + 1)) {
-                    region.tile_types[idx] = TileType::Ramp {
-                        direction: RampDirection::EastWest,
-                    };
-                    region.material_idx[idx] =
-                        region.material_idx[idx - (REGION_HEIGHT * REGION_HEIGHT)];
+                    region.set_tile_type(
+                        idx,
+                        TileType::Ramp {
+                            direction: RampDirection::EastWest,
+                        },
+                    );
+                    region
+                        .set_material(idx, region.material(idx - (REGION_HEIGHT * REGION_HEIGHT)));
                 }
             }
         }
@@ -40,7 +40,9 @@ pub fn spawn_settlers(
     }
 }
 
-fn spawn_settler(
+/// Spawns a randomly generated settler, with their profession's clothing and
+/// equipment. Returns the settler's identity.
+pub fn spawn_settler(
     ecs: &mut World,
     rng: &mut RandomNumberGenerator,
     x: usize,
@@ -48,7 +50,7 @@ fn spawn_settler(
     z: usize,
     region_idx: usize,
     palette: Option<&Palette>,
-) {
+) -> usize {
     let species_def = RAWS.read().species.species[0].clone();
 
     let gender = if rng.roll_dice(1, 20) < 11 {
@@ -224,8 +226,7 @@ fn spawn_settler(
         Sentient {},
         id,
         Settler {
-            miner: false,
-            lumberjack: false,
+            labors: Labors::new(),
         },
     ));
 
@@ -257,4 +258,5 @@ fn spawn_settler(
     let plasteel = get_material_by_tag("Plasteel").unwrap();
     spawner::spawn_item_worn(ecs, "ray_pistol", settler_id, plasteel, palette);
     spawner::spawn_item_carried(ecs, "small_energy_cell", settler_id, plasteel, palette);
+    settler_id
 }
@@ -123,10 +123,7 @@ pub fn layer_cake(
     rng: &mut RandomNumberGenerator,
 ) {
     // Clear it
-    region
-        .tile_types
-        .iter_mut()
-        .for_each(|tt| *tt = TileType::Empty);
+    region.fill_tile_types(TileType::Empty);
 
     let soils = get_soil_indices();
 
@@ -140,18 +137,18 @@ pub fn layer_cake(
             };
 
             // Bottom layer is always SMR
-            region.tile_types[mapidx(x, y, 0)] = TileType::SemiMoltenRock;
+            region.set_tile_type(mapidx(x, y, 0), TileType::SemiMoltenRock);
 
             // Add lava above the bottom
             let mut z = 1;
             while z < altitude / 3 {
                 let cell_idx = mapidx(x, y, z);
                 if x == 0 || x == REGION_WIDTH - 1 || y == 0 || y == REGION_HEIGHT - 1 {
-                    region.tile_types[cell_idx] = TileType::SemiMoltenRock;
+                    region.set_tile_type(cell_idx, TileType::SemiMoltenRock);
                 } else {
-                    region.tile_types[cell_idx] = TileType::Empty;
+                    region.set_tile_type(cell_idx, TileType::Empty);
                     // Just add magma
-                    region.material_idx[cell_idx] = 0;
+                    region.set_material(cell_idx, 0);
                 }
                 z += 1;
             }
@@ -159,32 +156,34 @@ pub fn layer_cake(
             // Next is rock until the soil layer
             while z < altitude - 1 {
                 let cell_idx = mapidx(x, y, z);
-                region.tile_types[cell_idx] = TileType::Solid;
+                region.set_tile_type(cell_idx, TileType::Solid);
                 let mat_idx = strata.map[cell_idx];
-                region.material_idx[cell_idx] = strata.material_idx[mat_idx];
+                region.set_material(cell_idx, strata.material_idx[mat_idx]);
                 z += 1;
             }
 
             // Add a top floor
             let cell_idx = mapidx(x, y, z);
-            region.tile_types[cell_idx] = TileType::Floor;
+            region.set_tile_type(cell_idx, TileType::Floor);
             let mat_idx = strata.map[cell_idx];
-            region.material_idx[cell_idx] = *rng
-                .random_slice_entry(&soils)
-                .unwrap_or(&strata.material_idx[mat_idx]);
+            region.set_material(
+                cell_idx,
+                *rng.random_slice_entry(&soils)
+                    .unwrap_or(&strata.material_idx[mat_idx]),
+            );
 
             // Temporary reveal code
             //z -= 1;
             while z < REGION_DEPTH {
                 let cell_idx = mapidx(x, y, z);
-                region.revealed[cell_idx] = true;
+                region.reveal(cell_idx);
 
                 if x > 1 && x < REGION_WIDTH - 2 && y > 1 && y < REGION_HEIGHT - 2 {
                     for oy in -1..=1 {
                         for ox in -1..=1 {
                             let cell_idx =
                                 mapidx((x as i32 + ox) as usize, (y as i32 + oy) as usize, z);
-                            region.revealed[cell_idx] = true;
+                            region.reveal(cell_idx);
                         }
                     }
                 }
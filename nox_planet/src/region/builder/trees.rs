@@ -42,10 +42,10 @@ pub fn plant_trees(
             let idx = mapidx(x, y, z);
             if crash_distance > 20.0
                 && region.is_floor(idx)
-                && region.water_level[idx] == 0
+                && region.water_level(idx) == 0
                 && can_see_sky(region, x, y, z)
             {
-                let mat_idx = region.material_idx[idx];
+                let mat_idx = region.material(idx);
                 let floor_material = &RAWS.read().materials.materials[mat_idx];
                 let (can_plant, quality) = match floor_material.layer {
                     MaterialLayer::Sand => (true, 2.0),
@@ -91,7 +91,7 @@ pub fn plant_trees(
 fn can_see_sky(region: &Region, x: usize, y: usize, z: usize) -> bool {
     let mut sz = z + 1;
     while sz < REGION_DEPTH {
-        if region.tile_types[mapidx(x, y, sz)] != TileType::Empty {
+        if region.tile_type(mapidx(x, y, sz)) != TileType::Empty {
             return false;
         }
         sz += 1;
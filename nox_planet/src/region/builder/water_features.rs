@@ -202,10 +202,10 @@ pub fn set_water_tiles(region: &mut Region, water: &Vec<u8>, planet_water_level:
         for y in 0..REGION_HEIGHT {
             for x in 0..REGION_WIDTH {
                 let idx = mapidx(x, y, z);
-                if region.is_floor(idx) || region.tile_types[idx] == TileType::Empty {
+                if region.is_floor(idx) || region.tile_type(idx) == TileType::Empty {
                     let pool_idx = (y * REGION_WIDTH) + x;
                     if z <= water[pool_idx] as usize || z <= planet_water_level {
-                        region.water_level[idx] = 10;
+                        region.set_water_level(idx, 10);
                     }
                 }
             }
@@ -1,5 +1,6 @@
+use crate::pathfinding::{Connectivity, NavGraph};
 pub use crate::Planet;
-use nox_spatial::{idxmap, mapidx, REGION_TILES_COUNT};
+use nox_spatial::{idxmap, mapidx};
 use serde::{Deserialize, Serialize};
 mod tiletype;
 pub use tiletype::*;
@@ -7,79 +8,163 @@ mod builder;
 use bengine::geometry::*;
 pub use builder::*;
 use smallvec::SmallVec;
+use std::sync::Arc;
 mod mining_map;
 pub use mining_map::*;
 mod lumber_map;
 pub use lumber_map::*;
 mod construction_map;
 pub use construction_map::*;
+mod rally_map;
+pub use rally_map::*;
+mod storage;
+pub use storage::*;
+
+/// Cost of walking onto a paved (constructed) floor; the cheapest step there is.
+pub const ROAD_COST: f32 = 0.75;
+/// Extra cost of going up or down a level, by stairs or ramp.
+pub const CLIMBING_COST: f32 = 1.0;
+/// Extra cost of walking onto a tile for each level of water in it.
+pub const WADING_COST: f32 = 0.5;
+/// Water deeper than this has to be swum.
+pub const MAX_WADING_DEPTH: u8 = 4;
+pub const SWIMMING_COST: f32 = 10.0;
+/// Whether walkers may cut across diagonally. They can't cut corners: both of
+/// the tiles beside a diagonal step have to be open too.
+pub const DIAGONAL_MOVEMENT: bool = true;
+
+/// How far settlers will walk to get to work, when looking for it.
+pub const JOB_SEARCH_RADIUS: f32 = 2048.0;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Region {
     pub world_idx: usize,
-    pub tile_types: Vec<TileType>,
-    pub material_idx: Vec<usize>,
+    pub(crate) tile_types: ChunkedStorage<TileType>,
+    pub(crate) material_idx: ChunkedStorage<usize>,
     pub biome_info_idx: usize,
     pub biome_raw_idx: usize,
-    pub revealed: Vec<bool>,
-    pub water_level: Vec<u8>,
-    flags: Vec<u16>,
+    pub(crate) revealed: ChunkedStorage<bool>,
+    pub(crate) water_level: ChunkedStorage<u8>,
+    pub(crate) flags: ChunkedStorage<u16>,
+    #[serde(skip)]
+    pub(crate) navigation: Arc<NavGraph>,
+    #[serde(skip)]
+    pub(crate) connectivity: Arc<Connectivity>,
 }
 
 impl Region {
     pub fn zeroed(world_idx: usize, planet: &Planet) -> Self {
         Self {
             world_idx,
-            tile_types: vec![TileType::Empty; REGION_TILES_COUNT],
+            tile_types: ChunkedStorage::new(TileType::Empty),
             biome_info_idx: planet.landblocks[world_idx].biome_idx,
             biome_raw_idx: planet.biomes[planet.landblocks[world_idx].biome_idx].biome_type,
-            material_idx: vec![0; REGION_TILES_COUNT],
-            revealed: vec![false; REGION_TILES_COUNT],
-            water_level: vec![0; REGION_TILES_COUNT],
-            flags: vec![0u16; REGION_TILES_COUNT],
+            material_idx: ChunkedStorage::new(0),
+            revealed: ChunkedStorage::new(false),
+            water_level: ChunkedStorage::new(0),
+            flags: ChunkedStorage::new(0),
+            navigation: Arc::new(NavGraph::default()),
+            connectivity: Arc::new(Connectivity::default()),
         }
     }
 
     pub fn initial() -> Self {
         Self {
             world_idx: 0,
-            tile_types: vec![TileType::Empty; REGION_TILES_COUNT],
+            tile_types: ChunkedStorage::new(TileType::Empty),
             biome_info_idx: 0,
             biome_raw_idx: 0,
-            material_idx: vec![0; REGION_TILES_COUNT],
-            revealed: vec![false; REGION_TILES_COUNT],
-            water_level: vec![0; REGION_TILES_COUNT],
-            flags: vec![0u16; REGION_TILES_COUNT],
+            material_idx: ChunkedStorage::new(0),
+            revealed: ChunkedStorage::new(false),
+            water_level: ChunkedStorage::new(0),
+            flags: ChunkedStorage::new(0),
+            navigation: Arc::new(NavGraph::default()),
+            connectivity: Arc::new(Connectivity::default()),
         }
     }
 
+    #[inline]
+    pub fn tile_type(&self, idx: usize) -> TileType {
+        self.tile_types.get(idx)
+    }
+
+    pub fn set_tile_type(&mut self, idx: usize, tile_type: TileType) {
+        self.tile_types.set(idx, tile_type);
+    }
+
+    /// Sets every tile in the region to `tile_type`.
+    pub fn fill_tile_types(&mut self, tile_type: TileType) {
+        self.tile_types.fill(tile_type);
+    }
+
+    /// Indices of every tile whose type matches `predicate`.
+    pub fn find_tiles<F: Fn(&TileType) -> bool>(&self, predicate: F) -> Vec<usize> {
+        self.tile_types.find_all(predicate)
+    }
+
+    #[inline]
+    pub fn material(&self, idx: usize) -> usize {
+        self.material_idx.get(idx)
+    }
+
+    pub fn set_material(&mut self, idx: usize, material: usize) {
+        self.material_idx.set(idx, material);
+    }
+
+    #[inline]
+    pub fn is_revealed(&self, idx: usize) -> bool {
+        self.revealed.get(idx)
+    }
+
+    pub fn reveal(&mut self, idx: usize) {
+        self.revealed.set(idx, true);
+    }
+
+    #[inline]
+    pub fn water_level(&self, idx: usize) -> u8 {
+        self.water_level.get(idx)
+    }
+
+    pub fn set_water_level(&mut self, idx: usize, level: u8) {
+        self.water_level.set(idx, level);
+    }
+
+    /// Re-packs the tile storage; worth doing before a save.
+    pub fn compact(&mut self) {
+        self.tile_types.compact();
+        self.material_idx.compact();
+        self.revealed.compact();
+        self.water_level.compact();
+        self.flags.compact();
+    }
+
     #[inline]
     pub fn flag(&self, idx: usize, flag: u16) -> bool {
-        self.flags[idx] & flag > 0
+        self.flags.get(idx) & flag > 0
     }
 
     pub fn set_flag(&mut self, idx: usize, flag: u16) {
-        self.flags[idx] = self.flags[idx] | flag;
+        self.flags.set(idx, self.flags.get(idx) | flag);
     }
 
     pub fn clear_flag(&mut self, idx: usize, flag: u16) {
-        self.flags[idx] = self.flags[idx] & !flag;
+        self.flags.set(idx, self.flags.get(idx) & !flag);
     }
 
     pub fn reset_all_flags(&mut self) {
-        self.flags.iter_mut().for_each(|f| *f = 0);
+        self.flags.fill(0);
     }
 
     pub fn reset_all_flags_at(&mut self, idx: &usize) {
-        self.flags[*idx] = 0;
+        self.flags.set(*idx, 0);
     }
 
     pub fn reset_flags(&mut self, idx: usize) {
-        self.flags[idx] = 0;
+        self.flags.set(idx, 0);
     }
 
     pub fn is_floor(&self, idx: usize) -> bool {
-        match self.tile_types[idx] {
+        match self.tile_types.get(idx) {
             TileType::Floor { .. } => true,
             _ => false,
         }
@@ -96,34 +181,123 @@ impl Region {
     pub const CAN_GO_UP: u16 = 128;
     pub const CAN_GO_DOWN: u16 = 256;
     pub const CAN_STAND_HERE: u16 = 512;
+    pub const CAN_GO_NORTH_EAST: u16 = 1024;
+    pub const CAN_GO_NORTH_WEST: u16 = 2048;
+    pub const CAN_GO_SOUTH_EAST: u16 = 4096;
+    pub const CAN_GO_SOUTH_WEST: u16 = 8192;
+    /// Up a ramp, onto the level above at its high end.
+    pub const CAN_GO_UP_RAMP: u16 = 16384;
+    /// Down one or more ramps whose high end is next to this tile.
+    pub const CAN_GO_DOWN_RAMP: u16 = 32768;
 
-    pub fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
-        let mut exits = SmallVec::<[(usize, f32); 10]>::new();
+    pub fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 16]> {
+        let mut exits = SmallVec::<[(usize, f32); 16]>::new();
 
         let (x, y, z) = idxmap(idx);
 
         if self.flag(idx, Region::CAN_GO_NORTH) {
-            exits.push((mapidx(x, y - 1, z), 1.0))
+            exits.push(self.exit_to(idx, mapidx(x, y - 1, z)))
         }
         if self.flag(idx, Region::CAN_GO_SOUTH) {
-            exits.push((mapidx(x, y + 1, z), 1.0))
+            exits.push(self.exit_to(idx, mapidx(x, y + 1, z)))
         }
         if self.flag(idx, Region::CAN_GO_WEST) {
-            exits.push((mapidx(x - 1, y, z), 1.0))
+            exits.push(self.exit_to(idx, mapidx(x - 1, y, z)))
         }
         if self.flag(idx, Region::CAN_GO_EAST) {
-            exits.push((mapidx(x + 1, y, z), 1.0))
+            exits.push(self.exit_to(idx, mapidx(x + 1, y, z)))
         }
         if self.flag(idx, Region::CAN_GO_UP) {
-            exits.push((mapidx(x, y, z + 1), 1.0));
+            exits.push(self.exit_to(idx, mapidx(x, y, z + 1)));
         }
         if self.flag(idx, Region::CAN_GO_DOWN) {
-            exits.push((mapidx(x, y, z - 1), 1.0));
+            exits.push(self.exit_to(idx, mapidx(x, y, z - 1)));
+        }
+        if self.flag(idx, Region::CAN_GO_NORTH_EAST) {
+            exits.push(self.exit_to(idx, mapidx(x + 1, y - 1, z)));
+        }
+        if self.flag(idx, Region::CAN_GO_NORTH_WEST) {
+            exits.push(self.exit_to(idx, mapidx(x - 1, y - 1, z)));
+        }
+        if self.flag(idx, Region::CAN_GO_SOUTH_EAST) {
+            exits.push(self.exit_to(idx, mapidx(x + 1, y + 1, z)));
+        }
+        if self.flag(idx, Region::CAN_GO_SOUTH_WEST) {
+            exits.push(self.exit_to(idx, mapidx(x - 1, y + 1, z)));
+        }
+        if self.flag(idx, Region::CAN_GO_UP_RAMP) {
+            if let TileType::Ramp { direction } = self.tile_type(idx) {
+                let (dx, dy) = direction.uphill();
+                let top = mapidx((x as i32 + dx) as usize, (y as i32 + dy) as usize, z + 1);
+                exits.push(self.exit_to(idx, top));
+            }
+        }
+        if self.flag(idx, Region::CAN_GO_DOWN_RAMP) {
+            for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)].iter() {
+                let ramp = mapidx((x as i32 + dx) as usize, (y as i32 + dy) as usize, z - 1);
+                if let TileType::Ramp { direction } = self.tile_type(ramp) {
+                    if direction.uphill() == (-dx, -dy) {
+                        exits.push(self.exit_to(idx, ramp));
+                    }
+                }
+            }
         }
 
         exits
     }
 
+    /// Whether `to` is one of the exits from `from`, as things stand now.
+    pub fn can_step(&self, from: usize, to: usize) -> bool {
+        self.get_available_exits(from)
+            .iter()
+            .any(|(exit, _)| *exit == to)
+    }
+
+    #[inline]
+    fn exit_to(&self, from: usize, to: usize) -> (usize, f32) {
+        (to, self.step_cost(from, to))
+    }
+
+    /// What it costs to step from one tile to the next (which must be one of
+    /// its exits). Depends on the tile being stepped onto: paved floors are
+    /// quicker than bare ground, and water slows walkers down until they have
+    /// to swim. Diagonal steps cover more ground, and cost more to match;
+    /// climbing up or down a level costs extra. Never less than `ROAD_COST`
+    /// per tile crossed, which keeps `get_pathing_estimate` admissible.
+    pub fn step_cost(&self, from: usize, to: usize) -> f32 {
+        let mut cost = match self.water_level(to) {
+            0 => {
+                if self.flag(to, Region::CONSTRUCTED) && self.is_floor(to) {
+                    ROAD_COST
+                } else {
+                    1.0
+                }
+            }
+            level if level <= MAX_WADING_DEPTH => 1.0 + level as f32 * WADING_COST,
+            _ => SWIMMING_COST,
+        };
+        let (from_x, from_y, from_z) = idxmap(from);
+        let (to_x, to_y, to_z) = idxmap(to);
+        if from_x != to_x && from_y != to_y {
+            cost *= std::f32::consts::SQRT_2;
+        }
+        if from_z != to_z {
+            cost += CLIMBING_COST;
+        }
+        cost
+    }
+
+    /// Whether a walker at `start` could get to (or next to) `end`, without
+    /// searching for a path. See `pathfinding::Connectivity`.
+    pub fn are_connected(&self, start: usize, end: usize) -> bool {
+        self.connectivity.are_connected(start, end)
+    }
+
+    /// The connected component of walkable tiles that `idx` is part of.
+    pub fn component_id(&self, idx: usize) -> Option<u32> {
+        self.connectivity.component(idx)
+    }
+
     pub(crate) fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
         let (sx, sy, sz) = idxmap(idx1);
         let (ex, ey, ez) = idxmap(idx2);
@@ -131,4 +305,21 @@ impl Region {
         let pt2 = Point3::new(ex as i32, ey as i32, ez as i32);
         DistanceAlg::Pythagoras.distance3d(pt1, pt2)
     }
+
+    /// A* heuristic: the cheapest a trip between two tiles could possibly be,
+    /// which is walking there on a road, diagonally as far as that helps and
+    /// then straight. Each level climbed is at least one more step.
+    pub(crate) fn get_pathing_estimate(&self, idx1: usize, idx2: usize) -> f32 {
+        let (sx, sy, sz) = idxmap(idx1);
+        let (ex, ey, ez) = idxmap(idx2);
+        let dx = (sx as i32 - ex as i32).abs() as f32;
+        let dy = (sy as i32 - ey as i32).abs() as f32;
+        let dz = (sz as i32 - ez as i32).abs() as f32;
+        let flat = if DIAGONAL_MOVEMENT {
+            f32::max(dx, dy) + (std::f32::consts::SQRT_2 - 1.0) * f32::min(dx, dy)
+        } else {
+            dx + dy
+        };
+        (flat + dz) * ROAD_COST
+    }
 }
@@ -0,0 +1,27 @@
+use crate::pathfinding::DijkstraMap;
+
+/// Where settlers have been called to gather, if anywhere, and the map that
+/// takes them there. Rallies are called off when a game is saved.
+pub struct RallyMap {
+    pub is_dirty: bool,
+    pub rally_point: Option<usize>,
+    pub dijkstra: DijkstraMap,
+}
+
+impl RallyMap {
+    pub fn new() -> Self {
+        Self {
+            is_dirty: false,
+            rally_point: None,
+            dijkstra: DijkstraMap::new(),
+        }
+    }
+
+    /// Calls everyone to `idx`, or (with `None`) stands them down.
+    pub fn set_rally_point(&mut self, idx: Option<usize>) {
+        if self.rally_point != idx {
+            self.rally_point = idx;
+            self.is_dirty = true;
+        }
+    }
+}
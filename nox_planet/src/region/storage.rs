@@ -0,0 +1,286 @@
+use nox_spatial::{idxmap, mapidx, REGION_DEPTH, REGION_HEIGHT, REGION_TILES_COUNT, REGION_WIDTH};
+use serde::{Deserialize, Serialize};
+
+/// Width, height and depth of a storage chunk.
+pub const STORAGE_CHUNK_SIZE: usize = 16;
+const CHUNK_TILES: usize = STORAGE_CHUNK_SIZE * STORAGE_CHUNK_SIZE * STORAGE_CHUNK_SIZE;
+const CHUNKS_X: usize = REGION_WIDTH / STORAGE_CHUNK_SIZE;
+const CHUNKS_Y: usize = REGION_HEIGHT / STORAGE_CHUNK_SIZE;
+const CHUNKS_Z: usize = REGION_DEPTH / STORAGE_CHUNK_SIZE;
+const CHUNK_COUNT: usize = CHUNKS_X * CHUNKS_Y * CHUNKS_Z;
+
+#[derive(Clone, Serialize, Deserialize)]
+enum StorageChunk<T> {
+    /// Every tile in the chunk has the same value (solid rock, open air).
+    Uniform(T),
+    /// Up to 256 distinct values, with a byte per tile pointing into the palette.
+    Palette { palette: Vec<T>, indices: Vec<u8> },
+    /// Too varied to be worth compressing.
+    Dense(Vec<T>),
+}
+
+/// Per-tile region data, stored in 16x16x16 chunks. Most of a region is
+/// uniform rock or air, so chunks are kept as a single value or a small palette
+/// and only expanded when they get too varied. Indices are the usual `mapidx`
+/// tile indices, so this is a drop-in replacement for a `Vec` of
+/// `REGION_TILES_COUNT` entries.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChunkedStorage<T> {
+    chunks: Vec<StorageChunk<T>>,
+}
+
+#[inline(always)]
+fn split_idx(idx: usize) -> (usize, usize) {
+    let (x, y, z) = idxmap(idx);
+    let chunk = ((z / STORAGE_CHUNK_SIZE) * CHUNKS_Y * CHUNKS_X)
+        + ((y / STORAGE_CHUNK_SIZE) * CHUNKS_X)
+        + (x / STORAGE_CHUNK_SIZE);
+    let local = ((z % STORAGE_CHUNK_SIZE) * STORAGE_CHUNK_SIZE * STORAGE_CHUNK_SIZE)
+        + ((y % STORAGE_CHUNK_SIZE) * STORAGE_CHUNK_SIZE)
+        + (x % STORAGE_CHUNK_SIZE);
+    (chunk, local)
+}
+
+#[inline(always)]
+fn join_idx(chunk: usize, local: usize) -> usize {
+    let cx = chunk % CHUNKS_X;
+    let cy = (chunk / CHUNKS_X) % CHUNKS_Y;
+    let cz = chunk / (CHUNKS_X * CHUNKS_Y);
+    let lx = local % STORAGE_CHUNK_SIZE;
+    let ly = (local / STORAGE_CHUNK_SIZE) % STORAGE_CHUNK_SIZE;
+    let lz = local / (STORAGE_CHUNK_SIZE * STORAGE_CHUNK_SIZE);
+    mapidx(
+        cx * STORAGE_CHUNK_SIZE + lx,
+        cy * STORAGE_CHUNK_SIZE + ly,
+        cz * STORAGE_CHUNK_SIZE + lz,
+    )
+}
+
+impl<T: Copy + PartialEq> StorageChunk<T> {
+    #[inline(always)]
+    fn get(&self, local: usize) -> T {
+        match self {
+            StorageChunk::Uniform(v) => *v,
+            StorageChunk::Palette { palette, indices } => palette[indices[local] as usize],
+            StorageChunk::Dense(values) => values[local],
+        }
+    }
+
+    fn set(&mut self, local: usize, value: T) {
+        match self {
+            StorageChunk::Uniform(v) => {
+                if *v != value {
+                    let mut indices = vec![0u8; CHUNK_TILES];
+                    indices[local] = 1;
+                    *self = StorageChunk::Palette {
+                        palette: vec![*v, value],
+                        indices,
+                    };
+                }
+            }
+            StorageChunk::Palette { palette, indices } => {
+                if let Some(pos) = palette.iter().position(|p| *p == value) {
+                    indices[local] = pos as u8;
+                } else if palette.len() < 256 {
+                    indices[local] = palette.len() as u8;
+                    palette.push(value);
+                } else {
+                    let mut values: Vec<T> = indices.iter().map(|i| palette[*i as usize]).collect();
+                    values[local] = value;
+                    *self = StorageChunk::Dense(values);
+                }
+            }
+            StorageChunk::Dense(values) => values[local] = value,
+        }
+    }
+
+    fn from_values(values: &[T]) -> Self {
+        let mut palette: Vec<T> = Vec::new();
+        let mut indices = Vec::with_capacity(CHUNK_TILES);
+        for v in values.iter() {
+            if let Some(pos) = palette.iter().position(|p| p == v) {
+                indices.push(pos as u8);
+            } else if palette.len() < 256 {
+                indices.push(palette.len() as u8);
+                palette.push(*v);
+            } else {
+                return StorageChunk::Dense(values.to_vec());
+            }
+        }
+        if palette.len() == 1 {
+            StorageChunk::Uniform(palette[0])
+        } else {
+            StorageChunk::Palette { palette, indices }
+        }
+    }
+
+    fn values(&self) -> Vec<T> {
+        (0..CHUNK_TILES).map(|i| self.get(i)).collect()
+    }
+}
+
+impl<T: Copy + PartialEq> ChunkedStorage<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            chunks: vec![StorageChunk::Uniform(value); CHUNK_COUNT],
+        }
+    }
+
+    /// Builds storage from a dense, `mapidx` ordered slice.
+    pub fn from_dense(values: &[T]) -> Self {
+        debug_assert!(values.len() == REGION_TILES_COUNT);
+        let mut buffer = Vec::with_capacity(CHUNK_TILES);
+        let chunks = (0..CHUNK_COUNT)
+            .map(|chunk| {
+                buffer.clear();
+                buffer.extend((0..CHUNK_TILES).map(|local| values[join_idx(chunk, local)]));
+                StorageChunk::from_values(&buffer)
+            })
+            .collect();
+        Self { chunks }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, idx: usize) -> T {
+        let (chunk, local) = split_idx(idx);
+        self.chunks[chunk].get(local)
+    }
+
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: T) {
+        let (chunk, local) = split_idx(idx);
+        self.chunks[chunk].set(local, value);
+    }
+
+    /// Sets every tile to `value`.
+    pub fn fill(&mut self, value: T) {
+        self.chunks
+            .iter_mut()
+            .for_each(|c| *c = StorageChunk::Uniform(value));
+    }
+
+    /// Returns the index of every tile matching `predicate`, in ascending
+    /// order. Uniform chunks are tested once, so this is much cheaper than
+    /// visiting every tile.
+    pub fn find_all<F: Fn(&T) -> bool>(&self, predicate: F) -> Vec<usize> {
+        let mut result = Vec::new();
+        for (chunk_idx, chunk) in self.chunks.iter().enumerate() {
+            match chunk {
+                StorageChunk::Uniform(v) => {
+                    if predicate(v) {
+                        result.extend((0..CHUNK_TILES).map(|local| join_idx(chunk_idx, local)));
+                    }
+                }
+                StorageChunk::Palette { palette, indices } => {
+                    let matches: Vec<bool> = palette.iter().map(&predicate).collect();
+                    if matches.iter().any(|m| *m) {
+                        indices
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, i)| matches[**i as usize])
+                            .for_each(|(local, _)| result.push(join_idx(chunk_idx, local)));
+                    }
+                }
+                StorageChunk::Dense(values) => values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| predicate(v))
+                    .for_each(|(local, _)| result.push(join_idx(chunk_idx, local))),
+            }
+        }
+        // Chunk order isn't index order; callers expect the order a `Vec` gives
+        result.sort_unstable();
+        result
+    }
+
+    /// Re-packs chunks that have become simpler than their representation
+    /// (e.g. a palette that only has one value left in use).
+    pub fn compact(&mut self) {
+        for chunk in self.chunks.iter_mut() {
+            if !matches!(chunk, StorageChunk::Uniform(_)) {
+                *chunk = StorageChunk::from_values(&chunk.values());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_uniform<T>(chunk: &StorageChunk<T>) -> bool {
+        matches!(chunk, StorageChunk::Uniform(_))
+    }
+
+    fn is_dense<T>(chunk: &StorageChunk<T>) -> bool {
+        matches!(chunk, StorageChunk::Dense(_))
+    }
+
+    #[test]
+    fn too_many_values_overflow_to_dense() {
+        let mut storage = ChunkedStorage::new(0usize);
+        // Tiles of the first chunk, in its own order
+        let idx = |i: usize| join_idx(0, i);
+        for i in 1..256 {
+            storage.set(idx(i), i);
+        }
+        assert!(!is_dense(&storage.chunks[0]));
+
+        storage.set(idx(256), 256);
+        assert!(is_dense(&storage.chunks[0]));
+        (0..=256).for_each(|i| assert_eq!(storage.get(idx(i)), i));
+        assert_eq!(storage.get(idx(257)), 0);
+    }
+
+    #[test]
+    fn compact_returns_chunks_to_uniform() {
+        let mut storage = ChunkedStorage::new(false);
+        let idx = mapidx(20usize, 20, 20);
+        let (chunk, _) = split_idx(idx);
+        storage.set(idx, true);
+        assert!(!is_uniform(&storage.chunks[chunk]));
+
+        storage.set(idx, false);
+        storage.compact();
+        assert!(storage.chunks.iter().all(is_uniform));
+        assert!(!storage.get(idx));
+    }
+
+    #[test]
+    fn find_all_matches_a_dense_scan() {
+        let mut dense = vec![0u8; REGION_TILES_COUNT];
+        for (i, tile) in dense.iter_mut().enumerate() {
+            if i % 7919 == 0 {
+                *tile = 1;
+            }
+        }
+        // A whole matching chunk, so the uniform shortcut is used too
+        for z in 32..48usize {
+            for y in 0..16 {
+                for x in 16..32 {
+                    dense[mapidx(x, y, z)] = 1;
+                }
+            }
+        }
+        let storage = ChunkedStorage::from_dense(&dense);
+
+        let expected: Vec<usize> = (0..REGION_TILES_COUNT).filter(|i| dense[*i] == 1).collect();
+        assert_eq!(storage.find_all(|v| *v == 1), expected);
+    }
+
+    #[test]
+    fn mostly_uniform_regions_are_much_smaller_than_dense() {
+        let mut dense = vec![0u16; REGION_TILES_COUNT];
+        for x in 0..REGION_WIDTH {
+            for y in 0..REGION_HEIGHT {
+                dense[mapidx(x, y, 128)] = 1 + ((x + y) % 3) as u16;
+            }
+        }
+        let storage = ChunkedStorage::from_dense(&dense);
+        (0..REGION_TILES_COUNT).for_each(|i| assert_eq!(storage.get(i), dense[i]));
+
+        let chunked_size = bincode::serialize(&storage).unwrap().len();
+        let dense_size = bincode::serialize(&dense).unwrap().len();
+        assert!(chunked_size * 10 < dense_size);
+    }
+}
@@ -20,6 +20,18 @@ pub enum RampDirection {
     WestEast,
 }
 
+impl RampDirection {
+    /// The (x, y) step that walks up the ramp, onto the level above.
+    pub fn uphill(&self) -> (i32, i32) {
+        match self {
+            RampDirection::NorthSouth => (0, -1),
+            RampDirection::SouthNorth => (0, 1),
+            RampDirection::WestEast => (1, 0),
+            RampDirection::EastWest => (-1, 0),
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum StairsType {
     Up,
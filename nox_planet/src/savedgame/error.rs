@@ -0,0 +1,58 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    NotFound(String),
+    NotASaveFile,
+    TooNew { version: u32 },
+    Decompress,
+    Encode(String),
+    Decode(String),
+    Migration { from: u32, reason: String },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "Unable to access the save file: {}", e),
+            SaveError::NotFound(slot) => write!(f, "There is no saved game called '{}'", slot),
+            SaveError::NotASaveFile => write!(f, "This file isn't a Nox Futura saved game"),
+            SaveError::TooNew { version } => write!(
+                f,
+                "This game was saved by a newer version of Nox Futura (format {}, we understand up to {})",
+                version,
+                super::SAVE_FORMAT_VERSION
+            ),
+            SaveError::Decompress => write!(f, "The saved game is corrupted (unable to decompress)"),
+            SaveError::Encode(e) => write!(f, "Unable to encode the game: {}", e),
+            SaveError::Decode(e) => write!(f, "The saved game is corrupted: {}", e),
+            SaveError::Migration { from, reason } => write!(
+                f,
+                "Unable to upgrade a save from format {}: {}",
+                from, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for SaveError {
+    fn from(e: bincode::Error) -> Self {
+        SaveError::Decode(e.to_string())
+    }
+}
@@ -0,0 +1,486 @@
+//! The components as `world.dat` stored them. The ECS was written as RON, so
+//! these keep the names (and shapes) that the old registry used; they must
+//! never change along with the live types.
+use legion::storage::Component;
+use legion::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Attributes {
+    pub str: i32,
+    pub dex: i32,
+    pub con: i32,
+    pub int: i32,
+    pub wis: i32,
+    pub cha: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Skills(pub HashMap<Skill, i32>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub(super) enum Skill {
+    Lumberjack,
+    Mining,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Initiative {
+    pub initiative: i32,
+    pub modifier: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Material(pub usize);
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Storage {}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Tool {
+    pub usage: ToolType,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum ToolType {
+    Chopping,
+    Digging,
+    Farming,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CameraOptions {
+    pub zoom_level: i32,
+    pub mode: CameraMode,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CameraMode {
+    TopDown,
+    Front,
+    DiagonalNW,
+    DiagonalNE,
+    DiagonalSW,
+    DiagonalSE,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct VoxLayer {
+    pub model: usize,
+    pub tint: (f32, f32, f32),
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CompositeRender {
+    pub layers: Vec<VoxLayer>,
+    pub rotation: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Light {
+    pub color: (f32, f32, f32),
+    pub radius: usize,
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Tint {
+    pub color: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct VoxelModel {
+    pub index: usize,
+    pub rotation_radians: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Position {
+    pub loc: Location,
+    pub region_idx: usize,
+    pub dimensions: (i32, i32, i32),
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum Location {
+    Tile { idx: usize },
+    Stored { container: usize },
+    Carried { by: usize },
+    Worn { by: usize },
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Cordex {}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Building {
+    pub complete: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Workshop {
+    pub has_automatic_jobs: bool,
+    pub queued_autojob: Option<ReactionJob>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Item {}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Sentient {}
+
+/// Before they had labors, settlers could only be hired as miners and
+/// lumberjacks.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(super) struct Settler {
+    pub miner: bool,
+    pub lumberjack: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Vegetation {
+    pub size: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Tree {
+    pub chop: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Terrain {}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Tag(pub String);
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Calendar {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct MyTurn {
+    pub active: bool,
+    pub shift: ScheduleTime,
+    pub job: JobType,
+    pub order: WorkOrder,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum WorkOrder {
+    None,
+    MoveRandomly,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum JobType {
+    None,
+    CollectTool {
+        tool_id: usize,
+        step: CollectToolSteps,
+    },
+    Haul {
+        item_id: usize,
+        step: HaulSteps,
+    },
+    FellTree {
+        tool_id: Option<usize>,
+        step: LumberjackSteps,
+    },
+    ConstructBuilding {
+        building_id: usize,
+        step: BuildingSteps,
+    },
+    Mining {
+        step: MiningSteps,
+        tool_id: Option<usize>,
+    },
+    Reaction {
+        reaction_id: usize,
+        reaction_location: usize,
+        step: ReactionSteps,
+    },
+    Construct {
+        building_id: usize,
+        step: ConstructionSteps,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum CollectToolSteps {
+    TravelToTool { path: Vec<usize> },
+    CollectTool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum HaulSteps {
+    FindItem,
+    TravelToItem { path: Vec<usize> },
+    CollectItem,
+    TravelToDestination { path: Vec<usize> },
+    DropItem,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum LumberjackSteps {
+    FindAxe,
+    FindTree,
+    ChopTree,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum MiningSteps {
+    FindPick,
+    TravelToMine,
+    Dig,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum BuildingSteps {
+    FindBuilding,
+    TravelToBuilding { path: Vec<usize> },
+    Construct,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum ConstructionSteps {
+    FindBuilding,
+    TravelToBuilding { path: Vec<usize> },
+    Construct,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum ReactionSteps {
+    FindReaction,
+    TravelToReaction { path: Vec<usize> },
+    PerformReaction,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(super) enum ScheduleTime {
+    Work,
+    Sleep,
+    Leisure,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct WorkSchedule {
+    pub hours: [ScheduleTime; 24],
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Species {
+    pub gender: Gender,
+    pub gender_identity: GenderIdentity,
+    pub sexuality: Sexuality,
+    pub height_cm: f32,
+    pub weight_kg: f32,
+    pub bearded: bool,
+    pub skin_color: (f32, f32, f32),
+    pub hair_color: (f32, f32, f32),
+    pub hair_style: HairStyle,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum Gender {
+    Male,
+    Female,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum GenderIdentity {
+    Male,
+    Female,
+    Neutral,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum Sexuality {
+    Heterosexual,
+    Pansexual,
+    Homosexual,
+    ASexual,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum HairStyle {
+    Bald,
+    Balding,
+    Mohawk,
+    ShortHair,
+    LongHair,
+    Pigtails,
+    Triangle,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Name {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Description {
+    pub desc: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Tagline {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct FieldOfView {
+    pub is_dirty: bool,
+    pub radius: usize,
+    pub visible_tiles: HashSet<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct IdentityTag(pub usize);
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ObjModel {
+    pub index: usize,
+    pub rotation_radians: f32,
+    pub scale: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Claimed {
+    pub by: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Health {
+    pub max: i32,
+    pub current: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum MiningMode {
+    Dig,
+    Channel,
+    Ramp,
+    Up,
+    Down,
+    UpDown,
+    Clear,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct RequestHaul {
+    pub destination: usize,
+    pub in_progress: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Blueprint {
+    pub required_items: Vec<usize>,
+    pub ready_to_build: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ReactionJob {
+    pub reaction_tag: String,
+    pub workshop_id: usize,
+    pub in_progress: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Construction {
+    pub mode: usize,
+    pub in_progress: Option<usize>,
+}
+
+/// Replaces every `Old` component with the live type of the same name, by
+/// writing it out as RON and reading it back in. A live type that can no
+/// longer read its old RON fails the import rather than guessing; give it a
+/// conversion of its own (as `Settler` has) instead.
+fn upgrade<Old, New>(world: &mut World) -> Result<(), String>
+where
+    Old: Component + Serialize,
+    New: Component + DeserializeOwned,
+{
+    let old = <(Entity, &Old)>::query()
+        .iter(world)
+        .map(|(entity, c)| ron::to_string(c).map(|text| (*entity, text)))
+        .collect::<Result<Vec<(Entity, String)>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (entity, text) in old {
+        let new: New =
+            ron::from_str(&text).map_err(|e| format!("{}: {}", std::any::type_name::<New>(), e))?;
+        if let Some(mut entry) = world.entry(entity) {
+            entry.remove_component::<Old>();
+            entry.add_component(new);
+        }
+    }
+    Ok(())
+}
+
+macro_rules! unchanged_components {
+    ($( $type:ident),*) => {
+        pub(super) fn register_unchanged(registry: &mut Registry<String>) {
+            $(
+                registry.register::<$type>(stringify!($type).to_string());
+            )*
+        }
+
+        pub(super) fn upgrade_unchanged(world: &mut World) -> Result<(), String> {
+            $(
+                upgrade::<$type, nox_components::$type>(world)?;
+            )*
+            Ok(())
+        }
+    };
+}
+
+// Every component but `Settler`, whose RON the live types can still read.
+// `IdentityTag` goes last: until then, every entity still has a frozen
+// component, so the archetypes holding the live tag are the finished ones.
+unchanged_components!(
+    Attributes,
+    Skills,
+    Initiative,
+    Material,
+    Storage,
+    Tool,
+    CameraOptions,
+    VoxLayer,
+    CompositeRender,
+    Light,
+    Tint,
+    VoxelModel,
+    Position,
+    Cordex,
+    Building,
+    Workshop,
+    Item,
+    Sentient,
+    Vegetation,
+    Tree,
+    Terrain,
+    Tag,
+    Calendar,
+    MyTurn,
+    Species,
+    Name,
+    Description,
+    Tagline,
+    FieldOfView,
+    WorkSchedule,
+    ObjModel,
+    Claimed,
+    Health,
+    MiningMode,
+    RequestHaul,
+    Blueprint,
+    ReactionJob,
+    Construction,
+    IdentityTag
+);
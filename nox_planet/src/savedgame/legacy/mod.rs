@@ -0,0 +1,287 @@
+use super::{SaveError, SavedGame};
+use crate::{rebuild_navigation_flags, ChunkedStorage, Region};
+use legion::*;
+use nox_components::{serialize_world_binary, IdentityTag, Labor, Labors, Settler};
+use serde::{Deserialize, Serialize};
+mod components;
+mod planet;
+use planet::{DenseRegion, Planet};
+
+/// Where the game was saved before there were save slots.
+pub const LEGACY_SAVE_FILE: &str = "world.dat";
+
+/// `world.dat` has no version of its own; errors report it as format 0.
+const LEGACY_FORMAT: u32 = 0;
+
+/// The whole file was a deflated bincode `SavedGame`, with the ECS as RON.
+/// Everything in it is read with the frozen copies in `planet` and
+/// `components`, which must not change along with the live types.
+#[derive(Serialize, Deserialize)]
+struct LegacySavedGame {
+    planet: Planet,
+    current_region: DenseRegion,
+    ecs_text: String,
+}
+
+/// The components `world.dat` could contain, under the names it used.
+fn legacy_registry() -> Registry<String> {
+    let mut registry = Registry::default();
+    components::register_unchanged(&mut registry);
+    registry.register::<components::Settler>("Settler".to_string());
+    registry
+}
+
+fn import_error<E: ToString>(e: E) -> SaveError {
+    SaveError::Migration {
+        from: LEGACY_FORMAT,
+        reason: e.to_string(),
+    }
+}
+
+/// Converts the (decompressed) contents of a `world.dat` into a current saved
+/// game, returning the ECS as well so that a header can be made for it.
+pub(crate) fn import(body: &[u8]) -> Result<(SavedGame, World), SaveError> {
+    let old: LegacySavedGame = bincode::deserialize(body).map_err(import_error)?;
+    let ecs = import_world(&old.ecs_text).map_err(import_error)?;
+    let ecs_data = serialize_world_binary(&ecs).map_err(import_error)?;
+    let saved = SavedGame {
+        planet: old.planet.into(),
+        current_region: import_region(old.current_region),
+        ecs_data,
+        play_state_text: None,
+    };
+    Ok((saved, ecs))
+}
+
+/// Regions are stored in chunks now, and diagonal steps and sloped ramps mean
+/// that the navigation flags have to be worked out again.
+fn import_region(dense: DenseRegion) -> Region {
+    let tile_types: Vec<crate::TileType> = dense.tile_types.into_iter().map(|t| t.into()).collect();
+    let mut region = Region {
+        world_idx: dense.world_idx,
+        tile_types: ChunkedStorage::from_dense(&tile_types),
+        material_idx: ChunkedStorage::from_dense(&dense.material_idx),
+        biome_info_idx: dense.biome_info_idx,
+        biome_raw_idx: dense.biome_raw_idx,
+        revealed: ChunkedStorage::from_dense(&dense.revealed),
+        water_level: ChunkedStorage::from_dense(&dense.water_level),
+        flags: ChunkedStorage::from_dense(&dense.flags),
+        navigation: Default::default(),
+        connectivity: Default::default(),
+    };
+    rebuild_navigation_flags(&mut region);
+    region.compact();
+    region
+}
+
+/// Reads the RON world, giving miners and lumberjacks the matching labor and
+/// then swapping every other component for its live type.
+fn import_world(ecs_text: &str) -> Result<World, String> {
+    use serde::de::DeserializeSeed;
+    let reg = legacy_registry();
+    let de = reg.as_deserialize();
+    let mut ronnie = ron::Deserializer::from_str(ecs_text).map_err(|e| e.to_string())?;
+    let mut world = de.deserialize(&mut ronnie).map_err(|e| e.to_string())?;
+
+    let settlers: Vec<(Entity, components::Settler)> = <(Entity, &components::Settler)>::query()
+        .iter(&world)
+        .map(|(entity, old)| (*entity, *old))
+        .collect();
+    for (entity, old) in settlers {
+        let mut labors = Labors::new();
+        if old.miner {
+            labors.set_priority(Labor::Mining, 1);
+        }
+        if old.lumberjack {
+            labors.set_priority(Labor::Lumber, 1);
+        }
+        if let Some(mut entry) = world.entry(entity) {
+            entry.remove_component::<components::Settler>();
+            entry.add_component(Settler { labors });
+        }
+    }
+    components::upgrade_unchanged(&mut world)?;
+
+    // The archetypes the entities passed through on the way are empty now,
+    // but would still be saved (and then not load); leave them behind. Every
+    // saved entity had an `IdentityTag`, and it is upgraded last.
+    let mut upgraded = World::default();
+    upgraded.move_from(&mut world, &component::<IdentityTag>());
+    Ok(upgraded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rebuild_flags, TileType};
+    use nox_components::{
+        deserialize_world_binary, Item, JobType, Location, MyTurn, Name, Position,
+    };
+    use nox_spatial::{mapidx, REGION_TILES_COUNT};
+
+    /// A `world.dat` body, as the game used to write it.
+    fn legacy_body() -> Vec<u8> {
+        let idx = mapidx(10usize, 10, 100);
+        let mut region = Region::initial();
+        region.fill_tile_types(TileType::Solid);
+        region.set_tile_type(idx, TileType::Floor);
+        rebuild_flags(&mut region);
+        let mut tile_types = vec![planet::TileType::Solid; REGION_TILES_COUNT];
+        tile_types[idx] = planet::TileType::Floor;
+        let mut material_idx = vec![0; REGION_TILES_COUNT];
+        material_idx[idx] = 3;
+        let dense = DenseRegion {
+            world_idx: 12,
+            tile_types,
+            material_idx,
+            biome_info_idx: 0,
+            biome_raw_idx: 0,
+            revealed: vec![false; REGION_TILES_COUNT],
+            water_level: vec![0; REGION_TILES_COUNT],
+            // Only the flags the old rules knew about
+            flags: (0..REGION_TILES_COUNT)
+                .map(|i| region.flags.get(i) & 1023)
+                .collect(),
+        };
+
+        let center = planet::Point { x: 3, y: 4 };
+        let planet = Planet {
+            rng_seed: 42,
+            perlin_seed: 7,
+            remaining_settlers: 0,
+            migrant_counter: 0,
+            water_divisor: 3,
+            plains_divisor: 3,
+            starting_settlers: 6,
+            strict_beamdown: true,
+            water_height: 10,
+            plains_height: 20,
+            hills_height: 30,
+            lacunarity: 2.0,
+            landblocks: vec![planet::Block {
+                height: 25,
+                variance: 2,
+                btype: planet::BlockType::Hills,
+                temperature: 12,
+                rainfall: 40,
+                biome_idx: 0,
+            }],
+            biomes: vec![planet::Biome {
+                biome_type: 1,
+                name: "Grassland".to_string(),
+                mean_temperature: 12,
+                mean_rainfall: 40,
+                mean_altitude: 25,
+                mean_variance: 2,
+                warp_mutation: 0,
+                evil: 0,
+                savagery: 0,
+                center,
+            }],
+            rivers: vec![planet::River {
+                name: "Long River".to_string(),
+                start: center,
+                steps: vec![planet::RiverStep {
+                    pos: planet::Point { x: 4, y: 4 },
+                }],
+            }],
+        };
+
+        let mut ecs = World::default();
+        ecs.push((
+            components::IdentityTag(7),
+            components::Settler {
+                miner: true,
+                lumberjack: false,
+            },
+            components::Name {
+                name: "Miner".to_string(),
+            },
+            components::Position {
+                loc: components::Location::Tile { idx },
+                region_idx: 12,
+                dimensions: (1, 1, 1),
+            },
+            components::MyTurn {
+                active: false,
+                shift: components::ScheduleTime::Work,
+                job: components::JobType::Mining {
+                    step: components::MiningSteps::Dig,
+                    tool_id: Some(9),
+                },
+                order: components::WorkOrder::None,
+            },
+        ));
+        ecs.push((
+            components::IdentityTag(8),
+            components::Settler {
+                miner: false,
+                lumberjack: true,
+            },
+            components::Name {
+                name: "Lumberjack".to_string(),
+            },
+        ));
+        ecs.push((components::IdentityTag(9), components::Item {}));
+        let ecs_text = ron::to_string(
+            &ecs.as_serializable(component::<components::IdentityTag>(), &legacy_registry()),
+        )
+        .unwrap();
+
+        bincode::serialize(&LegacySavedGame {
+            planet,
+            current_region: dense,
+            ecs_text,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn world_dat_imports_as_a_current_save() {
+        let (saved, _) = import(&legacy_body()).unwrap();
+        let idx = mapidx(10usize, 10, 100);
+        assert_eq!(saved.current_region.world_idx, 12);
+        assert_eq!(saved.current_region.tile_type(idx), TileType::Floor);
+        assert_eq!(saved.current_region.material(idx), 3);
+        assert!(saved.current_region.flag(idx, Region::CAN_STAND_HERE));
+        assert!(saved.play_state_text.is_none());
+
+        assert_eq!(saved.planet.rng_seed, 42);
+        assert_eq!(saved.planet.landblocks[0].btype, nox_raws::BlockType::Hills);
+        assert_eq!(saved.planet.biomes[0].name, "Grassland");
+        assert_eq!(saved.planet.biomes[0].center.x, 3);
+        assert_eq!(saved.planet.rivers[0].steps[0].pos.x, 4);
+
+        // The body has to load as the current format, with miners still mining
+        let ecs = deserialize_world_binary(&saved.ecs_data).unwrap();
+        let mut settlers: Vec<(usize, &Settler, &Name)> =
+            <(&IdentityTag, &Settler, &Name)>::query()
+                .iter(&ecs)
+                .map(|(id, settler, name)| (id.0, settler, name))
+                .collect();
+        settlers.sort_by_key(|(id, _, _)| *id);
+        assert_eq!(settlers.len(), 2);
+        assert_eq!(settlers[0].2.name, "Miner");
+        assert!(settlers[0].1.labors.is_enabled(&Labor::Mining));
+        assert!(!settlers[0].1.labors.is_enabled(&Labor::Lumber));
+        assert_eq!(settlers[1].2.name, "Lumberjack");
+        assert!(!settlers[1].1.labors.is_enabled(&Labor::Mining));
+        assert!(settlers[1].1.labors.is_enabled(&Labor::Lumber));
+        assert_eq!(<&Item>::query().iter(&ecs).count(), 1);
+
+        let (pos, turn) = <(&Position, &MyTurn)>::query().iter(&ecs).next().unwrap();
+        assert_eq!(pos.loc, Location::Tile { idx });
+        match turn.job {
+            JobType::Mining { tool_id, .. } => assert_eq!(tool_id, Some(9)),
+            _ => panic!("Expected the miner to still be mining"),
+        }
+    }
+
+    #[test]
+    fn garbage_is_reported_not_imported() {
+        match import(&[1, 2, 3]) {
+            Err(SaveError::Migration { from, .. }) => assert_eq!(from, LEGACY_FORMAT),
+            _ => panic!("Expected an import error"),
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! The planet and region as `world.dat` stored them, and how they map onto the
+//! current types. bincode only knows fields by position, so these must never
+//! change along with the live types.
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Planet {
+    pub rng_seed: u64,
+    pub perlin_seed: u64,
+    pub remaining_settlers: i32,
+    pub migrant_counter: i32,
+    pub water_divisor: i32,
+    pub plains_divisor: i32,
+    pub starting_settlers: i32,
+    pub strict_beamdown: bool,
+    pub water_height: u8,
+    pub plains_height: u8,
+    pub hills_height: u8,
+    pub lacunarity: f32,
+    pub landblocks: Vec<Block>,
+    pub biomes: Vec<Biome>,
+    pub rivers: Vec<River>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Block {
+    pub height: u8,
+    pub variance: u8,
+    pub btype: BlockType,
+    pub temperature: i8,
+    pub rainfall: i8,
+    pub biome_idx: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(super) enum BlockType {
+    None,
+    Water,
+    Plains,
+    Hills,
+    Mountains,
+    Marsh,
+    Plateau,
+    Highlands,
+    Coastal,
+    SaltMarsh,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct Biome {
+    pub biome_type: usize,
+    pub name: String,
+    pub mean_temperature: i8,
+    pub mean_rainfall: i8,
+    pub mean_altitude: u8,
+    pub mean_variance: u8,
+    pub warp_mutation: u8,
+    pub evil: u8,
+    pub savagery: u8,
+    pub center: Point,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct River {
+    pub name: String,
+    pub start: Point,
+    pub steps: Vec<RiverStep>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct RiverStep {
+    pub pos: Point,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(super) struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Every region layer was stored as a flat `Vec`.
+#[derive(Serialize, Deserialize)]
+pub(super) struct DenseRegion {
+    pub world_idx: usize,
+    pub tile_types: Vec<TileType>,
+    pub material_idx: Vec<usize>,
+    pub biome_info_idx: usize,
+    pub biome_raw_idx: usize,
+    pub revealed: Vec<bool>,
+    pub water_level: Vec<u8>,
+    pub flags: Vec<u16>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(super) enum TileType {
+    Empty,
+    Solid,
+    Floor,
+    Wall,
+    Ramp { direction: RampDirection },
+    Stairs { direction: StairsType },
+    SemiMoltenRock,
+    Window,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(super) enum RampDirection {
+    NorthSouth,
+    SouthNorth,
+    EastWest,
+    WestEast,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub(super) enum StairsType {
+    Up,
+    Down,
+    UpDown,
+}
+
+impl From<Planet> for crate::Planet {
+    fn from(old: Planet) -> Self {
+        Self {
+            rng_seed: old.rng_seed,
+            perlin_seed: old.perlin_seed,
+            remaining_settlers: old.remaining_settlers,
+            migrant_counter: old.migrant_counter,
+            water_divisor: old.water_divisor,
+            plains_divisor: old.plains_divisor,
+            starting_settlers: old.starting_settlers,
+            strict_beamdown: old.strict_beamdown,
+            water_height: old.water_height,
+            plains_height: old.plains_height,
+            hills_height: old.hills_height,
+            lacunarity: old.lacunarity,
+            landblocks: old.landblocks.into_iter().map(|b| b.into()).collect(),
+            biomes: old.biomes.into_iter().map(|b| b.into()).collect(),
+            rivers: old.rivers.into_iter().map(|r| r.into()).collect(),
+        }
+    }
+}
+
+impl From<Block> for crate::Block {
+    fn from(old: Block) -> Self {
+        Self {
+            height: old.height,
+            variance: old.variance,
+            btype: old.btype.into(),
+            temperature: old.temperature,
+            rainfall: old.rainfall,
+            biome_idx: old.biome_idx,
+        }
+    }
+}
+
+impl From<BlockType> for nox_raws::BlockType {
+    fn from(old: BlockType) -> Self {
+        match old {
+            BlockType::None => Self::None,
+            BlockType::Water => Self::Water,
+            BlockType::Plains => Self::Plains,
+            BlockType::Hills => Self::Hills,
+            BlockType::Mountains => Self::Mountains,
+            BlockType::Marsh => Self::Marsh,
+            BlockType::Plateau => Self::Plateau,
+            BlockType::Highlands => Self::Highlands,
+            BlockType::Coastal => Self::Coastal,
+            BlockType::SaltMarsh => Self::SaltMarsh,
+        }
+    }
+}
+
+impl From<Biome> for crate::Biome {
+    fn from(old: Biome) -> Self {
+        Self {
+            biome_type: old.biome_type,
+            name: old.name,
+            mean_temperature: old.mean_temperature,
+            mean_rainfall: old.mean_rainfall,
+            mean_altitude: old.mean_altitude,
+            mean_variance: old.mean_variance,
+            warp_mutation: old.warp_mutation,
+            evil: old.evil,
+            savagery: old.savagery,
+            center: old.center.into(),
+        }
+    }
+}
+
+impl From<River> for crate::River {
+    fn from(old: River) -> Self {
+        Self {
+            name: old.name,
+            start: old.start.into(),
+            steps: old
+                .steps
+                .into_iter()
+                .map(|s| crate::RiverStep { pos: s.pos.into() })
+                .collect(),
+        }
+    }
+}
+
+impl From<Point> for bengine::geometry::Point {
+    fn from(old: Point) -> Self {
+        Self::new(old.x, old.y)
+    }
+}
+
+impl From<TileType> for crate::TileType {
+    fn from(old: TileType) -> Self {
+        match old {
+            TileType::Empty => Self::Empty,
+            TileType::Solid => Self::Solid,
+            TileType::Floor => Self::Floor,
+            TileType::Wall => Self::Wall,
+            TileType::Ramp { direction } => Self::Ramp {
+                direction: match direction {
+                    RampDirection::NorthSouth => crate::RampDirection::NorthSouth,
+                    RampDirection::SouthNorth => crate::RampDirection::SouthNorth,
+                    RampDirection::EastWest => crate::RampDirection::EastWest,
+                    RampDirection::WestEast => crate::RampDirection::WestEast,
+                },
+            },
+            TileType::Stairs { direction } => Self::Stairs {
+                direction: match direction {
+                    StairsType::Up => crate::StairsType::Up,
+                    StairsType::Down => crate::StairsType::Down,
+                    StairsType::UpDown => crate::StairsType::UpDown,
+                },
+            },
+            TileType::SemiMoltenRock => Self::SemiMoltenRock,
+            TileType::Window => Self::Window,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use super::{SaveError, SAVE_FORMAT_VERSION};
+
+/// Converts the (decompressed) body of a save written by one format version
+/// into the body the next version expects.
+type Migration = fn(Vec<u8>) -> Result<Vec<u8>, SaveError>;
+
+// When you change anything that ends up in a save - `Planet`, `Region`, or a
+// registered component - bump `SAVE_FORMAT_VERSION` and add an entry here that
+// upgrades a body from the previous version. Keep a frozen copy of every
+// structure the old body contains for the migration to read, rather than using
+// the live types; `legacy/` does the same for `world.dat`. Don't edit entries
+// once they have shipped.
+const MIGRATIONS: &[(u32, Migration)] = &[];
+
+/// Runs every migration needed to bring `body` from `version` up to the
+/// current format.
+pub(crate) fn migrate(version: u32, body: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+    run(MIGRATIONS, SAVE_FORMAT_VERSION, version, body)
+}
+
+fn run(
+    migrations: &[(u32, Migration)],
+    current: u32,
+    mut version: u32,
+    mut body: Vec<u8>,
+) -> Result<Vec<u8>, SaveError> {
+    if version > current {
+        return Err(SaveError::TooNew { version });
+    }
+
+    while version < current {
+        let step = migrations
+            .iter()
+            .find(|(from, _)| *from == version)
+            .ok_or_else(|| SaveError::Migration {
+                from: version,
+                reason: "no upgrade path is available".to_string(),
+            })?;
+        println!("Upgrading save from format {}", version);
+        body = (step.1)(body)?;
+        version += 1;
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    /// A made-up format 0, and a format 1 that added a field to it.
+    #[derive(Serialize, Deserialize)]
+    struct V0 {
+        seed: u64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct V1 {
+        seed: u64,
+        settlers: i32,
+    }
+
+    fn v0_to_v1(body: Vec<u8>) -> Result<Vec<u8>, SaveError> {
+        let old: V0 = bincode::deserialize(&body).map_err(|e| SaveError::Migration {
+            from: 0,
+            reason: e.to_string(),
+        })?;
+        Ok(bincode::serialize(&V1 {
+            seed: old.seed,
+            settlers: 6,
+        })
+        .unwrap())
+    }
+
+    const TEST_MIGRATIONS: &[(u32, Migration)] = &[(0, v0_to_v1)];
+
+    #[test]
+    fn old_bodies_are_upgraded_one_version_at_a_time() {
+        let body = bincode::serialize(&V0 { seed: 42 }).unwrap();
+        let upgraded = run(TEST_MIGRATIONS, 1, 0, body).unwrap();
+        let new: V1 = bincode::deserialize(&upgraded).unwrap();
+        assert_eq!(new.seed, 42);
+        assert_eq!(new.settlers, 6);
+    }
+}
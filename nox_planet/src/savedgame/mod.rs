@@ -0,0 +1,357 @@
+use super::Planet;
+use super::Region;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use legion::*;
+use nox_components::{Calendar, Settler};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+mod error;
+pub use error::SaveError;
+mod legacy;
+pub use legacy::LEGACY_SAVE_FILE;
+mod migrations;
+
+/// Directory (relative to the working directory) that holds save slots.
+pub const SAVE_DIR: &str = "saves";
+const SAVE_EXTENSION: &str = "nox";
+const SAVE_MAGIC: &[u8; 4] = b"NOXS";
+
+/// Version of the save body layout. Bump this (and add a migration) whenever
+/// a saved structure changes.
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+lazy_static! {
+    static ref ACTIVE_SLOT: RwLock<Option<String>> = RwLock::new(None);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub planet: Planet,
+    pub current_region: Region,
+    /// The legion world, from `nox_components::serialize_world_binary`.
+    pub ecs_data: Vec<u8>,
+    /// Play-mode state (run state and friends), serialized by the game as RON.
+    /// Freshly generated worlds don't have one yet.
+    pub play_state_text: Option<String>,
+}
+
+/// Summary of a save slot, stored uncompressed at the front of the file so
+/// the menus can list saves without inflating the whole world. It is stored
+/// as RON so that new (`#[serde(default)]`) fields don't hide older saves.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveHeader {
+    pub slot: String,
+    pub world_seed: u64,
+    pub calendar: Calendar,
+    pub settler_count: usize,
+    pub biome: String,
+    pub saved_at: u64,
+    /// For autosaves, the slot that they are a backup of.
+    #[serde(default)]
+    pub autosave_of: Option<String>,
+}
+
+impl SaveHeader {
+    pub fn new(slot: &str, planet: &Planet, region: &Region, ecs: &World) -> Self {
+        let calendar = <&Calendar>::query()
+            .iter(ecs)
+            .next()
+            .cloned()
+            .unwrap_or(Calendar {
+                year: 0,
+                month: 0,
+                day: 0,
+                hour: 0,
+                minute: 0,
+                second: 0,
+            });
+        let settler_count = <&Settler>::query().iter(ecs).count();
+        let biome = nox_raws::RAWS
+            .read()
+            .biomes
+            .areas
+            .get(region.biome_raw_idx)
+            .map(|b| b.name.clone())
+            .unwrap_or_default();
+        let saved_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            slot: slot.to_string(),
+            world_seed: planet.rng_seed,
+            calendar,
+            settler_count,
+            biome,
+            saved_at,
+            autosave_of: None,
+        }
+    }
+}
+
+/// Sets the slot that the game will load from and save to.
+pub fn set_active_slot<S: ToString>(slot: S) {
+    *ACTIVE_SLOT.write() = Some(slot.to_string());
+}
+
+pub fn active_slot() -> Option<String> {
+    ACTIVE_SLOT.read().clone()
+}
+
+pub fn slot_path(slot: &str) -> PathBuf {
+    Path::new(SAVE_DIR).join(format!("{}.{}", slot, SAVE_EXTENSION))
+}
+
+pub fn slot_exists(slot: &str) -> bool {
+    slot_path(slot).exists()
+}
+
+/// Picks a slot name based on `base` that isn't already in use.
+pub fn unused_slot_name(base: &str) -> String {
+    let base: String = base
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !slot_exists(&base) {
+        return base;
+    }
+    let mut n = 2;
+    while slot_exists(&format!("{}-{}", base, n)) {
+        n += 1;
+    }
+    format!("{}-{}", base, n)
+}
+
+pub fn save_world(header: SaveHeader, mut state: SavedGame) -> Result<(), SaveError> {
+    state.current_region.compact();
+    std::fs::create_dir_all(SAVE_DIR)?;
+    let final_path = slot_path(&header.slot);
+    let tmp_path = final_path.with_extension("tmp");
+
+    // Write to a temporary file first, so a crash mid-save can't eat the old game
+    let mut world_file = File::create(&tmp_path)?;
+    write_save(&mut world_file, &header, &state)?;
+    world_file.sync_all()?;
+    std::mem::drop(world_file);
+    std::fs::rename(&tmp_path, &final_path)?;
+    Ok(())
+}
+
+// File layout: magic, format version, header length, RON header, deflated bincode body.
+fn write_save<W: Write>(
+    w: &mut W,
+    header: &SaveHeader,
+    state: &SavedGame,
+) -> Result<(), SaveError> {
+    let header_text = ron::to_string(header).map_err(|e| SaveError::Encode(e.to_string()))?;
+    let mem_vec = bincode::serialize(state).map_err(|e| SaveError::Encode(e.to_string()))?;
+    let compressed_bytes = miniz_oxide::deflate::compress_to_vec(&mem_vec, 6);
+
+    w.write_all(SAVE_MAGIC)?;
+    w.write_u32::<LittleEndian>(SAVE_FORMAT_VERSION)?;
+    w.write_u32::<LittleEndian>(header_text.len() as u32)?;
+    w.write_all(header_text.as_bytes())?;
+    w.write_all(&compressed_bytes)?;
+    Ok(())
+}
+
+/// Reads the file preamble, returning the format version and header.
+fn read_header<R: Read>(f: &mut R) -> Result<(u32, SaveHeader), SaveError> {
+    let mut magic = [0u8; 4];
+    f.read_exact(&mut magic)
+        .map_err(|_| SaveError::NotASaveFile)?;
+    if &magic != SAVE_MAGIC {
+        return Err(SaveError::NotASaveFile);
+    }
+    let version = f.read_u32::<LittleEndian>()?;
+    let len = f.read_u32::<LittleEndian>()? as usize;
+    let mut buffer = vec![0u8; len];
+    f.read_exact(&mut buffer)?;
+    let text = String::from_utf8(buffer).map_err(|e| SaveError::Decode(e.to_string()))?;
+    let header = ron::from_str(&text).map_err(|e| SaveError::Decode(e.to_string()))?;
+    Ok((version, header))
+}
+
+/// Reads just the header of a save slot.
+pub fn load_header(slot: &str) -> Result<SaveHeader, SaveError> {
+    let mut f = File::open(slot_path(slot))?;
+    Ok(read_header(&mut f)?.1)
+}
+
+/// Lists all readable save slots, most recently saved first.
+pub fn list_saves() -> Vec<SaveHeader> {
+    let mut result = Vec::new();
+    if let Ok(dir) = std::fs::read_dir(SAVE_DIR) {
+        for entry in dir.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(SAVE_EXTENSION) {
+                continue;
+            }
+            match File::open(&path)
+                .map_err(SaveError::from)
+                .and_then(|mut f| read_header(&mut f))
+            {
+                Ok((_, header)) => result.push(header),
+                Err(e) => println!("Skipping {}: {}", path.display(), e),
+            }
+        }
+    }
+    result.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    result
+}
+
+pub fn load_game(slot: &str) -> Result<SavedGame, SaveError> {
+    let savepath = slot_path(slot);
+    if !savepath.exists() {
+        return Err(SaveError::NotFound(slot.to_string()));
+    }
+
+    let mut f = File::open(&savepath)?;
+    Ok(read_save(&mut f)?.1)
+}
+
+fn read_save<R: Read>(f: &mut R) -> Result<(SaveHeader, SavedGame), SaveError> {
+    let (version, header) = read_header(f)?;
+    if version > SAVE_FORMAT_VERSION {
+        return Err(SaveError::TooNew { version });
+    }
+    let mut buffer = Vec::<u8>::new();
+    println!("Reading file");
+    f.read_to_end(&mut buffer)?;
+    println!("Decompressing file");
+    let raw_bytes =
+        miniz_oxide::inflate::decompress_to_vec(&buffer).map_err(|_| SaveError::Decompress)?;
+    let raw_bytes = migrations::migrate(version, raw_bytes)?;
+
+    println!("Deserializing");
+    let saved: SavedGame = bincode::deserialize(&raw_bytes)?;
+    println!("Done");
+    Ok((header, saved))
+}
+
+/// Games used to be saved to a single `world.dat`, before there were slots.
+/// If there is one, it is converted into a slot of its own and then renamed,
+/// so that this only happens once. Returns the new slot, if any.
+pub fn import_legacy_save() -> Result<Option<String>, SaveError> {
+    let legacy_path = Path::new(LEGACY_SAVE_FILE);
+    if !legacy_path.exists() {
+        return Ok(None);
+    }
+
+    println!("Importing {}", LEGACY_SAVE_FILE);
+    let compressed = std::fs::read(legacy_path)?;
+    let body =
+        miniz_oxide::inflate::decompress_to_vec(&compressed).map_err(|_| SaveError::Decompress)?;
+    let (state, ecs) = legacy::import(&body)?;
+    let slot = unused_slot_name("world");
+    let header = SaveHeader::new(&slot, &state.planet, &state.current_region, &ecs);
+    save_world(header, state)?;
+    std::fs::rename(legacy_path, legacy_path.with_extension("dat.imported"))?;
+    Ok(Some(slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TileType;
+    use nox_components::{IdentityTag, Labor, Labors};
+    use nox_spatial::mapidx;
+    use std::io::Cursor;
+
+    fn game() -> (SaveHeader, SavedGame) {
+        let mut region = Region::initial();
+        region.set_tile_type(mapidx(5usize, 6, 7), TileType::Floor);
+        region.set_material(mapidx(5usize, 6, 7), 4);
+
+        let mut ecs = World::default();
+        let mut labors = Labors::new();
+        labors.set_priority(Labor::Mining, 2);
+        ecs.push((IdentityTag(3), Settler { labors }));
+
+        let mut planet = Planet::new();
+        planet.rng_seed = 42;
+        let header = SaveHeader::new("test", &planet, &region, &ecs);
+        let state = SavedGame {
+            planet,
+            current_region: region,
+            ecs_data: nox_components::serialize_world_binary(&ecs).unwrap(),
+            play_state_text: Some("Running".to_string()),
+        };
+        (header, state)
+    }
+
+    fn file_with_version(version: u32) -> Vec<u8> {
+        let (header, state) = game();
+        let mut file = Vec::new();
+        write_save(&mut file, &header, &state).unwrap();
+        file[4..8].copy_from_slice(&version.to_le_bytes());
+        file
+    }
+
+    #[test]
+    fn saves_round_trip() {
+        let (header, state) = game();
+        let mut file = Vec::new();
+        write_save(&mut file, &header, &state).unwrap();
+
+        let (loaded_header, loaded) = read_save(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(loaded_header.slot, "test");
+        assert_eq!(loaded_header.world_seed, 42);
+        assert_eq!(loaded_header.settler_count, 1);
+        assert_eq!(loaded.planet.rng_seed, 42);
+        assert_eq!(
+            loaded.current_region.tile_type(mapidx(5usize, 6, 7)),
+            TileType::Floor
+        );
+        assert_eq!(loaded.current_region.material(mapidx(5usize, 6, 7)), 4);
+        assert_eq!(loaded.play_state_text.as_deref(), Some("Running"));
+        assert_eq!(loaded.ecs_data, state.ecs_data);
+
+        let ecs = nox_components::deserialize_world_binary(&loaded.ecs_data).unwrap();
+        let settler = <&Settler>::query().iter(&ecs).next().unwrap();
+        assert_eq!(settler.labors.priority(&Labor::Mining), 2);
+    }
+
+    #[test]
+    fn only_save_files_are_read() {
+        let mut file = file_with_version(SAVE_FORMAT_VERSION);
+        file[0] = b'X';
+        assert!(matches!(
+            read_save(&mut Cursor::new(&file)),
+            Err(SaveError::NotASaveFile)
+        ));
+    }
+
+    #[test]
+    fn newer_formats_are_refused() {
+        let file = file_with_version(SAVE_FORMAT_VERSION + 1);
+        match read_save(&mut Cursor::new(&file)) {
+            Err(SaveError::TooNew { version }) => assert_eq!(version, SAVE_FORMAT_VERSION + 1),
+            _ => panic!("Expected the save to be too new"),
+        }
+    }
+
+    #[test]
+    fn formats_without_a_migration_are_refused() {
+        let file = file_with_version(0);
+        match read_save(&mut Cursor::new(&file)) {
+            Err(SaveError::Migration { from, .. }) => assert_eq!(from, 0),
+            _ => panic!("Expected no upgrade path"),
+        }
+        let body = vec![1, 2, 3];
+        assert_eq!(
+            migrations::migrate(SAVE_FORMAT_VERSION, body.clone()).unwrap(),
+            body
+        );
+    }
+}
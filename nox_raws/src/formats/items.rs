@@ -31,7 +31,7 @@ pub struct ItemDef {
     pub build_rules: Option<Vec<ItemDefBuild>>,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum ItemDefType {
     Component,
     ToolChopping,
@@ -1,5 +1,5 @@
 use super::formats::MaterialDef;
-use bengine::Palette;
+use bengine::ColorFinder;
 use std::collections::HashMap;
 
 pub struct MaterialMap {
@@ -13,7 +13,7 @@ impl MaterialMap {
         }
     }
 
-    pub fn build(&mut self, materials: &[MaterialDef], palette: &Palette) {
+    pub fn build(&mut self, materials: &[MaterialDef], palette: &ColorFinder) {
         self.map.clear();
 
         materials.iter().enumerate().for_each(|(idx, m)| {
@@ -6,7 +6,7 @@ pub fn ground_z(region: &Region, x: usize, y: usize) -> usize {
     let mut hit_ground = false;
     while !hit_ground {
         let idx = mapidx(x, y, z);
-        if region.tile_types[idx] == TileType::Solid {
+        if region.tile_type(idx) == TileType::Solid {
             hit_ground = true;
             z += 1;
         } else {
@@ -82,5 +82,9 @@ impl BEngineGame for NoxFutura {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--headless") {
+        std::process::exit(run_headless(&args));
+    }
     run(NoxFutura::new(), "Nox Futura");
 }
@@ -54,7 +54,7 @@ impl LoaderState {
             {
                 let mut rawlock = nox_raws::RAWS.write();
                 let mats = rawlock.materials.materials.clone();
-                rawlock.matmap.build(&mats, &palette);
+                rawlock.matmap.build(&mats, &palette.colors);
             }
 
             let gbuffer = GBuffer::new();
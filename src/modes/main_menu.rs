@@ -1,9 +1,16 @@
+use super::playgame::{LoadState, LOAD_STATE};
 use crate::{GameMode, NoxMode, SharedResources};
 use bengine::random::RandomNumberGenerator;
 use bengine::*;
+use nox_planet::SaveHeader;
+use std::time::SystemTime;
 
 pub struct MainMenu {
     tagline: String,
+    saves: Vec<SaveHeader>,
+    saves_scanned: Option<SystemTime>,
+    show_saves: bool,
+    load_error: Option<String>,
 }
 
 impl MainMenu {
@@ -30,7 +37,63 @@ impl MainMenu {
 
         tagline = format!("{} of {} and {}", tagline, first_noun, second_noun).to_string();
 
-        Self { tagline }
+        // Games saved before there were slots get one of their own
+        let load_error = match nox_planet::import_legacy_save() {
+            Ok(Some(slot)) => {
+                println!("Imported {} as {}", nox_planet::LEGACY_SAVE_FILE, slot);
+                None
+            }
+            Ok(None) => None,
+            Err(e) => Some(format!(
+                "Unable to import {}: {}",
+                nox_planet::LEGACY_SAVE_FILE,
+                e
+            )),
+        };
+
+        Self {
+            tagline,
+            saves: Vec::new(),
+            saves_scanned: None,
+            show_saves: false,
+            load_error,
+        }
+    }
+
+    /// Re-reads the save headers whenever the save directory changes.
+    fn refresh_saves(&mut self) {
+        let modified = std::fs::metadata(nox_planet::SAVE_DIR)
+            .and_then(|m| m.modified())
+            .ok();
+        if modified.is_none() || modified != self.saves_scanned {
+            self.saves = nox_planet::list_saves();
+            self.saves_scanned = modified;
+        }
+    }
+
+    fn describe_save(save: &SaveHeader) -> String {
+        let now = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let age = now.saturating_sub(save.saved_at);
+        let age = if age < 60 {
+            "just now".to_string()
+        } else if age < 3600 {
+            format!("{} minutes ago", age / 60)
+        } else if age < 86400 {
+            format!("{} hours ago", age / 3600)
+        } else {
+            format!("{} days ago", age / 86400)
+        };
+        format!(
+            "{} - {}, {} settlers, {} (saved {})",
+            save.slot,
+            save.calendar.get_date_time(),
+            save.settler_count,
+            save.biome,
+            age
+        )
     }
 
     const NOUNS: &'static [&'static str] = &[
@@ -70,6 +133,14 @@ impl NoxMode for MainMenu {
         use gui::*;
 
         let mut result = GameMode::MainMenu;
+        self.refresh_saves();
+        {
+            let mut lock = LOAD_STATE.write();
+            if let LoadState::Failed { error } = &lock.state {
+                self.load_error = Some(error.clone());
+                lock.state = LoadState::Idle;
+            }
+        }
         shared.quad_render.render(shared.background_image, core);
         let ui = core.imgui;
 
@@ -94,6 +165,9 @@ impl NoxMode for MainMenu {
         let width = f32::max(tagline_size[0], kylah_size[0]);
         let hpos = (size.width as f32 / 2.0) - (width / 2.0);
 
+        let tagline = &self.tagline;
+        let saves = &self.saves;
+        let show_saves = &mut self.show_saves;
         let mainmenu = gui::Window::new(im_str!("Main Menu"));
         mainmenu
             .position(
@@ -104,14 +178,27 @@ impl NoxMode for MainMenu {
             .collapsible(false)
             .no_decoration()
             .build(ui, || {
-                ui.text_colored([1.0, 1.0, 0.0, 1.0], &self.tagline);
+                ui.text_colored([1.0, 1.0, 0.0, 1.0], tagline);
                 if ui.button(im_str!("New Game"), [100.0, 20.0]) {
                     result = GameMode::WorldGen1;
                 }
-                if std::path::Path::new("world.dat").exists() {
-                    if ui.button(im_str!("Play/Continue Game"), [100.0, 20.0]) {
+                if let Some(latest) = saves.first() {
+                    if ui.button(im_str!("Continue Game"), [100.0, 20.0]) {
+                        nox_planet::set_active_slot(&latest.slot);
                         result = GameMode::PlayGame;
                     }
+                    if ui.button(im_str!("Load Game"), [100.0, 20.0]) {
+                        *show_saves = !*show_saves;
+                    }
+                    if *show_saves {
+                        for save in saves.iter() {
+                            let label = ImString::new(MainMenu::describe_save(save));
+                            if Selectable::new(&label).build(ui) {
+                                nox_planet::set_active_slot(&save.slot);
+                                result = GameMode::PlayGame;
+                            }
+                        }
+                    }
                 }
                 if ui.button(im_str!("Quit"), [100.0, 20.0]) {
                     result = GameMode::Quitting;
@@ -119,6 +206,26 @@ impl NoxMode for MainMenu {
                 ui.text_colored([1.0, 0.0, 0.0, 1.0], &MainMenu::DEDICATION);
             });
 
+        let mut dismiss_error = false;
+        if let Some(error) = &self.load_error {
+            gui::Window::new(im_str!("Unable to load game"))
+                .position(
+                    [hpos, (size.height as f32 / 2.0) + 100.0],
+                    Condition::Always,
+                )
+                .always_auto_resize(true)
+                .collapsible(false)
+                .build(ui, || {
+                    ui.text_colored([1.0, 0.0, 0.0, 1.0], &ImString::new(error));
+                    if ui.button(im_str!("OK"), [100.0, 20.0]) {
+                        dismiss_error = true;
+                    }
+                });
+        }
+        if dismiss_error {
+            self.load_error = None;
+        }
+
         result
     }
 }
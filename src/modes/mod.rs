@@ -7,6 +7,6 @@ mod worldgen;
 pub use loader::{Loader, LOADER};
 pub use main_menu::MainMenu;
 use playgame::GBuffer;
-pub use playgame::PlayTheGame;
+pub use playgame::{run_headless, PlayTheGame};
 pub use shared_resources::SharedResources;
 pub use worldgen::{WorldGen1, WorldGen2};
@@ -0,0 +1,218 @@
+use super::{savestate::PlayState, simulation::*, systems::REGION, RunState};
+use legion::*;
+use nox_components::*;
+use nox_planet::{PlanetParams, SavedGame};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+const USAGE: &str =
+    "Usage: noxfutura --headless [--ticks N] [--load SLOT | --seed SEED [--settlers N]]";
+
+/// Where the headless run gets its world from.
+pub enum WorldSource {
+    Slot(String),
+    Generate { seed: i32, settlers: i32 },
+}
+
+pub struct HeadlessOptions {
+    pub ticks: usize,
+    pub source: WorldSource,
+}
+
+impl HeadlessOptions {
+    /// Reads the command line. With no world given, continues the most
+    /// recently saved game.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut ticks = 1000;
+        let mut slot = None;
+        let mut seed = None;
+        let mut settlers = 6;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--headless" => {}
+                "--ticks" => ticks = number_arg(arg, iter.next())?,
+                "--load" => slot = Some(value_arg(arg, iter.next())?.to_string()),
+                "--seed" => seed = Some(number_arg(arg, iter.next())?),
+                "--settlers" => settlers = number_arg(arg, iter.next())?,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        let source = match (slot, seed) {
+            (Some(_), Some(_)) => return Err("Use either --load or --seed, not both".to_string()),
+            (Some(slot), None) => WorldSource::Slot(slot),
+            (None, Some(seed)) => WorldSource::Generate { seed, settlers },
+            (None, None) => match nox_planet::list_saves().first() {
+                Some(header) => WorldSource::Slot(header.slot.clone()),
+                None => return Err("There are no saved games; use --seed to make one".to_string()),
+            },
+        };
+
+        Ok(Self { ticks, source })
+    }
+}
+
+fn value_arg<'a>(name: &str, value: Option<&'a String>) -> Result<&'a str, String> {
+    value
+        .map(|v| v.as_str())
+        .ok_or_else(|| format!("{} needs a value", name))
+}
+
+fn number_arg<T: std::str::FromStr>(name: &str, value: Option<&String>) -> Result<T, String> {
+    let value = value_arg(name, value)?;
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, not '{}'", name, value))
+}
+
+/// Runs the simulation without a window or GPU, and prints colony statistics
+/// when done. Returns the process exit code.
+pub fn run_headless(args: &[String]) -> i32 {
+    let options = match HeadlessOptions::from_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}", e);
+            println!("{}", USAGE);
+            return 2;
+        }
+    };
+
+    match simulate(&options) {
+        Ok(()) => 0,
+        Err(e) => {
+            println!("Headless run failed: {}", e);
+            1
+        }
+    }
+}
+
+fn simulate(options: &HeadlessOptions) -> Result<(), String> {
+    println!("Loading raw files");
+    load_raws_headless();
+
+    let slot = match &options.source {
+        WorldSource::Slot(slot) => slot.clone(),
+        WorldSource::Generate { seed, settlers } => {
+            println!("Generating a world from seed {}", seed);
+            nox_planet::build_planet_blocking(PlanetParams {
+                world_seed: *seed,
+                water_level: 3,
+                plains_level: 3,
+                starting_settlers: *settlers,
+                strict_beamdown: true,
+                extra_noise: true,
+                bumpiness: 2.0,
+            })
+            .ok_or_else(|| "Unable to save the generated world".to_string())?
+        }
+    };
+
+    println!("Loading {}", slot);
+    let game = nox_planet::load_game(&slot).map_err(|e| e.to_string())?;
+    let (mut ecs, mut resources) = start_saved_game(game)?;
+
+    println!("Running {} ticks", options.ticks);
+    let mut schedule = super::systems::build_scheduler();
+    let started = Instant::now();
+    for _ in 0..options.ticks {
+        headless_tick(&mut schedule, &mut ecs, &mut resources);
+    }
+
+    print_stats(&ecs, options.ticks, started.elapsed().as_secs_f32());
+    Ok(())
+}
+
+/// Sets up a saved game to run flat out, without autosaving.
+pub(super) fn start_saved_game(game: SavedGame) -> Result<(World, Resources), String> {
+    let mut ecs = nox_components::deserialize_world_binary(&game.ecs_data)
+        .map_err(|e| format!("Unable to read the saved entities: {}", e))?;
+    *REGION.write() = game.current_region;
+
+    let mut play_state = PlayState::from_text(&game.play_state_text);
+    play_state.run_state = RunState::FullSpeed;
+    play_state.autosave.enabled = false;
+    let mut resources = Resources::default();
+    start_simulation(&mut ecs, &mut resources, &game.planet, play_state);
+    Ok((ecs, resources))
+}
+
+fn job_name(job: &JobType) -> &'static str {
+    match job {
+        JobType::None => "Idle",
+        JobType::CollectTool { .. } => "Collecting tools",
+        JobType::Haul { .. } => "Hauling",
+        JobType::FellTree { .. } => "Felling trees",
+        JobType::ConstructBuilding { .. } => "Building",
+        JobType::Mining { .. } => "Mining",
+        JobType::Reaction { .. } => "Working a workshop",
+        JobType::Construct { .. } => "Constructing",
+    }
+}
+
+fn print_stats(ecs: &World, ticks: usize, seconds: f32) {
+    println!("==== Colony report ====");
+    println!(
+        "Ran {} ticks in {:.1}s ({:.0} ticks/s)",
+        ticks,
+        seconds,
+        ticks as f32 / seconds.max(0.001)
+    );
+    if let Some(calendar) = <&Calendar>::query().iter(ecs).next() {
+        println!("Date: {}", calendar.get_date_time());
+    }
+
+    let mut jobs: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut settlers = 0;
+    let mut miners = 0;
+    let mut lumberjacks = 0;
+    <(&Settler, &MyTurn)>::query()
+        .iter(ecs)
+        .for_each(|(settler, turn)| {
+            settlers += 1;
+            if settler.labors.is_enabled(&Labor::Mining) {
+                miners += 1;
+            }
+            if settler.labors.is_enabled(&Labor::Lumber) {
+                lumberjacks += 1;
+            }
+            *jobs.entry(job_name(&turn.job)).or_insert(0) += 1;
+        });
+    println!(
+        "Settlers: {} ({} miners, {} lumberjacks)",
+        settlers, miners, lumberjacks
+    );
+    for (job, count) in jobs.iter() {
+        println!("  {}: {}", job, count);
+    }
+
+    let items = <&Item>::query().iter(ecs).count();
+    println!("Items: {}", items);
+
+    let buildings = <&Building>::query().iter(ecs).count();
+    let complete = <&Building>::query()
+        .iter(ecs)
+        .filter(|b| b.complete)
+        .count();
+    println!(
+        "Buildings: {} complete, {} planned",
+        complete,
+        buildings - complete
+    );
+
+    let trees = <&Tree>::query().iter(ecs).count();
+    let marked = <&Tree>::query().iter(ecs).filter(|t| t.chop).count();
+    println!("Trees: {} ({} marked for felling)", trees, marked);
+
+    // The simulation is deterministic, so the same save and tick count should
+    // always give the same checksum.
+    println!("State checksum: {:08x}", state_checksum(ecs));
+}
+
+pub(super) fn state_checksum(ecs: &World) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(&nox_components::serialize_world_binary(ecs).unwrap());
+    crc.update(&bincode::serialize(&*REGION.read()).unwrap());
+    crc.sum()
+}
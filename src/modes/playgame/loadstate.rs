@@ -6,6 +6,7 @@ pub enum LoadState {
     Idle,
     Loading,
     Loaded { game: SavedGame },
+    Failed { error: String },
 }
 
 pub struct GameLoader {
@@ -0,0 +1,74 @@
+use super::BLOCKED_PATHS;
+use legion::{systems::CommandBuffer, *};
+use nox_components::*;
+
+/// Gives up on whatever a settler is doing, and undoes everything the job had
+/// set in motion, so that the work can be picked up again by anyone:
+///
+/// * Hauls, reactions and constructions they were working on are no longer
+///   marked as in progress.
+/// * Anything they were carrying is dropped at their feet. Items still on
+///   their way somewhere keep their haul request, and are collected from
+///   there.
+/// * Tools they had borrowed for the job are put down and unclaimed. Settlers
+///   whose labors call for a tool, such as miners, hang on to it.
+pub(crate) fn abandon_job(ecs: &mut World, id: usize) {
+    let settler = <(&IdentityTag, &Position, &Settler)>::query()
+        .iter(ecs)
+        .filter(|(idt, _, _)| idt.0 == id)
+        .map(|(_, pos, settler)| (pos.get_idx(), settler.labors.clone()))
+        .nth(0);
+    let (feet, labors) = match settler {
+        Some(s) => s,
+        None => return,
+    };
+
+    let mut cmds = CommandBuffer::new(ecs);
+    let mut dropped = false;
+    <(Entity, &mut Position, Option<&Tool>, Option<&Claimed>)>::query()
+        .iter_mut(ecs)
+        .for_each(|(e, pos, tool, claim)| {
+            let carried = pos.loc == Location::Carried { by: id };
+            let claimed = claim.map(|c| c.by == id).unwrap_or(false);
+            match tool {
+                Some(tool) if claimed => {
+                    if !labors.needs_tool(tool.usage) {
+                        cmds.remove_component::<Claimed>(*e);
+                        if carried {
+                            pos.to_ground(feet);
+                            dropped = true;
+                        }
+                    }
+                }
+                _ if carried => {
+                    pos.to_ground(feet);
+                    dropped = true;
+                }
+                _ => {}
+            }
+        });
+    cmds.flush(ecs);
+
+    <&mut RequestHaul>::query()
+        .iter_mut(ecs)
+        .filter(|rh| rh.in_progress == Some(id))
+        .for_each(|rh| rh.in_progress = None);
+    <&mut ReactionJob>::query()
+        .iter_mut(ecs)
+        .filter(|rj| rj.in_progress == Some(id))
+        .for_each(|rj| rj.in_progress = None);
+    <&mut Construction>::query()
+        .iter_mut(ecs)
+        .filter(|c| c.in_progress == Some(id))
+        .for_each(|c| c.in_progress = None);
+
+    <(&mut MyTurn, &IdentityTag)>::query()
+        .iter_mut(ecs)
+        .filter(|(_, idt)| idt.0 == id)
+        .for_each(|(turn, _)| turn.job = JobType::None);
+    BLOCKED_PATHS.lock().remove(&id);
+
+    if dropped {
+        super::super::vox_moved();
+    }
+}
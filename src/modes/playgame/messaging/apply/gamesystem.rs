@@ -1,8 +1,14 @@
-use super::super::super::RNG;
+use bengine::random::RandomNumberGenerator;
 use legion::*;
 use nox_components::*;
 
-pub(crate) fn skill_check(ecs: &World, settler_id: usize, skill: Skill, difficulty: i32) -> i32 {
+pub(crate) fn skill_check(
+    ecs: &World,
+    rng: &mut RandomNumberGenerator,
+    settler_id: usize,
+    skill: Skill,
+    difficulty: i32,
+) -> i32 {
     let (skill_value, attr_bonus) = if let Some((skill, attr)) =
         <(&IdentityTag, &Skills, &Attributes)>::query()
             .iter(ecs)
@@ -15,7 +21,7 @@ pub(crate) fn skill_check(ecs: &World, settler_id: usize, skill: Skill, difficul
         (0, 0)
     };
 
-    let die_roll = RNG.lock().roll_dice(1, 20);
+    let die_roll = rng.roll_dice(1, 20);
     let modified_roll = die_roll + attr_bonus + skill_value;
     modified_roll - difficulty
 }
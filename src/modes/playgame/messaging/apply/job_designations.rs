@@ -1,34 +1,34 @@
 use bengine::geometry::*;
-use legion::*;
+use legion::{systems::CommandBuffer, *};
 use nox_components::*;
 
-pub fn become_miner(ecs: &mut World, id: usize) {
-    // Find the settler in question
-    let mut success = false;
-    if let Some(settler_pos) = <(&IdentityTag, &Position)>::query()
-        .filter(component::<Settler>())
-        .iter(ecs)
-        .filter(|(sid, _)| sid.0 == id)
-        .map(|(_, pos)| pos.as_point3())
-        .nth(0)
-    {
-        if find_closest_tool(ecs, ToolType::Digging, id, &settler_pos) {
-            success = true
-        }
-    }
+pub fn set_labor(ecs: &mut World, id: usize, labor: &Labor, priority: u8) {
+    let mut needs_tool = true;
+    <(&mut Settler, &IdentityTag)>::query()
+        .iter_mut(ecs)
+        .filter(|(_, sid)| sid.0 == id)
+        .for_each(|(settler, _)| {
+            settler.labors.set_priority(labor.clone(), priority);
+            if let Some(usage) = labor.tool() {
+                needs_tool = settler.labors.needs_tool(usage);
+            }
+        });
 
-    if success {
-        <(&mut Settler, &IdentityTag)>::query()
-            .iter_mut(ecs)
-            .filter(|(_, sid)| sid.0 == id)
-            .for_each(|(settler, _)| settler.miner = true);
+    if !needs_tool {
+        drop_associated_tool(ecs, labor.tool().unwrap(), id);
     }
 }
 
-pub fn become_lumberjack(ecs: &mut World, id: usize) {
-    println!("Become LJ");
-    // Find the settler in question
-    let mut success = false;
+/// Has a settler claim the nearest free tool of a kind, unless they already
+/// have one.
+pub fn claim_tool(ecs: &mut World, id: usize, usage: ToolType) {
+    let has_tool = <(&Claimed, &Tool)>::query()
+        .iter(ecs)
+        .any(|(claim, tool)| claim.by == id && tool.usage == usage);
+    if has_tool {
+        return;
+    }
+
     if let Some(settler_pos) = <(&IdentityTag, &Position)>::query()
         .filter(component::<Settler>())
         .iter(ecs)
@@ -36,34 +36,8 @@ pub fn become_lumberjack(ecs: &mut World, id: usize) {
         .map(|(_, pos)| pos.as_point3())
         .nth(0)
     {
-        println!("Found settler");
-        if find_closest_tool(ecs, ToolType::Chopping, id, &settler_pos) {
-            success = true
-        }
+        find_closest_tool(ecs, usage, id, &settler_pos);
     }
-
-    if success {
-        <(&mut Settler, &IdentityTag)>::query()
-            .iter_mut(ecs)
-            .filter(|(_, sid)| sid.0 == id)
-            .for_each(|(settler, _)| settler.lumberjack = true);
-    }
-}
-
-pub fn fire_miner(ecs: &mut World, id: usize) {
-    drop_associated_tool(ecs, ToolType::Digging, id);
-    <(&mut Settler, &IdentityTag)>::query()
-        .iter_mut(ecs)
-        .filter(|(_s, sid)| sid.0 == id)
-        .for_each(|(s, _)| s.miner = false);
-}
-
-pub fn fire_lumberjack(ecs: &mut World, id: usize) {
-    drop_associated_tool(ecs, ToolType::Chopping, id);
-    <(&mut Settler, &IdentityTag)>::query()
-        .iter_mut(ecs)
-        .filter(|(_s, sid)| sid.0 == id)
-        .for_each(|(s, _)| s.lumberjack = false);
 }
 
 fn find_closest_tool(ecs: &mut World, usage: ToolType, claimant: usize, position: &Point3) -> bool {
@@ -93,18 +67,26 @@ fn find_closest_tool(ecs: &mut World, usage: ToolType, claimant: usize, position
     return true;
 }
 
-fn drop_associated_tool(ecs: &World, usage: ToolType, id: usize) {
+/// Puts down a settler's tool, if they are carrying it, and gives up their
+/// claim on it.
+fn drop_associated_tool(ecs: &mut World, usage: ToolType, id: usize) {
     let item_req = Location::Carried { by: id };
 
-    let to_drop: Vec<(usize, usize)> = <(&Claimed, &Tool, &Position, &IdentityTag)>::query()
-        .iter(ecs)
-        .filter(|(claim, tool, pos, _tid)| {
-            claim.by == id && tool.usage == usage && pos.loc == item_req
-        })
-        .map(|(_claim, _tool, pos, id)| (pos.effective_location(ecs), id.0))
-        .collect();
+    let tools: Vec<(Entity, usize, usize, bool)> =
+        <(Entity, &Claimed, &Tool, &Position, &IdentityTag)>::query()
+            .iter(ecs)
+            .filter(|(_, claim, tool, _, _)| claim.by == id && tool.usage == usage)
+            .map(|(e, _claim, _tool, pos, tid)| {
+                (*e, pos.effective_location(ecs), tid.0, pos.loc == item_req)
+            })
+            .collect();
 
-    to_drop.iter().for_each(|(pos, tid)| {
-        super::super::drop_item(*tid, *pos);
+    let mut cmds = CommandBuffer::new(ecs);
+    tools.iter().for_each(|(e, pos, tid, carried)| {
+        cmds.remove_component::<Claimed>(*e);
+        if *carried {
+            super::super::drop_item(*tid, *pos);
+        }
     });
+    cmds.flush(ecs);
 }
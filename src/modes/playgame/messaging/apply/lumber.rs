@@ -1,18 +1,24 @@
 use super::super::{models_moved, vox_moved};
 use super::{skill_check, REGION};
-use bengine::{geometry::*, Palette};
+use bengine::{geometry::*, random::RandomNumberGenerator, Palette};
 use legion::*;
 use nox_components::*;
 use nox_spatial::idxmap;
 
-pub(crate) fn chop_tree(ecs: &mut World, actor_id: usize, tree_pos: usize, palette: &Palette) {
+pub(crate) fn chop_tree(
+    ecs: &mut World,
+    rng: &mut RandomNumberGenerator,
+    actor_id: usize,
+    tree_pos: usize,
+    palette: Option<&Palette>,
+) {
     println!("Chop tree");
     let mut to_remove = Vec::new();
     let mut to_spawn = Vec::new();
 
     // Identify a neighboring tree
     if let Some((tree_entity, tree_pos)) = locate_target(ecs, tree_pos) {
-        let skill_check_result = skill_check(ecs, actor_id, Skill::Lumberjack, 12);
+        let skill_check_result = skill_check(ecs, rng, actor_id, Skill::Lumberjack, 12);
         if skill_check_result > 0 {
             // Damage the tree
             if let Ok(mut te) = ecs.entry_mut(tree_entity) {
@@ -47,7 +53,7 @@ pub(crate) fn chop_tree(ecs: &mut World, actor_id: usize, tree_pos: usize, palet
                 tz,
                 &mut *rlock,
                 wood,
-                Some(palette),
+                palette,
             );
         }
         vox_moved();
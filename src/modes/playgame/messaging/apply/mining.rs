@@ -1,13 +1,19 @@
 use super::skill_check;
 use super::REGION;
-use bengine::{geometry::*, Palette};
+use bengine::{geometry::*, random::RandomNumberGenerator, Palette};
 use legion::*;
 use nox_components::*;
 use nox_planet::{StairsType, TileType};
 use nox_raws::MinesTo;
 use nox_spatial::*;
 
-pub(crate) fn dig_at(ecs: &mut World, actor_id: usize, pos: usize, palette: &Palette) {
+pub(crate) fn dig_at(
+    ecs: &mut World,
+    rng: &mut RandomNumberGenerator,
+    actor_id: usize,
+    pos: usize,
+    palette: Option<&Palette>,
+) {
     let mining_designations: Vec<(usize, MiningMode)> = <(&MiningMode, &Position)>::query()
         .iter(ecs)
         .map(|(mm, pos)| (pos.get_idx(), *mm))
@@ -31,16 +37,16 @@ pub(crate) fn dig_at(ecs: &mut World, actor_id: usize, pos: usize, palette: &Pal
     println!("Nearby jobs: {:?}", nearby);
 
     if !nearby.is_empty() {
-        if skill_check(ecs, actor_id, Skill::Mining, 12) > 0 {
+        if skill_check(ecs, rng, actor_id, Skill::Mining, 12) > 0 {
             nearby.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
             println!("Applying: {:?}", nearby[0]);
             let (mine_id, task, _distance) = nearby[0];
             match task {
                 MiningMode::Dig => {
                     println!("Changed tile");
-                    rlock.tile_types[mine_id] = TileType::Floor;
+                    rlock.set_tile_type(mine_id, TileType::Floor);
                     super::super::tile_dirty(mine_id);
-                    let mut material_idx = rlock.material_idx[mine_id];
+                    let mut material_idx = rlock.material(mine_id);
                     let mat_info = nox_raws::RAWS.read().materials.materials[material_idx].clone();
                     for mt in mat_info.mines_to.iter() {
                         let (x, y, z) = idxmap(mine_id);
@@ -54,7 +60,7 @@ pub(crate) fn dig_at(ecs: &mut World, actor_id: usize, pos: usize, palette: &Pal
                                     z,
                                     &mut rlock,
                                     material_idx,
-                                    Some(palette),
+                                    palette,
                                 );
                             }
                             MinesTo::Ore { name } => {
@@ -67,7 +73,7 @@ pub(crate) fn dig_at(ecs: &mut World, actor_id: usize, pos: usize, palette: &Pal
                                     z,
                                     &mut rlock,
                                     material_idx,
-                                    Some(palette),
+                                    palette,
                                 );
                             }
                         }
@@ -75,30 +81,39 @@ pub(crate) fn dig_at(ecs: &mut World, actor_id: usize, pos: usize, palette: &Pal
                 }
                 MiningMode::Channel => {
                     println!("Changed tile");
-                    rlock.tile_types[mine_id] = TileType::Empty;
-                    rlock.tile_types[mine_id - (REGION_WIDTH * REGION_HEIGHT)] = TileType::Floor;
+                    rlock.set_tile_type(mine_id, TileType::Empty);
+                    rlock.set_tile_type(mine_id - (REGION_WIDTH * REGION_HEIGHT), TileType::Floor);
                     super::super::tile_dirty(mine_id);
                     super::super::tile_dirty(mine_id - (REGION_WIDTH * REGION_HEIGHT));
                 }
                 MiningMode::Up => {
                     println!("Changed tile");
-                    rlock.tile_types[mine_id] = TileType::Stairs {
-                        direction: StairsType::Up,
-                    };
+                    rlock.set_tile_type(
+                        mine_id,
+                        TileType::Stairs {
+                            direction: StairsType::Up,
+                        },
+                    );
                     super::super::tile_dirty(mine_id);
                 }
                 MiningMode::Down => {
                     println!("Changed tile");
-                    rlock.tile_types[mine_id] = TileType::Stairs {
-                        direction: StairsType::Down,
-                    };
+                    rlock.set_tile_type(
+                        mine_id,
+                        TileType::Stairs {
+                            direction: StairsType::Down,
+                        },
+                    );
                     super::super::tile_dirty(mine_id);
                 }
                 MiningMode::UpDown => {
                     println!("Changed tile");
-                    rlock.tile_types[mine_id] = TileType::Stairs {
-                        direction: StairsType::UpDown,
-                    };
+                    rlock.set_tile_type(
+                        mine_id,
+                        TileType::Stairs {
+                            direction: StairsType::UpDown,
+                        },
+                    );
                     super::super::tile_dirty(mine_id);
                 }
                 _ => {}
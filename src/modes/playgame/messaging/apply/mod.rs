@@ -1,7 +1,7 @@
 use super::super::GameStateResource;
-use super::{JobStep, MOVER_LIST};
-use crate::modes::playgame::systems::REGION;
-use bengine::{geometry::DistanceAlg, Palette};
+use super::{JobStep, BLOCKED_PATHS, MOVER_LIST};
+use crate::modes::playgame::systems::{JobBoard, PathRequests, REGION};
+use bengine::{geometry::DistanceAlg, random::RandomNumberGenerator, Palette};
 use legion::{systems::CommandBuffer, *};
 use nox_components::*;
 use nox_planet::{Region, StairsType, TileType};
@@ -16,16 +16,24 @@ mod gamesystem;
 use gamesystem::*;
 mod mining;
 use mining::*;
+mod abandon;
+use abandon::*;
+mod work_orders;
+use work_orders::*;
 
-pub fn apply_jobs_queue(ecs: &mut World, resources: &mut Resources, palette: &Palette) {
+pub fn apply_jobs_queue(ecs: &mut World, resources: &mut Resources, palette: Option<&Palette>) {
     let mut vox_moved = false;
     let mut models_moved = false;
     let mut lights_changed = false;
     let mut tiles_dirty = Vec::new();
+    let mut jobs_changed = false;
     MOVER_LIST.lock().clear();
+    let mut rng = resources.get_mut::<RandomNumberGenerator>().unwrap();
+    let mut paths = resources.get_mut::<PathRequests>().unwrap();
     loop {
         let js = super::JOBS_QUEUE.lock().pop_front();
         if let Some(mut js) = js {
+            jobs_changed |= js.changes_jobs();
             match js {
                 JobStep::VoxMoved => vox_moved = true,
                 JobStep::ModelsMoved => models_moved = true,
@@ -35,14 +43,23 @@ pub fn apply_jobs_queue(ecs: &mut World, resources: &mut Resources, palette: &Pa
                     vox_moved = true;
                     lights_changed = true;
                 }
-                _ => apply(ecs, &mut js, palette),
+                JobStep::FollowJobPath { id } => follow_path(ecs, id, &mut paths),
+                _ => apply(ecs, &mut js, &mut rng, palette),
             }
         } else {
             break;
         }
     }
+    std::mem::drop(rng);
+    std::mem::drop(paths);
     movers(ecs, resources);
 
+    if jobs_changed {
+        if let Some(mut board) = resources.get_mut::<JobBoard>() {
+            board.is_dirty = true;
+        }
+    }
+
     if vox_moved || models_moved || lights_changed {
         let mut gs = resources.get_mut::<GameStateResource>();
         let gsr = gs.as_mut().unwrap();
@@ -79,7 +96,12 @@ fn movers(ecs: &mut World, resources: &mut Resources) {
     }
 }
 
-fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
+fn apply(
+    ecs: &mut World,
+    js: &mut JobStep,
+    rng: &mut RandomNumberGenerator,
+    palette: Option<&Palette>,
+) {
     match js {
         JobStep::EntityMoved { id, end } => {
             MOVER_LIST
@@ -87,6 +109,8 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
                 .insert(*id, (end.x as usize, end.y as usize, end.z as usize));
         }
         JobStep::JobChanged { id, new_job } => {
+            // Whatever they were stuck on, they are doing something new now
+            BLOCKED_PATHS.lock().remove(id);
             <(&mut MyTurn, &IdentityTag)>::query()
                 .iter_mut(ecs)
                 .filter(|(_, idt)| idt.0 == *id)
@@ -95,16 +119,12 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
                 });
         }
         JobStep::JobCancelled { id } => {
-            <(&mut MyTurn, &IdentityTag)>::query()
-                .iter_mut(ecs)
-                .filter(|(_, idt)| idt.0 == *id)
-                .for_each(|(mut turn, _)| {
-                    //REGION.write().jobs_board.restore_job(&turn.job);
-                    turn.job = JobType::None;
-                });
+            // The job board notices, and lets someone else take it
+            abandon_job(ecs, *id);
         }
         JobStep::JobConcluded { id } => {
             println!("Job finished");
+            BLOCKED_PATHS.lock().remove(id);
             <(&mut MyTurn, &IdentityTag)>::query()
                 .iter_mut(ecs)
                 .filter(|(_, idt)| idt.0 == *id)
@@ -118,9 +138,6 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
                     turn.job = JobType::None;
                 });
         }
-        JobStep::FollowJobPath { id } => {
-            follow_path(ecs, *id);
-        }
         JobStep::DropItem { id, location } => {
             println!("Dropping item #{}, at {}", id, location);
             <(&mut Position, &IdentityTag)>::query()
@@ -135,11 +152,14 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
                 });
             super::vox_moved();
         }
-        JobStep::RelinquishClaim { .. } => {
-            /*REGION
-            .write()
-            .jobs_board
-            .relinquish_claim(*tool_id, *tool_pos);*/
+        JobStep::RelinquishClaim { tool_id, .. } => {
+            let mut cmds = CommandBuffer::new(ecs);
+            <(Entity, &IdentityTag)>::query()
+                .filter(component::<Claimed>())
+                .iter(ecs)
+                .filter(|(_, id)| id.0 == *tool_id)
+                .for_each(|(e, _)| cmds.remove_component::<Claimed>(*e));
+            cmds.flush(ecs);
         }
         JobStep::EquipItem { id, tool_id } => {
             <(&mut Position, &IdentityTag)>::query()
@@ -173,7 +193,7 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
             super::vox_moved();
         }
         JobStep::TreeChop { id, tree_pos } => {
-            chop_tree(ecs, *id, *tree_pos, palette);
+            chop_tree(ecs, rng, *id, *tree_pos, palette);
         }
         JobStep::DeleteBuilding { building_id } => {
             let i = <(Entity, Read<Position>, Read<IdentityTag>)>::query()
@@ -220,19 +240,23 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
             cmds.flush(ecs);
         }
         JobStep::DigAt { pos, id } => {
-            dig_at(ecs, *id, *pos, palette);
+            dig_at(ecs, rng, *id, *pos, palette);
         }
-        JobStep::BecomeMiner { id } => {
-            become_miner(ecs, *id);
+        JobStep::SetLabor {
+            id,
+            labor,
+            priority,
+        } => {
+            set_labor(ecs, *id, labor, *priority);
         }
-        JobStep::BecomeLumberjack { id } => {
-            become_lumberjack(ecs, *id);
+        JobStep::ClaimTool { id, usage } => {
+            claim_tool(ecs, *id, *usage);
         }
-        JobStep::FireMiner { id } => {
-            fire_miner(ecs, *id);
+        JobStep::AddReactionOrder { workshop_id, order } => {
+            add_reaction_order(ecs, *workshop_id, order);
         }
-        JobStep::FireLumberjack { id } => {
-            fire_lumberjack(ecs, *id);
+        JobStep::RemoveReactionOrder { workshop_id, index } => {
+            remove_reaction_order(ecs, *workshop_id, *index);
         }
         JobStep::SpawnItem {
             pos,
@@ -250,7 +274,7 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
                     z,
                     &mut REGION.write(),
                     *material,
-                    Some(palette),
+                    palette,
                 );
             }
         }
@@ -454,17 +478,18 @@ fn apply(ecs: &mut World, js: &mut JobStep, palette: &Palette) {
 
             // Convert the tile into the right type
             let mut rlock = REGION.write();
-            rlock.tile_types[bpos] = match build_type {
+            let tile_type = match build_type {
                 1 => TileType::Stairs { direction: StairsType::Up },
                 2 => TileType::Stairs { direction: StairsType::Down },
                 3 => TileType::Stairs { direction: StairsType::UpDown },
                 4 => TileType::Floor,
                 _ => TileType::Solid
             };
+            rlock.set_tile_type(bpos, tile_type);
             rlock.set_flag(bpos, Region::CONSTRUCTED);
             rlock.set_flag(bpos, Region::SOLID);
             rlock.clear_flag(bpos, Region::CAN_STAND_HERE);
-            rlock.material_idx[bpos] = mat_idx;
+            rlock.set_material(bpos, mat_idx);
             super::super::tile_dirty(bpos);
             std::mem::drop(rlock);
 
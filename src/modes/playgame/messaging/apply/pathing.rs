@@ -1,39 +1,41 @@
-use super::MOVER_LIST;
+use super::{BLOCKED_PATHS, MOVER_LIST};
+use crate::modes::playgame::systems::{PathRequests, REGION};
 use legion::*;
 use nox_components::*;
+use nox_planet::pathfinding::PathCache;
 use nox_spatial::idxmap;
 
-pub(crate) fn follow_path(ecs: &mut World, id: usize) {
-    <(&mut MyTurn, &IdentityTag)>::query()
+/// A settler whose path is blocked, and who can't find another way to where
+/// they were going this many times running, gives up on their job.
+const MAX_REPLAN_FAILURES: u32 = 5;
+
+pub(crate) fn follow_path(ecs: &mut World, id: usize, paths: &mut PathRequests) {
+    <(&mut MyTurn, &Position, &IdentityTag)>::query()
         .iter_mut(ecs)
-        .filter(|(_, idt)| idt.0 == id)
-        .for_each(|(turn, _)| {
-            let path = match &mut turn.job {
-                JobType::CollectTool { step, .. } => match step {
-                    CollectToolSteps::TravelToTool { path } => Some(path),
-                    _ => None,
-                },
-                JobType::ConstructBuilding { step, .. } => match step {
-                    BuildingSteps::TravelToBuilding { path, .. } => Some(path),
-                    _ => None,
-                },
-                JobType::Construct { step, .. } => match step {
-                    ConstructionSteps::TravelToBuilding { path, .. } => Some(path),
-                    _ => None,
-                },
-                JobType::Haul { step, .. } => match step {
-                    HaulSteps::TravelToItem { path } => Some(path),
-                    HaulSteps::TravelToDestination { path } => Some(path),
-                    _ => None,
-                },
-                JobType::Reaction { step, .. } => match step {
-                    ReactionSteps::TravelToReaction { path } => Some(path),
-                    _ => None,
-                },
-                _ => None,
-            };
+        .filter(|(_, _, idt)| idt.0 == id)
+        .for_each(|(turn, pos, _)| {
+            let job = turn.job.clone();
+            if let Some(path) = turn.job.path_mut() {
+                // The map may have changed since the path was planned
+                if !PathCache::is_still_walkable(&REGION.read(), pos.get_idx(), path) {
+                    // A new path comes back as a job change; until then, wait
+                    if paths.is_pending(id) {
+                        return;
+                    }
+                    let mut blocked = BLOCKED_PATHS.lock();
+                    let failures = blocked.entry(id).or_insert(0);
+                    if *failures >= MAX_REPLAN_FAILURES {
+                        blocked.remove(&id);
+                        super::super::cancel_job(id);
+                    } else {
+                        *failures += 1;
+                        let goal = *path.last().unwrap();
+                        paths.request(id, pos.get_idx(), goal, &job, job.clone(), false);
+                    }
+                    return;
+                }
+                BLOCKED_PATHS.lock().remove(&id);
 
-            if let Some(path) = path {
                 let destination = path[0];
                 path.remove(0);
                 let (x, y, z) = idxmap(destination);
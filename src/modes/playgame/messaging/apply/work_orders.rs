@@ -0,0 +1,29 @@
+use legion::*;
+use nox_components::*;
+
+pub fn add_reaction_order(ecs: &mut World, workshop_id: usize, order: &ReactionOrder) {
+    let workshop = <(Entity, &IdentityTag)>::query()
+        .filter(component::<Workshop>())
+        .iter(ecs)
+        .find(|(_, id)| id.0 == workshop_id)
+        .map(|(e, _)| *e);
+
+    if let Some(mut entry) = workshop.and_then(|e| ecs.entry(e)) {
+        if let Ok(orders) = entry.get_component_mut::<ReactionOrders>() {
+            orders.0.push(order.clone());
+        } else {
+            entry.add_component(ReactionOrders(vec![order.clone()]));
+        }
+    }
+}
+
+pub fn remove_reaction_order(ecs: &mut World, workshop_id: usize, index: usize) {
+    <(&mut ReactionOrders, &IdentityTag)>::query()
+        .iter_mut(ecs)
+        .filter(|(_, id)| id.0 == workshop_id)
+        .for_each(|(orders, _)| {
+            if index < orders.0.len() {
+                orders.0.remove(index);
+            }
+        });
+}
@@ -1,5 +1,5 @@
 use bengine::geometry::Point3;
-use nox_components::JobType;
+use nox_components::{JobType, Labor, ReactionOrder, ToolType};
 
 pub enum JobStep {
     EntityMoved {
@@ -61,17 +61,22 @@ pub enum JobStep {
     TileDirty {
         pos: usize,
     },
-    BecomeMiner {
+    SetLabor {
         id: usize,
+        labor: Labor,
+        priority: u8,
     },
-    BecomeLumberjack {
+    ClaimTool {
         id: usize,
+        usage: ToolType,
     },
-    FireMiner {
-        id: usize,
+    AddReactionOrder {
+        workshop_id: usize,
+        order: ReactionOrder,
     },
-    FireLumberjack {
-        id: usize,
+    RemoveReactionOrder {
+        workshop_id: usize,
+        index: usize,
     },
     SpawnItem {
         pos: usize,
@@ -106,3 +111,27 @@ pub enum JobStep {
         by: usize,
     },
 }
+
+impl JobStep {
+    /// Whether the step can add work to the job board, or take it away.
+    pub fn changes_jobs(&self) -> bool {
+        match self {
+            JobStep::DeleteItem { .. }
+            | JobStep::DropItem { .. }
+            | JobStep::SpawnItem { .. }
+            | JobStep::DigAt { .. }
+            | JobStep::TreeChop { .. }
+            | JobStep::RelinquishClaim { .. }
+            | JobStep::JobCancelled { .. }
+            | JobStep::SetLabor { .. }
+            | JobStep::FinishBuilding { .. }
+            | JobStep::FinishConstruction { .. }
+            | JobStep::DeleteBuilding { .. }
+            | JobStep::RemoveHaulTag { .. }
+            | JobStep::UpdateBlueprint { .. }
+            | JobStep::CreateReactionJob { .. }
+            | JobStep::PerformReaction { .. } => true,
+            _ => false,
+        }
+    }
+}
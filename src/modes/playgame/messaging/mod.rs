@@ -7,12 +7,19 @@ use job_step::*;
 use legion::*;
 use queue_backend::*;
 
-use nox_components::JobType;
+use nox_components::{JobType, Labor, OrderGoal, ReactionOrder, ToolType};
 
-pub fn process_queues(ecs: &mut World, resources: &mut Resources, palette: &Palette) {
+pub fn process_queues(ecs: &mut World, resources: &mut Resources, palette: Option<&Palette>) {
     apply::apply_jobs_queue(ecs, resources, palette);
 }
 
+/// Throws away any messages still waiting, e.g. from a game that was just left.
+pub fn clear_queues() {
+    JOBS_QUEUE.lock().clear();
+    MOVER_LIST.lock().clear();
+    BLOCKED_PATHS.lock().clear();
+}
+
 pub fn entity_moved(id: usize, end: &Point3) {
     JOBS_QUEUE.lock().push_back(JobStep::EntityMoved {
         id,
@@ -125,22 +132,18 @@ pub fn tile_dirty(pos: usize) {
     jql.push_back(JobStep::TileDirty { pos });
 }
 
-pub fn become_miner(id: usize) {
-    JOBS_QUEUE.lock().push_back(JobStep::BecomeMiner { id });
+pub fn set_labor(id: usize, labor: Labor, priority: u8) {
+    JOBS_QUEUE.lock().push_back(JobStep::SetLabor {
+        id,
+        labor,
+        priority,
+    });
 }
 
-pub fn become_lumberjack(id: usize) {
+pub fn claim_tool(id: usize, usage: ToolType) {
     JOBS_QUEUE
         .lock()
-        .push_back(JobStep::BecomeLumberjack { id });
-}
-
-pub fn fire_miner(id: usize) {
-    JOBS_QUEUE.lock().push_back(JobStep::FireMiner { id });
-}
-
-pub fn fire_lumberjack(id: usize) {
-    JOBS_QUEUE.lock().push_back(JobStep::FireLumberjack { id });
+        .push_back(JobStep::ClaimTool { id, usage });
 }
 
 pub fn spawn_item(position: &usize, tag: &String, qty: &i32, material: usize) {
@@ -191,6 +194,22 @@ pub fn create_reaction_job(workshop_id: usize, reaction_tag: &str, components: &
     });
 }
 
+pub fn add_reaction_order(workshop_id: usize, reaction_tag: &str, goal: OrderGoal) {
+    JOBS_QUEUE.lock().push_back(JobStep::AddReactionOrder {
+        workshop_id,
+        order: ReactionOrder {
+            reaction_tag: reaction_tag.to_string(),
+            goal,
+        },
+    });
+}
+
+pub fn remove_reaction_order(workshop_id: usize, index: usize) {
+    JOBS_QUEUE
+        .lock()
+        .push_back(JobStep::RemoveReactionOrder { workshop_id, index });
+}
+
 pub fn perform_reaction(reaction_id: usize) {
     JOBS_QUEUE
         .lock()
@@ -10,3 +10,8 @@ lazy_static! {
     pub static ref MOVER_LIST: Mutex<HashMap<usize, (usize, usize, usize)>> =
         Mutex::new(HashMap::new());
 }
+
+lazy_static! {
+    /// How many times in a row each settler has failed to re-plan a blocked path.
+    pub static ref BLOCKED_PATHS: Mutex<HashMap<usize, u32>> = Mutex::new(HashMap::new());
+}
@@ -1,19 +1,26 @@
+mod headless;
 mod loadstate;
 mod messaging;
 mod play;
 mod render;
 mod run_state;
+mod savestate;
+#[cfg(test)]
+mod scenario;
+mod simulation;
 mod systems;
 mod ui;
 mod uniforms;
 
+pub use headless::run_headless;
+pub use loadstate::{LoadState, LOAD_STATE};
 pub use messaging::*;
 pub use play::PlayTheGame;
 pub use render::{
     Chunks, CursorPass, GBuffer, GrassPass, LightingPass, Models, ModelsPass, TerrainPass, VoxPass,
 };
 pub use run_state::*;
-pub use systems::RNG;
+pub use savestate::AutosaveSettings;
 pub use uniforms::{Camera, CameraUniform};
 
 pub struct GameStateResource {
@@ -23,6 +30,8 @@ pub struct GameStateResource {
     pub models_moved: bool,
     pub lights_changed: bool,
     pub dirty_tiles: Vec<usize>,
+    pub save_requested: bool,
+    pub autosave_requested: bool,
 }
 
 impl GameStateResource {
@@ -34,6 +43,8 @@ impl GameStateResource {
             models_moved: false,
             lights_changed: false,
             dirty_tiles: Vec::new(),
+            save_requested: false,
+            autosave_requested: false,
         }
     }
 
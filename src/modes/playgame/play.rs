@@ -1,12 +1,16 @@
 use super::{
-    loadstate::*, systems::REGION, Chunks, CursorPass, DesignMode, GBuffer, GrassPass,
-    LightingPass, ModelsPass, RunState, TerrainPass, VoxPass,
+    loadstate::*,
+    savestate::*,
+    simulation::*,
+    systems::{JobBoard, REGION},
+    AutosaveSettings, Chunks, CursorPass, DesignMode, GBuffer, GrassPass, LightingPass, ModelsPass,
+    RunState, TerrainPass, VoxPass,
 };
 use crate::{GameMode, NoxMode, SharedResources};
-use bengine::*;
+use bengine::{random::RandomNumberGenerator, *};
 use legion::*;
 use nox_components::{CameraOptions, Position};
-use nox_planet::{ConstructionMap, LumberMap, MiningMap};
+use nox_planet::{ConstructionMap, LumberMap, MiningMap, RallyMap};
 
 pub struct PlayTheGame {
     ready: bool,
@@ -62,11 +66,26 @@ impl PlayTheGame {
                 println!("Starting loader");
                 self.started_loader = true;
             }
-            std::thread::spawn(|| {
+            let slot = nox_planet::active_slot();
+            std::thread::spawn(move || {
                 LOAD_STATE.write().state = LoadState::Loading;
-                let lg = nox_planet::load_game();
+                let result = match slot {
+                    Some(slot) => {
+                        // Saving over an autosave should go back to the game it was made from
+                        if let Ok(Some(base)) =
+                            nox_planet::load_header(&slot).map(|h| h.autosave_of)
+                        {
+                            nox_planet::set_active_slot(base);
+                        }
+                        nox_planet::load_game(&slot).map_err(|e| e.to_string())
+                    }
+                    None => Err("No saved game was selected".to_string()),
+                };
                 println!("Loader process complete");
-                LOAD_STATE.write().state = LoadState::Loaded { game: lg };
+                LOAD_STATE.write().state = match result {
+                    Ok(game) => LoadState::Loaded { game },
+                    Err(error) => LoadState::Failed { error },
+                };
                 println!("Unlocked loader");
             });
         } else {
@@ -76,10 +95,18 @@ impl PlayTheGame {
                     // Do nothing while the loader spins
                 }
                 LoadState::Loaded { game } => {
+                    match nox_components::deserialize_world_binary(&game.ecs_data) {
+                        Ok(ecs) => self.ecs = ecs,
+                        Err(e) => {
+                            LOAD_STATE.write().state = LoadState::Failed {
+                                error: format!("Unable to read the saved entities: {}", e),
+                            };
+                            return;
+                        }
+                    }
                     LOAD_STATE.write().state = LoadState::Idle;
                     self.planet = Some(game.planet);
                     *REGION.write() = game.current_region;
-                    self.ecs = nox_components::deserialize_world(game.ecs_text);
 
                     let mut loader_lock = crate::modes::LOADER.write();
                     self.gbuffer = loader_lock.g_buffer.take();
@@ -103,20 +130,57 @@ impl PlayTheGame {
                         pass.uniforms.update_view_proj(&pass.camera);
                     }
 
-                    self.ecs_resources.insert(super::GameStateResource::new());
-                    self.ecs_resources.insert(RunState::Paused);
-                    self.ecs_resources.insert(MiningMap::new());
-                    self.ecs_resources.insert(LumberMap::new());
-                    self.ecs_resources.insert(ConstructionMap::new());
+                    let play_state = PlayState::from_text(&game.play_state_text);
+                    if let RunState::Design {
+                        mode: DesignMode::BuildingInfo { id },
+                    } = play_state.run_state
+                    {
+                        super::ui::setup_building_info(id, &self.ecs);
+                    }
+
+                    start_simulation(
+                        &mut self.ecs,
+                        &mut self.ecs_resources,
+                        self.planet.as_ref().unwrap(),
+                        play_state,
+                    );
                     println!("Finished loading");
                     self.ready = true;
                 }
 
-                LoadState::Idle => {}
+                LoadState::Idle | LoadState::Failed { .. } => {}
             }
         }
     }
 
+    fn play_state(&self) -> PlayState {
+        PlayState {
+            run_state: self.ecs_resources.get::<RunState>().unwrap().clone(),
+            autosave: self
+                .ecs_resources
+                .get::<AutosaveSettings>()
+                .unwrap()
+                .clone(),
+            rng: Some(
+                self.ecs_resources
+                    .get::<RandomNumberGenerator>()
+                    .unwrap()
+                    .clone(),
+            ),
+        }
+    }
+
+    fn save_game(&mut self) {
+        if !save_game(self.planet.as_ref().unwrap(), &self.ecs, self.play_state()) {
+            println!("Already saving, ignoring save request");
+        }
+    }
+
+    /// Returns false if another save is still running, so the autosave can be retried.
+    fn autosave_game(&mut self) -> bool {
+        autosave_game(self.planet.as_ref().unwrap(), &self.ecs, self.play_state())
+    }
+
     #[inline(always)]
     fn update_camera(&mut self) {
         if let Some(mut shared_state) = self.ecs_resources.get_mut::<super::GameStateResource>() {
@@ -150,32 +214,9 @@ impl PlayTheGame {
             }
             if !shared_state.dirty_tiles.is_empty() {
                 self.chunks.mark_dirty(&shared_state.dirty_tiles);
-                //TODO: This could be parallel
-                {
-                    let mut rlock = REGION.write();
-                    rlock.reset_all_flags();
-                    nox_planet::rebuild_flags(&mut rlock);
-                }
-
+                tiles_changed(&self.ecs_resources, &shared_state.dirty_tiles);
                 self.chunks.rebuild_all();
                 shared_state.dirty_tiles.clear();
-                self.ecs_resources
-                    .get_mut::<MiningMap>()
-                    .as_mut()
-                    .unwrap()
-                    .is_dirty = true;
-
-                self.ecs_resources
-                    .get_mut::<LumberMap>()
-                    .as_mut()
-                    .unwrap()
-                    .is_dirty = true;
-
-                self.ecs_resources
-                    .get_mut::<ConstructionMap>()
-                    .as_mut()
-                    .unwrap()
-                    .is_dirty = true;
             }
         }
     }
@@ -202,10 +243,15 @@ impl NoxMode for PlayTheGame {
 
     fn tick(&mut self, core: &mut Core, shared: &SharedResources) -> GameMode {
         use gui::*;
-        let result = GameMode::PlayGame;
+        let mut result = GameMode::PlayGame;
 
         if !self.ready {
             self.load();
+            if let LoadState::Failed { .. } = LOAD_STATE.read().state {
+                // The main menu reports the error; let the next attempt start afresh
+                self.started_loader = false;
+                result = GameMode::MainMenu;
+            }
             shared.quad_render.render(shared.background_image, core);
             let window = gui::Window::new(im_str!("Loading saved game - please wait"));
             window
@@ -257,9 +303,25 @@ impl NoxMode for PlayTheGame {
             super::messaging::process_queues(
                 &mut self.ecs,
                 &mut self.ecs_resources,
-                &self.palette.as_ref().unwrap(),
+                self.palette.as_ref(),
             );
 
+            // 1b -> Save if anyone asked for it
+            let (save_requested, autosave_requested) = {
+                let mut gs = self.ecs_resources.get_mut::<super::GameStateResource>();
+                let gsr = gs.as_mut().unwrap();
+                let requested = (gsr.save_requested, gsr.autosave_requested);
+                gsr.save_requested = false;
+                requested
+            };
+            if save_requested {
+                self.save_game();
+            }
+            if autosave_requested && !is_saving() && self.autosave_game() {
+                let mut gs = self.ecs_resources.get_mut::<super::GameStateResource>();
+                gs.as_mut().unwrap().autosave_requested = false;
+            }
+
             // Phase 2: Actually render stuff
             self.update_camera();
 
@@ -307,10 +369,11 @@ impl NoxMode for PlayTheGame {
 
             let mut rs = self.ecs_resources.get_mut::<RunState>();
             let run_state = rs.as_mut().unwrap();
+            let rally_point = self.ecs_resources.get::<RallyMap>().unwrap().rally_point;
             self.cursor_pass
                 .as_mut()
                 .unwrap()
-                .render(core, &mut self.ecs, &run_state);
+                .render(core, &mut self.ecs, &run_state, rally_point);
 
             // Phase 3: Draw the UI
             let zoom_request =
@@ -327,39 +390,66 @@ impl NoxMode for PlayTheGame {
                     super::ui::ZoomRequest::None => {}
                 },
             }
-            super::ui::draw_main_menu(&self.ecs, run_state, &core.imgui);
-            let mut mine_state = self.ecs_resources.get_mut::<MiningMap>();
-            let mut lumber_state = self.ecs_resources.get_mut::<LumberMap>();
-            let mut construction_state = self.ecs_resources.get_mut::<ConstructionMap>();
-            let ms = mine_state.as_mut().unwrap();
-            let ls = lumber_state.as_mut().unwrap();
-            let cs = construction_state.as_mut().unwrap();
-            design_ui(run_state, core, &mut self.ecs, ms, ls, cs);
+            {
+                let mut gs = self.ecs_resources.get_mut::<super::GameStateResource>();
+                let mut autosave = self.ecs_resources.get_mut::<AutosaveSettings>();
+                super::ui::draw_main_menu(
+                    &self.ecs,
+                    run_state,
+                    gs.as_mut().unwrap(),
+                    autosave.as_mut().unwrap(),
+                    &core.imgui,
+                );
+            }
+            if is_saving() {
+                let size = get_window_size();
+                gui::Window::new(im_str!("Saving"))
+                    .position([size.width as f32 - 150.0, 30.0], Condition::Always)
+                    .always_auto_resize(true)
+                    .no_decoration()
+                    .build(core.imgui, || {
+                        core.imgui
+                            .text_colored([1.0, 1.0, 0.0, 1.0], im_str!("\u{f0c7} Saving..."));
+                    });
+            }
+            if let Some(error) = last_save_error() {
+                gui::Window::new(im_str!("Unable to save"))
+                    .always_auto_resize(true)
+                    .collapsible(false)
+                    .build(core.imgui, || {
+                        core.imgui
+                            .text_colored([1.0, 0.0, 0.0, 1.0], &ImString::new(error));
+                        if core.imgui.button(im_str!("OK"), [100.0, 20.0]) {
+                            clear_save_error();
+                        }
+                    });
+            }
+            design_ui(run_state, core, &mut self.ecs, &self.ecs_resources);
         }
 
         result
     }
 }
 
-fn design_ui(
-    run_state: &mut RunState,
-    core: &mut Core,
-    ecs: &mut World,
-    mine_state: &mut MiningMap,
-    lumber_state: &mut LumberMap,
-    construction_map: &mut ConstructionMap,
-) {
+fn design_ui(run_state: &mut RunState, core: &mut Core, ecs: &mut World, resources: &Resources) {
     match run_state {
         RunState::Design {
             mode: DesignMode::Lumberjack,
         } => {
-            super::ui::lumberjack_display(core.imgui, ecs, &core.mouse_world_pos, lumber_state);
+            let mut lumber_map = resources.get_mut::<LumberMap>().unwrap();
+            super::ui::lumberjack_display(core.imgui, ecs, &core.mouse_world_pos, &mut lumber_map);
         }
         RunState::Design {
             mode: DesignMode::Buildings { bidx, .. },
         } => {
-            let (bidx, vox) =
-                super::ui::building_display(core.imgui, ecs, &core.mouse_world_pos, *bidx);
+            let mut job_board = resources.get_mut::<JobBoard>().unwrap();
+            let (bidx, vox) = super::ui::building_display(
+                core.imgui,
+                ecs,
+                &core.mouse_world_pos,
+                *bidx,
+                &mut job_board,
+            );
             *run_state = RunState::Design {
                 mode: DesignMode::Buildings { bidx, vox },
             };
@@ -367,7 +457,14 @@ fn design_ui(
         RunState::Design {
             mode: DesignMode::Mining { mode },
         } => {
-            super::ui::mining_display(core.imgui, ecs, &core.mouse_world_pos, mode, mine_state);
+            let mut mining_map = resources.get_mut::<MiningMap>().unwrap();
+            super::ui::mining_display(
+                core.imgui,
+                ecs,
+                &core.mouse_world_pos,
+                mode,
+                &mut mining_map,
+            );
         }
         RunState::Design {
             mode: DesignMode::SettlerList,
@@ -382,7 +479,27 @@ fn design_ui(
         RunState::Design {
             mode: DesignMode::Construction,
         } => {
-            super::ui::show_construction(core.imgui, ecs, &core.mouse_world_pos, construction_map);
+            let mut construction_map = resources.get_mut::<ConstructionMap>().unwrap();
+            let mut job_board = resources.get_mut::<JobBoard>().unwrap();
+            super::ui::show_construction(
+                core.imgui,
+                ecs,
+                &core.mouse_world_pos,
+                &mut construction_map,
+                &mut job_board,
+            );
+        }
+        RunState::Design {
+            mode: DesignMode::Rally,
+        } => {
+            let mut rally_map = resources.get_mut::<RallyMap>().unwrap();
+            super::ui::rally_display(core.imgui, &core.mouse_world_pos, &mut rally_map);
+        }
+        RunState::Design {
+            mode: DesignMode::Stockpiles,
+        } => {
+            let mut job_board = resources.get_mut::<JobBoard>().unwrap();
+            super::ui::stockpile_display(core.imgui, ecs, &core.mouse_world_pos, &mut job_board);
         }
         _ => {}
     }
@@ -67,18 +67,12 @@ impl Chunk {
 
     #[inline]
     fn calc_material(&self, idx: usize, region: &Region) -> (usize, bool) {
-        (
-            region.material_idx[idx],
-            region.flag(idx, Region::CONSTRUCTED),
-        )
+        (region.material(idx), region.flag(idx, Region::CONSTRUCTED))
     }
 
     #[inline]
     fn calc_floor_material(&self, idx: usize, region: &Region) -> (usize, bool) {
-        (
-            region.material_idx[idx],
-            region.flag(idx, Region::CONSTRUCTED),
-        )
+        (region.material(idx), region.flag(idx, Region::CONSTRUCTED))
     }
 
     #[inline]
@@ -106,7 +100,7 @@ impl Chunk {
         let mut count_empty = 0;
         self.cells
             .iter()
-            .for_each(|idx| match region.tile_types[*idx] {
+            .for_each(|idx| match region.tile_type(*idx) {
                 TileType::Empty => count_empty += 1,
                 _ => {}
             });
@@ -127,8 +121,8 @@ impl Chunk {
             for y in 0..CHUNK_SIZE {
                 for x in 0..CHUNK_SIZE {
                     let idx = mapidx(x + self.base.0, y + self.base.1, z + self.base.2);
-                    if region.revealed[idx] {
-                        match region.tile_types[idx] {
+                    if region.is_revealed(idx) {
+                        match region.tile_type(idx) {
                             TileType::Solid => {
                                 cubes.insert(idx, self.calc_material(idx, region));
                                 dcubes.insert(idx, self.calc_material(idx, region));
@@ -163,7 +157,7 @@ impl Chunk {
                             }
                         }
                         // Temporary water 2
-                        if region.water_level[idx] > 0 {
+                        if region.water_level(idx) > 0 {
                             floors.insert(idx, self.water_material());
                             dfloors.insert(idx, self.water_material());
                         }
@@ -12,6 +12,9 @@ pub struct CursorPass {
     pub vb: FloatBuffer<f32>,
     pub texture_id: usize,
     texture_bind_group: gpu::BindGroup,
+    rally_bind_group: gpu::BindGroup,
+    /// Set when the buffer holds the rally point, which is drawn with its own cursor.
+    showing_rally: bool,
 }
 
 impl CursorPass {
@@ -34,6 +37,10 @@ impl CursorPass {
             include_bytes!("../../../../../../resources/cursors/chop_cursor.png"),
             "Choppa",
         );
+        let rally_texture_id = TEXTURES.write().load_texture_from_bytes(
+            include_bytes!("../../../../../../resources/cursors/guard_cursor.png"),
+            "Rally",
+        );
 
         let mut ctx = RENDER_CONTEXT.write();
         let context = ctx.as_mut().unwrap();
@@ -72,28 +79,18 @@ impl CursorPass {
                     label: Some("texture_bind_group_layout"),
                 });
 
-        let texture_bind_group = context.device.create_bind_group(&gpu::BindGroupDescriptor {
-            layout: &texture_bind_group_layout,
-            entries: &[
-                gpu::BindGroupEntry {
-                    binding: 0,
-                    resource: gpu::BindingResource::Buffer(uniforms.uniform_buffer.slice(..)),
-                },
-                gpu::BindGroupEntry {
-                    binding: 1,
-                    resource: gpu::BindingResource::TextureView(
-                        TEXTURES.read().get_view(texture_id),
-                    ),
-                },
-                gpu::BindGroupEntry {
-                    binding: 2,
-                    resource: gpu::BindingResource::Sampler(
-                        TEXTURES.read().get_sampler(texture_id),
-                    ),
-                },
-            ],
-            label: Some("diffuse_bind_group"),
-        });
+        let texture_bind_group = cursor_bind_group(
+            &context.device,
+            &texture_bind_group_layout,
+            uniforms,
+            texture_id,
+        );
+        let rally_bind_group = cursor_bind_group(
+            &context.device,
+            &texture_bind_group_layout,
+            uniforms,
+            rally_texture_id,
+        );
 
         let pipeline_layout =
             context
@@ -160,6 +157,8 @@ impl CursorPass {
             vb,
             texture_id,
             texture_bind_group,
+            rally_bind_group,
+            showing_rally: false,
         }
     }
 
@@ -303,15 +302,95 @@ impl CursorPass {
         self.vb.update_buffer();
     }
 
-    pub fn render(&mut self, core: &Core, ecs: &World, run_state: &RunState) {
+    fn stockpiles(&mut self, ecs: &World, mouse_world_pos: &(usize, usize, usize)) {
         self.vb.clear();
-        if let RunState::Design { mode } = run_state {
-            match mode {
+        <&Stockpile>::query()
+            .iter(ecs)
+            .flat_map(|stockpile| stockpile.tiles.iter())
+            .for_each(|idx| {
+                let (x, y, z) = idxmap(*idx);
+                add_cube_geometry(
+                    &mut self.vb.data,
+                    x as f32,
+                    y as f32,
+                    z as f32,
+                    1.0,
+                    1.0,
+                    1.0,
+                    1.0,
+                );
+            });
+        add_cube_geometry(
+            &mut self.vb.data,
+            mouse_world_pos.0 as f32,
+            mouse_world_pos.1 as f32,
+            mouse_world_pos.2 as f32,
+            1.0,
+            1.0,
+            1.0,
+            1.0,
+        );
+        self.vb.update_buffer();
+    }
+
+    fn rally(
+        &mut self,
+        rally_point: Option<usize>,
+        mouse_world_pos: Option<&(usize, usize, usize)>,
+    ) {
+        self.vb.clear();
+        self.showing_rally = true;
+        if let Some(idx) = rally_point {
+            let (x, y, z) = idxmap(idx);
+            add_cube_geometry(
+                &mut self.vb.data,
+                x as f32,
+                y as f32,
+                z as f32,
+                1.0,
+                1.0,
+                1.0,
+                1.0,
+            );
+        }
+        if let Some(mouse_world_pos) = mouse_world_pos {
+            add_cube_geometry(
+                &mut self.vb.data,
+                mouse_world_pos.0 as f32,
+                mouse_world_pos.1 as f32,
+                mouse_world_pos.2 as f32,
+                1.0,
+                1.0,
+                1.0,
+                1.0,
+            );
+        }
+        if self.vb.len() == 0 {
+            return;
+        }
+        self.vb.update_buffer();
+    }
+
+    pub fn render(
+        &mut self,
+        core: &Core,
+        ecs: &World,
+        run_state: &RunState,
+        rally_point: Option<usize>,
+    ) {
+        self.vb.clear();
+        self.showing_rally = false;
+        match run_state {
+            RunState::Design { mode } => match mode {
                 DesignMode::Lumberjack => self.lumberjack(ecs),
                 DesignMode::Mining { mode } => self.mining(ecs, mode, &core.mouse_world_pos),
                 DesignMode::Construction => self.construction(ecs, &core.mouse_world_pos),
+                DesignMode::Rally => self.rally(rally_point, Some(&core.mouse_world_pos)),
+                DesignMode::Stockpiles => self.stockpiles(ecs, &core.mouse_world_pos),
                 _ => {}
-            }
+            },
+            // Keep showing where everyone has been called to
+            _ => self.rally(rally_point, None),
         }
 
         if self.vb.len() == 0 {
@@ -346,7 +425,11 @@ impl CursorPass {
             });
 
             rpass.set_pipeline(&self.render_pipeline);
-            rpass.set_bind_group(0, &self.texture_bind_group, &[]);
+            if self.showing_rally {
+                rpass.set_bind_group(0, &self.rally_bind_group, &[]);
+            } else {
+                rpass.set_bind_group(0, &self.texture_bind_group, &[]);
+            }
 
             if self.vb.len() > 0 {
                 rpass.set_vertex_buffer(0, self.vb.buffer.as_ref().unwrap().slice(..));
@@ -356,3 +439,29 @@ impl CursorPass {
         context.queue.submit(Some(encoder.finish()));
     }
 }
+
+fn cursor_bind_group(
+    device: &gpu::Device,
+    layout: &gpu::BindGroupLayout,
+    uniforms: &CameraUniform,
+    texture_id: usize,
+) -> gpu::BindGroup {
+    device.create_bind_group(&gpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            gpu::BindGroupEntry {
+                binding: 0,
+                resource: gpu::BindingResource::Buffer(uniforms.uniform_buffer.slice(..)),
+            },
+            gpu::BindGroupEntry {
+                binding: 1,
+                resource: gpu::BindingResource::TextureView(TEXTURES.read().get_view(texture_id)),
+            },
+            gpu::BindGroupEntry {
+                binding: 2,
+                resource: gpu::BindingResource::Sampler(TEXTURES.read().get_sampler(texture_id)),
+            },
+        ],
+        label: Some("diffuse_bind_group"),
+    })
+}
@@ -161,16 +161,14 @@ pub fn build_vox_instances2(
     let region = crate::modes::playgame::systems::REGION.read();
     use nox_planet::{StairsType, TileType};
     region
-        .tile_types
-        .iter()
-        .enumerate()
-        .filter(|(_idx, tt)| match tt {
+        .find_tiles(|tt| match tt {
             TileType::Stairs { .. } => true,
             _ => false,
         })
-        .map(|(idx, tt)| {
-            let (x, y, z) = nox_spatial::idxmap(idx);
-            (tt, x as f32, y as f32, z as f32)
+        .iter()
+        .map(|idx| {
+            let (x, y, z) = nox_spatial::idxmap(*idx);
+            (region.tile_type(*idx), x as f32, y as f32, z as f32)
         })
         .filter(|(_tt, x, y, z)| {
             *z as usize > camera_z - LAYERS_DOWN
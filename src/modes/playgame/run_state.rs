@@ -1,6 +1,7 @@
 use nox_components::MiningMode;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub enum RunState {
     Paused,
     SlowMo,
@@ -9,7 +10,7 @@ pub enum RunState {
     Design { mode: DesignMode },
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub enum DesignMode {
     Lumberjack,
     Buildings { bidx: i32, vox: Option<usize> },
@@ -17,4 +18,6 @@ pub enum DesignMode {
     SettlerList,
     BuildingInfo { id: usize },
     Construction,
+    Rally,
+    Stockpiles,
 }
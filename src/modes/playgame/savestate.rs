@@ -0,0 +1,177 @@
+use super::RunState;
+use bengine::random::RandomNumberGenerator;
+use legion::*;
+use nox_planet::{Planet, SaveHeader, SavedGame};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq)]
+pub enum SaveState {
+    Idle,
+    Saving,
+}
+
+pub struct GameSaver {
+    pub state: SaveState,
+    pub last_error: Option<String>,
+}
+
+lazy_static! {
+    pub static ref SAVE_STATE: RwLock<GameSaver> = RwLock::new(GameSaver {
+        state: SaveState::Idle,
+        last_error: None
+    });
+}
+
+/// How often the game saves itself, in game time, and how many autosaves to keep.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub keep: usize,
+    /// Calendar minute at which the next autosave is due; worked out on the first tick.
+    #[serde(skip)]
+    pub next_due: Option<u64>,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 24 * 60,
+            keep: 3,
+            next_due: None,
+        }
+    }
+}
+
+/// Play-mode state that lives outside of the ECS, but still needs to survive a save.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayState {
+    pub run_state: RunState,
+    #[serde(default)]
+    pub autosave: AutosaveSettings,
+    /// The simulation's dice. Older saves don't have one, and get one seeded
+    /// from the world instead.
+    #[serde(default)]
+    pub rng: Option<RandomNumberGenerator>,
+}
+
+impl PlayState {
+    pub fn from_text(text: &Option<String>) -> Self {
+        if let Some(text) = text {
+            if let Ok(state) = ron::from_str(text) {
+                return state;
+            }
+            println!("Unable to read play state, starting paused");
+        }
+        Self {
+            run_state: RunState::Paused,
+            autosave: AutosaveSettings::default(),
+            rng: None,
+        }
+    }
+}
+
+pub fn is_saving() -> bool {
+    SAVE_STATE.read().state == SaveState::Saving
+}
+
+pub fn last_save_error() -> Option<String> {
+    SAVE_STATE.read().last_error.clone()
+}
+
+pub fn clear_save_error() {
+    SAVE_STATE.write().last_error = None;
+}
+
+fn current_slot(planet: &Planet) -> String {
+    let slot = nox_planet::active_slot()
+        .unwrap_or_else(|| nox_planet::unused_slot_name(&format!("world-{}", planet.rng_seed)));
+    nox_planet::set_active_slot(&slot);
+    slot
+}
+
+/// Picks the autosave slot to write next: an unused one if there is room,
+/// otherwise the oldest.
+fn autosave_slot(base: &str, keep: usize) -> String {
+    let mut oldest: Option<(u64, String)> = None;
+    for n in 1..=keep.max(1) {
+        let slot = format!("{}-autosave-{}", base, n);
+        match nox_planet::load_header(&slot) {
+            Err(_) => return slot,
+            Ok(header) => {
+                if oldest.as_ref().map_or(true, |(t, _)| header.saved_at < *t) {
+                    oldest = Some((header.saved_at, slot));
+                }
+            }
+        }
+    }
+    oldest.unwrap().1
+}
+
+/// Takes a snapshot of the game and writes it to disk on a background thread.
+/// Returns false if a save is already in progress.
+pub fn save_game(planet: &Planet, ecs: &World, play_state: PlayState) -> bool {
+    let slot = current_slot(planet);
+    save_snapshot(&slot, None, planet, ecs, play_state)
+}
+
+/// As `save_game`, but writes to the next of the rotating autosave slots.
+pub fn autosave_game(planet: &Planet, ecs: &World, play_state: PlayState) -> bool {
+    let base = current_slot(planet);
+    let slot = autosave_slot(&base, play_state.autosave.keep);
+    save_snapshot(&slot, Some(base), planet, ecs, play_state)
+}
+
+fn save_snapshot(
+    slot: &str,
+    autosave_of: Option<String>,
+    planet: &Planet,
+    ecs: &World,
+    play_state: PlayState,
+) -> bool {
+    {
+        let mut lock = SAVE_STATE.write();
+        if lock.state == SaveState::Saving {
+            return false;
+        }
+        lock.state = SaveState::Saving;
+        lock.last_error = None;
+    }
+
+    // Snapshotting has to happen on the main thread; compression and disk access don't.
+    let region = super::systems::REGION.read().clone();
+    let mut header = SaveHeader::new(slot, planet, &region, ecs);
+    header.autosave_of = autosave_of;
+    let ecs_data = match nox_components::serialize_world_binary(ecs) {
+        Ok(data) => data,
+        Err(e) => {
+            let mut lock = SAVE_STATE.write();
+            lock.state = SaveState::Idle;
+            lock.last_error = Some(format!("Unable to encode the game: {}", e));
+            return true;
+        }
+    };
+    let snapshot = SavedGame {
+        planet: planet.clone(),
+        current_region: region,
+        ecs_data,
+        play_state_text: Some(ron::to_string(&play_state).unwrap()),
+    };
+
+    std::thread::spawn(move || {
+        println!("Saving game");
+        let result = nox_planet::save_world(header, snapshot);
+        let mut lock = SAVE_STATE.write();
+        lock.state = SaveState::Idle;
+        match result {
+            Ok(()) => println!("Game saved"),
+            Err(e) => {
+                println!("Save failed: {}", e);
+                lock.last_error = Some(e.to_string());
+            }
+        }
+    });
+    true
+}
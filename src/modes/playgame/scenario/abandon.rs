@@ -0,0 +1,22 @@
+use super::*;
+
+#[test]
+fn cancelled_hauls_drop_their_load() {
+    let mut scenario = Scenario::new();
+    let (settler, ore) = scenario.smelter_room();
+
+    let carrying = scenario.run_until(500, |s| {
+        s.location_of(ore) == Location::Carried { by: settler }
+    });
+    assert!(carrying, "Settler never picked up the ore");
+
+    messaging::cancel_job(settler);
+    scenario.tick();
+    let (x, y, z) = scenario.position_of(settler);
+    let feet = mapidx(x, y, z);
+    assert_eq!(scenario.location_of(ore), Location::Tile { idx: feet });
+    assert_eq!(scenario.haul_in_progress(ore), None);
+
+    let done = scenario.run_until(5000, |s| s.count_items("block") >= 2);
+    assert!(done, "No blocks after {} ticks", scenario.ticks);
+}
@@ -0,0 +1,20 @@
+use super::*;
+
+#[test]
+fn jobs_are_released_when_their_worker_is_gone() {
+    let mut scenario = Scenario::new();
+    let (settler, _) = scenario.smelter_room();
+    let hauling = scenario.run_until(500, |s| match s.job_of(settler) {
+        JobType::Haul { .. } => true,
+        _ => false,
+    });
+    assert!(hauling, "Settler never took a haul job");
+
+    scenario.despawn(settler);
+    scenario.spawn_settler(129, 129, GROUND);
+
+    let done = scenario.run_until(5000, |s| {
+        s.count_items("ore") == 0 && s.count_items("charcoal") == 0 && s.count_items("block") >= 2
+    });
+    assert!(done, "No blocks after {} ticks", scenario.ticks);
+}
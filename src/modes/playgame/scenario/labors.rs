@@ -0,0 +1,20 @@
+use super::*;
+
+#[test]
+fn settlers_only_do_the_labors_they_are_set_to() {
+    let mut scenario = Scenario::new();
+    let (settler, _) = scenario.smelter_room();
+    scenario.set_labor(settler, Labor::Hauling, 0);
+    scenario.set_labor(settler, Labor::of_skill("Carpentry"), 0);
+
+    scenario.run_until(500, |s| {
+        assert_eq!(s.job_of(settler), JobType::None);
+        false
+    });
+    assert_eq!(scenario.count_items("block"), 0);
+
+    scenario.set_labor(settler, Labor::Hauling, 1);
+    scenario.set_labor(settler, Labor::of_skill("Carpentry"), 1);
+    let done = scenario.run_until(5000, |s| s.count_items("block") >= 2);
+    assert!(done, "No blocks after {} ticks", scenario.ticks);
+}
@@ -0,0 +1,27 @@
+use super::*;
+
+fn marked_trees(scenario: &Scenario) -> usize {
+    <&Tree>::query()
+        .iter(&scenario.ecs)
+        .filter(|t| t.chop)
+        .count()
+}
+
+#[test]
+fn lumberjack_fells_only_the_marked_trees() {
+    let mut scenario = Scenario::new();
+    scenario.carve_room(120, 120, 130, 130, GROUND);
+    let settler = scenario.spawn_settler(121, 121, GROUND);
+    scenario.spawn_item("fire_axe", 123, 121, GROUND, "Plasteel");
+    scenario.spawn_tree(125, 125, GROUND);
+    scenario.spawn_tree(128, 128, GROUND);
+    scenario.spawn_tree(128, 122, GROUND);
+    scenario.designate_felling(125, 125, GROUND);
+    scenario.designate_felling(128, 128, GROUND);
+    scenario.make_lumberjack(settler);
+
+    let done = scenario.run_until(5000, |s| marked_trees(s) == 0);
+    assert!(done, "Marked trees left after {} ticks", scenario.ticks);
+    assert_eq!(scenario.count::<Tree>(), 1);
+    assert_eq!(scenario.count_items("wood_log"), 2);
+}
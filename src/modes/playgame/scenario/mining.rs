@@ -0,0 +1,38 @@
+use super::*;
+
+fn dig_site(scenario: &mut Scenario) {
+    for y in 122..=124 {
+        scenario.set_tile(127, y, GROUND, TileType::Solid, "Granite");
+        scenario.designate_mining(127, y, GROUND);
+    }
+}
+
+#[test]
+fn miner_collects_a_pick_and_digs_every_designated_tile() {
+    let mut scenario = Scenario::new();
+    scenario.carve_room(120, 120, 126, 126, GROUND);
+    dig_site(&mut scenario);
+    let settler = scenario.spawn_settler(121, 121, GROUND);
+    scenario.spawn_item("pickaxe", 124, 125, GROUND, "Plasteel");
+    scenario.make_miner(settler);
+
+    let done = scenario.run_until(5000, |s| s.count::<MiningMode>() == 0);
+    assert!(done, "Designations left after {} ticks", scenario.ticks);
+    for y in 122..=124 {
+        assert_eq!(scenario.tile_type(127, y, GROUND), TileType::Floor);
+    }
+    assert_eq!(scenario.count_items("stone_boulder"), 3);
+}
+
+#[test]
+fn settlers_without_a_pick_leave_designations_alone() {
+    let mut scenario = Scenario::new();
+    scenario.carve_room(120, 120, 126, 126, GROUND);
+    dig_site(&mut scenario);
+    let settler = scenario.spawn_settler(121, 121, GROUND);
+    scenario.make_miner(settler);
+
+    scenario.run_until(500, |_| false);
+    assert_eq!(scenario.count::<MiningMode>(), 3);
+    assert_eq!(scenario.tile_type(127, 123, GROUND), TileType::Solid);
+}
@@ -0,0 +1,387 @@
+//! Small, hand-built worlds for testing the job AI. A scenario starts as solid
+//! granite; tests carve out rooms, place settlers and items, and then run the
+//! real simulation schedule until something happens or the budget runs out.
+use super::{
+    messaging,
+    savestate::{AutosaveSettings, PlayState},
+    simulation::*,
+    systems::{JobBoard, REGION},
+    RunState,
+};
+use bengine::random::RandomNumberGenerator;
+use legion::*;
+use nox_components::*;
+use nox_planet::{LumberMap, MiningMap, Planet, RallyMap, Region, SavedGame, TileType};
+use nox_spatial::{idxmap, mapidx};
+use parking_lot::{Mutex, MutexGuard};
+use std::sync::Once;
+
+mod abandon;
+mod headless;
+mod job_board;
+mod labors;
+mod lumberjack;
+mod mining;
+mod pathing;
+mod rally;
+mod reactions;
+mod stockpiles;
+mod work_orders;
+
+lazy_static! {
+    /// The region and the message queues are global, so scenarios have to
+    /// take turns.
+    static ref SCENARIO_LOCK: Mutex<()> = Mutex::new(());
+}
+
+static LOAD_RAWS: Once = Once::new();
+
+/// The z level scenarios are usually built on.
+pub const GROUND: usize = 128;
+
+pub struct Scenario {
+    pub ecs: World,
+    pub resources: Resources,
+    pub ticks: usize,
+    schedule: Schedule,
+    dirty_tiles: Vec<usize>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Scenario {
+    /// Starts a new scenario: a region of solid granite with nothing in it,
+    /// running at full speed.
+    pub fn new() -> Self {
+        let lock = SCENARIO_LOCK.lock();
+        LOAD_RAWS.call_once(load_raws_headless);
+
+        {
+            let mut region = REGION.write();
+            *region = Region::initial();
+            region.fill_tile_types(TileType::Solid);
+            nox_planet::rebuild_flags(&mut region);
+        }
+
+        let mut ecs = World::default();
+        ecs.push((
+            IdentityTag::new(),
+            Calendar {
+                year: 2525,
+                month: 0,
+                day: 0,
+                hour: 0,
+                minute: 0,
+                second: 0,
+            },
+        ));
+
+        let mut play_state = PlayState::from_text(&None);
+        play_state.run_state = RunState::FullSpeed;
+        play_state.autosave.enabled = false;
+        let mut resources = Resources::default();
+        start_simulation(&mut ecs, &mut resources, &planet(), play_state);
+
+        Self {
+            ecs,
+            resources,
+            ticks: 0,
+            schedule: super::systems::build_scheduler(),
+            dirty_tiles: Vec::new(),
+            _lock: lock,
+        }
+    }
+
+    /// Hollows out a floored room covering (x1,y1) to (x2,y2), inclusive.
+    pub fn carve_room(&mut self, x1: usize, y1: usize, x2: usize, y2: usize, z: usize) {
+        for y in y1..=y2 {
+            for x in x1..=x2 {
+                self.set_tile(x, y, z, TileType::Floor, "Granite");
+            }
+        }
+    }
+
+    pub fn set_tile(&mut self, x: usize, y: usize, z: usize, tile_type: TileType, material: &str) {
+        let idx = mapidx(x, y, z);
+        let mut region = REGION.write();
+        region.set_tile_type(idx, tile_type);
+        region.set_material(idx, material_idx(material));
+        self.dirty_tiles.push(idx);
+    }
+
+    pub fn tile_type(&self, x: usize, y: usize, z: usize) -> TileType {
+        REGION.read().tile_type(mapidx(x, y, z))
+    }
+
+    /// Spawns a settler, who isn't yet a miner or lumberjack. Returns their id.
+    pub fn spawn_settler(&mut self, x: usize, y: usize, z: usize) -> usize {
+        let mut rng = self.resources.get_mut::<RandomNumberGenerator>().unwrap();
+        nox_planet::spawn_settler(&mut self.ecs, &mut rng, x, y, z, region_idx(), None)
+    }
+
+    /// Drops an item on the ground. Returns its id.
+    pub fn spawn_item(&mut self, tag: &str, x: usize, y: usize, z: usize, material: &str) -> usize {
+        let id = nox_components::spawner::spawn_item_on_ground(
+            &mut self.ecs,
+            tag,
+            x,
+            y,
+            z,
+            region_idx(),
+            material_idx(material),
+            None,
+        )
+        .unwrap();
+        self.resources.get_mut::<JobBoard>().unwrap().is_dirty = true;
+        id
+    }
+
+    /// Places a finished building. Returns its id.
+    pub fn spawn_building(&mut self, tag: &str, x: usize, y: usize, z: usize) -> usize {
+        nox_components::spawner::spawn_building(
+            &mut self.ecs,
+            tag,
+            mapidx(x, y, z),
+            region_idx(),
+            true,
+            &[],
+        )
+    }
+
+    pub fn spawn_tree(&mut self, x: usize, y: usize, z: usize) {
+        nox_components::spawner::spawn_tree(&mut self.ecs, x, y, z, region_idx(), 0, 1.0);
+    }
+
+    /// A room with a smelter in it, and the ore and charcoal it needs lying
+    /// about waiting to be hauled over. Returns the settler and the ore.
+    pub fn smelter_room(&mut self) -> (usize, usize) {
+        self.carve_room(120, 120, 130, 130, GROUND);
+        self.spawn_building("smelter", 127, 127, GROUND);
+        let ore = self.spawn_item("ore", 121, 128, GROUND, "Bauxite");
+        self.spawn_item("charcoal", 128, 121, GROUND, "Wood");
+        let settler = self.spawn_settler(121, 121, GROUND);
+        (settler, ore)
+    }
+
+    /// Removes an entity from the world outright, as if it had never been.
+    pub fn despawn(&mut self, id: usize) {
+        let entity = <(Entity, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, idt)| idt.0 == id)
+            .map(|(e, _)| *e)
+            .unwrap();
+        self.ecs.remove(entity);
+    }
+
+    /// Sets a settler to mining; they claim a pick when they take a job.
+    pub fn make_miner(&mut self, settler_id: usize) {
+        self.set_labor(settler_id, Labor::Mining, 1);
+    }
+
+    /// Sets a settler to felling trees; they claim an axe when they take a job.
+    pub fn make_lumberjack(&mut self, settler_id: usize) {
+        self.set_labor(settler_id, Labor::Lumber, 1);
+    }
+
+    /// Changes how keen a settler is on a labor, as the settler list does.
+    pub fn set_labor(&mut self, settler_id: usize, labor: Labor, priority: u8) {
+        messaging::set_labor(settler_id, labor, priority);
+        messaging::process_queues(&mut self.ecs, &mut self.resources, None);
+    }
+
+    /// Queues a work order at a workshop, as the building info window does.
+    pub fn queue_order(&mut self, workshop_id: usize, reaction_tag: &str, goal: OrderGoal) {
+        messaging::add_reaction_order(workshop_id, reaction_tag, goal);
+        messaging::process_queues(&mut self.ecs, &mut self.resources, None);
+    }
+
+    /// Designates a tile to be dug out, as the mining UI does.
+    pub fn designate_mining(&mut self, x: usize, y: usize, z: usize) {
+        self.ecs.push((
+            MiningMode::Dig,
+            Position::with_tile(x, y, z, region_idx(), (1, 1, 1)),
+        ));
+        self.resources.get_mut::<MiningMap>().unwrap().is_dirty = true;
+    }
+
+    /// Sets aside (x1,y1) to (x2,y2), inclusive, as a stockpile, as the
+    /// stockpile UI does. Returns its id.
+    pub fn designate_stockpile(
+        &mut self,
+        x1: usize,
+        y1: usize,
+        x2: usize,
+        y2: usize,
+        z: usize,
+        filter: StockpileFilter,
+    ) -> usize {
+        let mut tiles = Vec::new();
+        for y in y1..=y2 {
+            for x in x1..=x2 {
+                tiles.push(mapidx(x, y, z));
+            }
+        }
+        let id = IdentityTag::new();
+        let stockpile_id = id.0;
+        self.ecs.push((id, Stockpile { tiles, filter }));
+        self.resources.get_mut::<JobBoard>().unwrap().is_dirty = true;
+        stockpile_id
+    }
+
+    /// Marks the tree at a tile for felling, as the lumberjack UI does.
+    pub fn designate_felling(&mut self, x: usize, y: usize, z: usize) {
+        let idx = mapidx(x, y, z);
+        <(&mut Tree, &Position)>::query()
+            .iter_mut(&mut self.ecs)
+            .filter(|(_, pos)| pos.get_idx() == idx)
+            .for_each(|(tree, _)| tree.chop = true);
+        self.resources.get_mut::<LumberMap>().unwrap().is_dirty = true;
+    }
+
+    /// Calls everyone to a rally point, or stands them down, as the rally
+    /// UI does.
+    pub fn set_rally_point(&mut self, rally_point: Option<(usize, usize, usize)>) {
+        self.resources
+            .get_mut::<RallyMap>()
+            .unwrap()
+            .set_rally_point(rally_point.map(|(x, y, z)| mapidx(x, y, z)));
+    }
+
+    /// The scenario as it would be saved, to load elsewhere.
+    pub fn saved_game(&mut self) -> SavedGame {
+        self.update_tiles();
+        let play_state = PlayState {
+            run_state: RunState::FullSpeed,
+            autosave: AutosaveSettings::default(),
+            rng: Some(
+                self.resources
+                    .get::<RandomNumberGenerator>()
+                    .unwrap()
+                    .clone(),
+            ),
+        };
+        SavedGame {
+            planet: planet(),
+            current_region: REGION.read().clone(),
+            ecs_data: serialize_world_binary(&self.ecs).unwrap(),
+            play_state_text: Some(ron::to_string(&play_state).unwrap()),
+        }
+    }
+
+    fn update_tiles(&mut self) {
+        if !self.dirty_tiles.is_empty() {
+            tiles_changed(&self.resources, &self.dirty_tiles);
+            self.dirty_tiles.clear();
+        }
+    }
+
+    /// Runs a single simulation tick.
+    pub fn tick(&mut self) {
+        self.update_tiles();
+        headless_tick(&mut self.schedule, &mut self.ecs, &mut self.resources);
+        self.ticks += 1;
+    }
+
+    /// Ticks until `done` returns true, giving up after `max_ticks`. Returns
+    /// whether `done` was reached.
+    pub fn run_until<F: Fn(&Self) -> bool>(&mut self, max_ticks: usize, done: F) -> bool {
+        for _ in 0..max_ticks {
+            if done(self) {
+                return true;
+            }
+            self.tick();
+        }
+        done(self)
+    }
+
+    /// How many items with a given tag exist, wherever they are.
+    pub fn count_items(&self, tag: &str) -> usize {
+        <(&Item, &Tag)>::query()
+            .iter(&self.ecs)
+            .filter(|(_, t)| t.0 == tag)
+            .count()
+    }
+
+    pub fn count<T: legion::storage::Component>(&self) -> usize {
+        <&T>::query().iter(&self.ecs).count()
+    }
+
+    /// Whether an item is lying somewhere in a stockpile.
+    pub fn is_stockpiled(&self, item_id: usize) -> bool {
+        let stockpiled = stockpiled_tiles(<&Stockpile>::query().iter(&self.ecs));
+        <(&Position, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, idt)| idt.0 == item_id)
+            .map(|(pos, _)| match pos.loc {
+                Location::Tile { idx } => stockpiled.contains(&idx),
+                _ => false,
+            })
+            .unwrap_or(false)
+    }
+
+    /// Where an entity (usually a settler) is standing.
+    pub fn position_of(&self, id: usize) -> (usize, usize, usize) {
+        <(&Position, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, idt)| idt.0 == id)
+            .map(|(pos, _)| idxmap(pos.get_idx()))
+            .unwrap()
+    }
+
+    /// Where an item is: on the ground, or being carried.
+    pub fn location_of(&self, id: usize) -> Location {
+        <(&Position, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, idt)| idt.0 == id)
+            .map(|(pos, _)| pos.loc)
+            .unwrap()
+    }
+
+    /// What a settler is doing right now.
+    pub fn job_of(&self, id: usize) -> JobType {
+        <(&MyTurn, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, idt)| idt.0 == id)
+            .map(|(turn, _)| turn.job.clone())
+            .unwrap()
+    }
+
+    /// What a settler has been ordered to do, such as rally.
+    pub fn work_order_of(&self, id: usize) -> WorkOrder {
+        <(&MyTurn, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, idt)| idt.0 == id)
+            .map(|(turn, _)| turn.order)
+            .unwrap()
+    }
+
+    /// Who is on their way to haul an item, if anyone.
+    pub fn haul_in_progress(&self, item_id: usize) -> Option<usize> {
+        <(&RequestHaul, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, idt)| idt.0 == item_id)
+            .and_then(|(rh, _)| rh.in_progress)
+    }
+
+    /// The work orders queued at a workshop.
+    pub fn orders_at(&self, workshop_id: usize) -> Vec<ReactionOrder> {
+        <(&ReactionOrders, &IdentityTag)>::query()
+            .iter(&self.ecs)
+            .find(|(_, id)| id.0 == workshop_id)
+            .map(|(orders, _)| orders.0.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn planet() -> Planet {
+    let mut planet = Planet::new();
+    planet.rng_seed = 1;
+    planet
+}
+
+fn region_idx() -> usize {
+    REGION.read().world_idx
+}
+
+fn material_idx(name: &str) -> usize {
+    nox_raws::get_material_by_tag(name).unwrap_or_else(|| panic!("Unknown material: {}", name))
+}
@@ -0,0 +1,70 @@
+use super::*;
+
+const WALL_X: usize = 132;
+
+/// A miner's room with something to dig, and a corridor east to a room with
+/// the only pick in it.
+fn long_walk_for_a_pick(scenario: &mut Scenario) -> usize {
+    scenario.carve_room(120, 120, 124, 124, GROUND);
+    scenario.set_tile(125, 122, GROUND, TileType::Solid, "Granite");
+    scenario.designate_mining(125, 122, GROUND);
+    scenario.carve_room(125, 120, 139, 120, GROUND);
+    scenario.carve_room(140, 118, 142, 122, GROUND);
+    scenario.spawn_item("pickaxe", 141, 120, GROUND, "Plasteel");
+    let settler = scenario.spawn_settler(121, 121, GROUND);
+    scenario.make_miner(settler);
+    settler
+}
+
+/// Lets the miner set off down the corridor, walls it off ahead of them, and
+/// watches them for a while to make sure they don't walk through the wall.
+fn wall_off_corridor(scenario: &mut Scenario, settler: usize) {
+    let walking = scenario.run_until(500, |s| {
+        let travelling = match s.job_of(settler) {
+            JobType::CollectTool {
+                step: CollectToolSteps::TravelToTool { .. },
+                ..
+            } => true,
+            _ => false,
+        };
+        travelling && s.position_of(settler).0 >= 127
+    });
+    assert!(walking, "Miner never set off for the pick");
+    scenario.set_tile(WALL_X, 120, GROUND, TileType::Solid, "Granite");
+
+    scenario.run_until(300, |s| {
+        let (x, y, _) = s.position_of(settler);
+        assert!(x != WALL_X || y != 120, "Miner walked into the wall");
+        false
+    });
+}
+
+#[test]
+fn blocked_paths_are_replanned_around_the_obstacle() {
+    let mut scenario = Scenario::new();
+    let settler = long_walk_for_a_pick(&mut scenario);
+    // A longer way round, to the south
+    scenario.carve_room(122, 125, 122, 126, GROUND);
+    scenario.carve_room(122, 126, 141, 126, GROUND);
+    scenario.carve_room(141, 123, 141, 125, GROUND);
+
+    wall_off_corridor(&mut scenario, settler);
+    let done = scenario.run_until(3000, |s| s.count::<MiningMode>() == 0);
+    assert!(done, "Designation left after {} ticks", scenario.ticks);
+    assert_eq!(scenario.tile_type(125, 122, GROUND), TileType::Floor);
+}
+
+#[test]
+fn jobs_are_cancelled_when_there_is_no_way_round() {
+    let mut scenario = Scenario::new();
+    let settler = long_walk_for_a_pick(&mut scenario);
+
+    wall_off_corridor(&mut scenario, settler);
+    assert!(scenario.position_of(settler).0 < WALL_X);
+    let gave_up = scenario.run_until(100, |s| match s.job_of(settler) {
+        JobType::CollectTool { .. } => false,
+        _ => true,
+    });
+    assert!(gave_up, "Miner is still trying to reach the pick");
+    assert_eq!(scenario.count::<MiningMode>(), 1);
+}
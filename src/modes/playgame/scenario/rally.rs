@@ -0,0 +1,41 @@
+use super::*;
+
+#[test]
+fn settlers_gather_at_the_rally_point_and_stand_down() {
+    let mut scenario = Scenario::new();
+    // Two rooms, joined by a winding corridor
+    scenario.carve_room(120, 120, 126, 126, GROUND);
+    scenario.carve_room(127, 123, 134, 123, GROUND);
+    scenario.carve_room(134, 110, 134, 122, GROUND);
+    scenario.carve_room(135, 108, 140, 112, GROUND);
+    let settlers: Vec<usize> = [(120, 120), (126, 126), (123, 121), (121, 125)]
+        .iter()
+        .map(|(x, y)| scenario.spawn_settler(*x, *y, GROUND))
+        .collect();
+    scenario.tick();
+
+    scenario.set_rally_point(Some((139, 109, GROUND)));
+    let gathered = scenario.run_until(500, |s| {
+        settlers
+            .iter()
+            .all(|id| s.position_of(*id) == (139, 109, GROUND))
+    });
+    assert!(gathered, "Settlers didn't all reach the rally point");
+
+    // They wait there until they are stood down
+    scenario.run_until(50, |s| {
+        for id in settlers.iter() {
+            assert_eq!(s.position_of(*id), (139, 109, GROUND));
+            assert_eq!(s.work_order_of(*id), WorkOrder::Rally);
+        }
+        false
+    });
+
+    scenario.set_rally_point(None);
+    let stood_down = scenario.run_until(50, |s| {
+        settlers
+            .iter()
+            .all(|id| s.work_order_of(*id) != WorkOrder::Rally)
+    });
+    assert!(stood_down, "Settlers are still rallying");
+}
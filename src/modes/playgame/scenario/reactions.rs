@@ -0,0 +1,17 @@
+use super::*;
+
+#[test]
+fn smelter_produces_blocks_from_ore_and_charcoal() {
+    let mut scenario = Scenario::new();
+    scenario.carve_room(120, 120, 130, 130, GROUND);
+    scenario.spawn_settler(121, 121, GROUND);
+    scenario.spawn_building("smelter", 127, 127, GROUND);
+    // Both inputs have to be hauled to the smelter before it can run
+    scenario.spawn_item("ore", 121, 128, GROUND, "Bauxite");
+    scenario.spawn_item("charcoal", 128, 121, GROUND, "Wood");
+
+    let done = scenario.run_until(5000, |s| {
+        s.count_items("ore") == 0 && s.count_items("charcoal") == 0 && s.count_items("block") >= 2
+    });
+    assert!(done, "No blocks after {} ticks", scenario.ticks);
+}
@@ -0,0 +1,27 @@
+use super::*;
+use nox_raws::ItemDefType;
+
+fn components_only() -> StockpileFilter {
+    StockpileFilter {
+        item_types: vec![ItemDefType::Component],
+        ..StockpileFilter::default()
+    }
+}
+
+#[test]
+fn loose_items_are_put_away_if_the_stockpile_takes_them() {
+    let mut scenario = Scenario::new();
+    scenario.carve_room(120, 120, 132, 132, GROUND);
+    scenario.spawn_settler(121, 121, GROUND);
+    scenario.designate_stockpile(130, 130, 131, 131, GROUND, components_only());
+    let ore = scenario.spawn_item("ore", 122, 128, GROUND, "Bauxite");
+    let charcoal = scenario.spawn_item("charcoal", 126, 121, GROUND, "Wood");
+    let tea = scenario.spawn_item("tea_replicated", 124, 124, GROUND, "Wood");
+
+    let done = scenario.run_until(3000, |s| {
+        s.is_stockpiled(ore) && s.is_stockpiled(charcoal) && s.count::<RequestHaul>() == 0
+    });
+    assert!(done, "Nothing put away after {} ticks", scenario.ticks);
+    assert!(!scenario.is_stockpiled(tea), "Drinks aren't components");
+    assert_ne!(scenario.position_of(ore), scenario.position_of(charcoal));
+}
@@ -0,0 +1,26 @@
+use super::*;
+
+#[test]
+fn stock_orders_top_up_what_is_used() {
+    let mut scenario = Scenario::new();
+    scenario.carve_room(120, 120, 126, 126, GROUND);
+    scenario.spawn_settler(121, 121, GROUND);
+    let replicator = scenario.spawn_building("small_replicator", 124, 124, GROUND);
+    scenario.queue_order(replicator, "Replicate Sandwich", OrderGoal::KeepInStock(2));
+
+    let stocked = scenario.run_until(3000, |s| s.count_items("sandwich_replicated") == 2);
+    assert!(stocked, "No sandwiches after {} ticks", scenario.ticks);
+    scenario.run_until(500, |_| false);
+    assert_eq!(scenario.count_items("sandwich_replicated"), 2);
+
+    // Someone eats one
+    let sandwich = <(&IdentityTag, &Tag)>::query()
+        .iter(&scenario.ecs)
+        .find(|(_, tag)| tag.0 == "sandwich_replicated")
+        .map(|(id, _)| id.0)
+        .unwrap();
+    scenario.despawn(sandwich);
+    let restocked = scenario.run_until(3000, |s| s.count_items("sandwich_replicated") == 2);
+    assert!(restocked, "Not restocked after {} ticks", scenario.ticks);
+    assert_eq!(scenario.orders_at(replicator).len(), 1);
+}
@@ -0,0 +1,125 @@
+use super::{
+    messaging,
+    savestate::PlayState,
+    systems::{JobBoard, PathRequests, REGION},
+    GameStateResource,
+};
+use bengine::{random::RandomNumberGenerator, ColorFinder};
+use legion::*;
+use nox_planet::{pathfinding::PathCache, ConstructionMap, LumberMap, MiningMap, Planet, RallyMap};
+
+lazy_static! {
+    /// Headless runs (and the scenario tests) run systems one at a time, so
+    /// that they always roll dice, claim jobs and queue messages in the same
+    /// order. That keeps them repeatable: the same save always plays out the
+    /// same way. The game itself runs systems side by side, as legion likes.
+    static ref SIMULATION_POOL: rayon::ThreadPool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+}
+
+/// Loads the raw files for a run without a GPU palette.
+pub(super) fn load_raws_headless() {
+    nox_raws::load_raws();
+    // Item and terrain tints need the material map, but not a GPU palette
+    let colors = ColorFinder::load();
+    let mut rawlock = nox_raws::RAWS.write();
+    let mats = rawlock.materials.materials.clone();
+    rawlock.matmap.build(&mats, &colors);
+}
+
+/// Inserts the resources the simulation systems expect into a freshly loaded
+/// game, and builds its job maps.
+pub(super) fn start_simulation(
+    ecs: &mut World,
+    resources: &mut Resources,
+    planet: &Planet,
+    play_state: PlayState,
+) {
+    messaging::clear_queues();
+    let rng = play_state
+        .rng
+        .unwrap_or_else(|| RandomNumberGenerator::seeded(planet.rng_seed));
+    resources.insert(rng);
+    resources.insert(GameStateResource::new());
+    resources.insert(play_state.run_state);
+    resources.insert(play_state.autosave);
+    resources.insert(MiningMap::new());
+    resources.insert(LumberMap::new());
+    resources.insert(ConstructionMap::new());
+    resources.insert(RallyMap::new());
+    resources.insert(PathRequests::new());
+    resources.insert(JobBoard::new());
+    resources.insert(PathCache::default());
+
+    // Neither is the navigation graph
+    nox_planet::pathfinding::build_navigation(&mut REGION.write());
+
+    // The job maps are derived data, so rebuild them rather than storing them
+    run_schedule(&mut super::systems::map_scheduler(), ecs, resources);
+}
+
+/// Executes a simulation schedule deterministically, one system at a time.
+pub(super) fn run_schedule(schedule: &mut Schedule, ecs: &mut World, resources: &mut Resources) {
+    schedule.execute_in_thread_pool(ecs, resources, &SIMULATION_POOL);
+}
+
+/// Runs one tick of the simulation without rendering: the systems, their
+/// messages, and any terrain changes they made.
+pub(super) fn headless_tick(schedule: &mut Schedule, ecs: &mut World, resources: &mut Resources) {
+    run_schedule(schedule, ecs, resources);
+    messaging::process_queues(ecs, resources, None);
+
+    let dirty_tiles = {
+        let mut gs = resources.get_mut::<GameStateResource>();
+        std::mem::take(&mut gs.as_mut().unwrap().dirty_tiles)
+    };
+    if !dirty_tiles.is_empty() {
+        tiles_changed(resources, &dirty_tiles);
+    }
+}
+
+/// Recalculates the tile flags and navigation graph around tiles that have
+/// changed, and marks the job maps and board for rebuilding.
+pub(super) fn tiles_changed(resources: &Resources, dirty_tiles: &[usize]) {
+    {
+        let mut region = REGION.write();
+        nox_planet::update_flags(&mut region, dirty_tiles);
+        nox_planet::pathfinding::update_navigation(&mut region, dirty_tiles);
+    }
+
+    resources
+        .get_mut::<PathCache>()
+        .as_mut()
+        .unwrap()
+        .invalidate(dirty_tiles);
+
+    // The terrain has changed, so the maps have to be built from scratch
+    {
+        let mut map = resources.get_mut::<MiningMap>();
+        let map = map.as_mut().unwrap();
+        map.is_dirty = true;
+        map.dijkstra.invalidate();
+    }
+    {
+        let mut map = resources.get_mut::<LumberMap>();
+        let map = map.as_mut().unwrap();
+        map.is_dirty = true;
+        map.dijkstra.invalidate();
+    }
+    {
+        let mut map = resources.get_mut::<ConstructionMap>();
+        let map = map.as_mut().unwrap();
+        map.is_dirty = true;
+        map.dijkstra.invalidate();
+    }
+    {
+        let mut map = resources.get_mut::<RallyMap>();
+        let map = map.as_mut().unwrap();
+        map.is_dirty = true;
+        map.dijkstra.invalidate();
+    }
+    // Work that couldn't be reached before may be now
+    resources.get_mut::<JobBoard>().unwrap().is_dirty = true;
+}
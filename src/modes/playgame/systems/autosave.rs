@@ -0,0 +1,28 @@
+use super::super::{AutosaveSettings, GameStateResource};
+use legion::world::SubWorld;
+use legion::*;
+use nox_components::*;
+
+#[system]
+#[read_component(Calendar)]
+pub fn autosave(
+    ecs: &SubWorld,
+    #[resource] settings: &mut AutosaveSettings,
+    #[resource] state: &mut GameStateResource,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    if let Some(calendar) = <&Calendar>::query().iter(ecs).next() {
+        let now = calendar.total_minutes();
+        match settings.next_due {
+            None => settings.next_due = Some(now + settings.interval_minutes),
+            Some(due) if now >= due => {
+                state.autosave_requested = true;
+                settings.next_due = Some(now + settings.interval_minutes);
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,9 +1,8 @@
 use super::messaging;
-use super::REGION;
+use super::PathRequests;
 use legion::world::SubWorld;
 use legion::*;
 use nox_components::*;
-use nox_planet::pathfinding::a_star_search;
 
 #[system]
 #[read_component(MyTurn)]
@@ -11,7 +10,8 @@ use nox_planet::pathfinding::a_star_search;
 #[read_component(IdentityTag)]
 #[read_component(Settler)]
 #[read_component(RequestHaul)]
-pub fn hauling(ecs: &SubWorld) {
+#[read_component(Claimed)]
+pub fn hauling(ecs: &SubWorld, #[resource] paths: &mut PathRequests) {
     let mut lquery = <(&MyTurn, &IdentityTag, &Position)>::query();
     lquery.iter(ecs).for_each(|(turn, id, pos)| {
         if turn.active
@@ -24,25 +24,27 @@ pub fn hauling(ecs: &SubWorld) {
             if let JobType::Haul { item_id, step } = &turn.job {
                 match step {
                     HaulSteps::FindItem => {
-                        <(&RequestHaul, &Position, &IdentityTag)>::query()
+                        let item_pos = <(&RequestHaul, &Position, &IdentityTag)>::query()
                             .iter(ecs)
                             .filter(|(_, _, hid)| hid.0 == *item_id)
-                            .for_each(|(_rh, hpos, _hid)| {
-                                let destination = hpos.effective_location_sw(ecs);
-                                let path =
-                                    a_star_search(pos.get_idx(), destination, &REGION.read());
-                                if path.success {
-                                    messaging::job_changed(
-                                        id.0,
-                                        JobType::Haul {
-                                            item_id: *item_id,
-                                            step: HaulSteps::TravelToItem { path: path.steps },
-                                        },
-                                    );
-                                } else {
-                                    // Cancel job
-                                }
-                            });
+                            .map(|(_rh, hpos, _hid)| hpos.effective_location_sw(ecs))
+                            .nth(0);
+                        if let Some(item_pos) = item_pos {
+                            paths.request(
+                                id.0,
+                                pos.get_idx(),
+                                item_pos,
+                                &turn.job,
+                                JobType::Haul {
+                                    item_id: *item_id,
+                                    step: HaulSteps::TravelToItem { path: Vec::new() },
+                                },
+                                true,
+                            );
+                        } else {
+                            // It has gone, or no longer needs hauling
+                            messaging::cancel_job(id.0);
+                        }
                     }
                     HaulSteps::TravelToItem { path } => {
                         if path.len() > 1 {
@@ -58,28 +60,31 @@ pub fn hauling(ecs: &SubWorld) {
                         }
                     }
                     HaulSteps::CollectItem => {
-                        messaging::get_item(id.0, *item_id);
-                        <(&RequestHaul, &Position, &IdentityTag)>::query()
+                        if paths.is_pending(id.0) {
+                            return;
+                        }
+                        let destination = <(&RequestHaul, &IdentityTag)>::query()
                             .iter(ecs)
-                            .filter(|(_, _, hid)| hid.0 == *item_id)
-                            .for_each(|(rh, _hpos, _)| {
-                                let destination = rh.destination;
-                                let path =
-                                    a_star_search(pos.get_idx(), destination, &REGION.read());
-                                if path.success {
-                                    messaging::job_changed(
-                                        id.0,
-                                        JobType::Haul {
-                                            item_id: *item_id,
-                                            step: HaulSteps::TravelToDestination {
-                                                path: path.steps,
-                                            },
-                                        },
-                                    );
-                                } else {
-                                    // Cancel job
-                                }
-                            });
+                            .filter(|(_, hid)| hid.0 == *item_id)
+                            .map(|(rh, _)| rh.destination)
+                            .nth(0);
+                        if let Some(destination) = destination {
+                            messaging::get_item(id.0, *item_id);
+                            paths.request(
+                                id.0,
+                                pos.get_idx(),
+                                destination,
+                                &turn.job,
+                                JobType::Haul {
+                                    item_id: *item_id,
+                                    step: HaulSteps::TravelToDestination { path: Vec::new() },
+                                },
+                                true,
+                            );
+                        } else {
+                            // It has gone, or no longer needs hauling
+                            messaging::cancel_job(id.0);
+                        }
                     }
                     HaulSteps::TravelToDestination { path } => {
                         if path.len() > 1 {
@@ -95,15 +100,26 @@ pub fn hauling(ecs: &SubWorld) {
                         }
                     }
                     HaulSteps::DropItem => {
-                        let destination = <(&RequestHaul, &Position, &IdentityTag)>::query()
-                            .iter(ecs)
-                            .filter(|(_, _, hid)| hid.0 == *item_id)
-                            .map(|(_, hpos, _)| hpos.effective_location_sw(ecs))
-                            .nth(0)
-                            .unwrap();
-                        messaging::drop_item(*item_id, destination);
-                        messaging::update_blueprint(*item_id);
-                        messaging::conclude_job(id.0);
+                        // Components are put down next to what needs them;
+                        // stockpiled items go on their own tile
+                        let destination =
+                            <(&RequestHaul, &Position, &IdentityTag, Option<&Claimed>)>::query()
+                                .iter(ecs)
+                                .filter(|(_, _, hid, _)| hid.0 == *item_id)
+                                .map(|(rh, hpos, _, claim)| match claim {
+                                    Some(_) => hpos.effective_location_sw(ecs),
+                                    None => rh.destination,
+                                })
+                                .nth(0);
+                        if let Some(destination) = destination {
+                            messaging::drop_item(*item_id, destination);
+                            messaging::update_blueprint(*item_id);
+                            messaging::conclude_job(id.0);
+                        } else {
+                            // It was used up or called off on the way; cancelling
+                            // puts down whatever is still being carried
+                            messaging::cancel_job(id.0);
+                        }
                     }
                 }
             }
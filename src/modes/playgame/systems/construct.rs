@@ -1,9 +1,8 @@
 use super::messaging;
-use super::REGION;
+use super::PathRequests;
 use legion::world::SubWorld;
 use legion::*;
 use nox_components::*;
-use nox_planet::pathfinding::a_star_search;
 
 #[system()]
 #[read_component(MyTurn)]
@@ -11,7 +10,7 @@ use nox_planet::pathfinding::a_star_search;
 #[read_component(IdentityTag)]
 #[read_component(Construction)]
 #[read_component(Blueprint)]
-pub fn construction(ecs: &SubWorld) {
+pub fn construction(ecs: &SubWorld, #[resource] paths: &mut PathRequests) {
     let mut bquery = <(&MyTurn, &Position, &IdentityTag)>::query();
     bquery.iter(ecs).for_each(|(turn, pos, id)| {
         if turn.active
@@ -34,23 +33,19 @@ pub fn construction(ecs: &SubWorld) {
                             .nth(0)
                             .unwrap();
 
-                        let rlock = REGION.read();
                         let start = pos.get_idx();
                         println!("Pathing from {} to {}", start, bpos);
-                        let path = a_star_search(start, bpos, &rlock);
-                        std::mem::drop(rlock);
-                        if path.success {
-                            messaging::job_changed(
-                                id.0,
-                                JobType::Construct {
-                                    building_id: *building_id,
-                                    step: ConstructionSteps::TravelToBuilding { path: path.steps },
-                                },
-                            );
-                        } else {
-                            // Abandon all hope
-                            println!("Unable to find building");
-                        }
+                        paths.request(
+                            id.0,
+                            start,
+                            bpos,
+                            &turn.job,
+                            JobType::Construct {
+                                building_id: *building_id,
+                                step: ConstructionSteps::TravelToBuilding { path: Vec::new() },
+                            },
+                            true,
+                        );
                     }
                     ConstructionSteps::TravelToBuilding { path } => {
                         println!("Following path");
@@ -1,9 +1,8 @@
 use super::messaging;
-use super::REGION;
+use super::PathRequests;
 use legion::world::SubWorld;
 use legion::*;
 use nox_components::*;
-use nox_planet::pathfinding::a_star_search;
 
 #[system()]
 #[read_component(MyTurn)]
@@ -11,7 +10,7 @@ use nox_planet::pathfinding::a_star_search;
 #[read_component(IdentityTag)]
 #[read_component(Building)]
 #[read_component(Blueprint)]
-pub fn construction_building(ecs: &SubWorld) {
+pub fn construction_building(ecs: &SubWorld, #[resource] paths: &mut PathRequests) {
     let mut bquery = <(&MyTurn, &Position, &IdentityTag)>::query();
     bquery.iter(ecs).for_each(|(turn, pos, id)| {
         if turn.active
@@ -32,22 +31,17 @@ pub fn construction_building(ecs: &SubWorld) {
                             .nth(0)
                             .unwrap();
 
-                        let rlock = REGION.read();
-                        let start = pos.get_idx();
-                        let path = a_star_search(start, bpos, &rlock);
-                        std::mem::drop(rlock);
-                        if path.success {
-                            messaging::job_changed(
-                                id.0,
-                                JobType::ConstructBuilding {
-                                    building_id: *building_id,
-                                    step: BuildingSteps::TravelToBuilding { path: path.steps },
-                                },
-                            );
-                        } else {
-                            // Abandon all hope
-                            println!("Unable to find building");
-                        }
+                        paths.request(
+                            id.0,
+                            pos.get_idx(),
+                            bpos,
+                            &turn.job,
+                            JobType::ConstructBuilding {
+                                building_id: *building_id,
+                                step: BuildingSteps::TravelToBuilding { path: Vec::new() },
+                            },
+                            true,
+                        );
                     }
                     BuildingSteps::TravelToBuilding { path } => {
                         if path.len() > 1 {
@@ -1,3 +1,4 @@
+use super::JobBoard;
 use legion::*;
 use legion::{systems::CommandBuffer, world::SubWorld};
 use nox_components::*;
@@ -12,10 +13,13 @@ use std::collections::HashSet;
 #[read_component(Blueprint)]
 #[read_component(Tag)]
 #[read_component(IdentityTag)]
+#[read_component(RequestHaul)]
+#[read_component(Stockpile)]
 pub fn construction_designator(
     ecs: &SubWorld,
     commands: &mut CommandBuffer,
     #[resource] cmap: &mut ConstructionMap,
+    #[resource] board: &mut JobBoard,
 ) {
     let mut available_blocks = <(&Item, &Tag)>::query()
         .filter(!component::<Claimed>())
@@ -28,6 +32,7 @@ pub fn construction_designator(
     }
 
     let mut used_blocks = HashSet::new();
+    let stockpiled = stockpiled_tiles(<&Stockpile>::query().iter(ecs));
 
     <(&Construction, &Position, &IdentityTag, Entity)>::query()
         .filter(!component::<Blueprint>())
@@ -36,17 +41,23 @@ pub fn construction_designator(
             println!("Found a matching construction job");
             if available_blocks > 0 {
                 let idx = pos.get_idx();
-                if cmap.dijkstra[idx] < f32::MAX {
+                if cmap.dijkstra.distance(idx) < f32::MAX {
                     // The building site is accessible
-                    // Select components
-                    let mut blocks: Vec<(usize, f32, Entity)> =
-                        <(&Tag, &IdentityTag, &Position, Entity)>::query()
+                    // Select components, preferring ones from a stockpile
+                    let mut blocks: Vec<(usize, (bool, f32), Entity)> =
+                        <(&Tag, &IdentityTag, &Position, Option<&RequestHaul>, Entity)>::query()
                             .filter(!component::<Claimed>())
                             .iter(ecs)
-                            .filter(|(t, block_id, _, _)| {
-                                t.0 == "block" && !used_blocks.contains(&block_id.0)
+                            .filter(|(t, block_id, _, haul, _)| {
+                                t.0 == "block"
+                                    && !used_blocks.contains(&block_id.0)
+                                    && !is_being_hauled(*haul)
+                            })
+                            .map(|(_, bid, bpos, _, be)| {
+                                let idx = bpos.get_idx();
+                                let distance = cmap.dijkstra.distance(idx);
+                                (bid.0, (!stockpiled.contains(&idx), distance), *be)
                             })
-                            .map(|(_, bid, bpos, be)| (bid.0, cmap.dijkstra[bpos.get_idx()], *be))
                             .collect();
 
                     println!("Found {} blocks", blocks.len());
@@ -73,6 +84,7 @@ pub fn construction_designator(
                         );
 
                         cmap.is_dirty = true;
+                        board.is_dirty = true;
                         available_blocks -= 1;
                         used_blocks.insert(blocks[0].0);
                     }
@@ -4,7 +4,6 @@ use legion::*;
 use nox_components::*;
 use nox_planet::{ConstructionMap, Region};
 use nox_spatial::*;
-use std::collections::VecDeque;
 
 #[system]
 #[read_component(Construction)]
@@ -15,8 +14,6 @@ pub fn construction_map(ecs: &SubWorld, #[resource] map: &mut ConstructionMap) {
         return;
     }
 
-    map.dijkstra.iter_mut().for_each(|n| *n = f32::MAX);
-
     // Build the tree starting points
     let rlock = REGION.read();
     let mut starts = Vec::new();
@@ -55,30 +52,8 @@ pub fn construction_map(ecs: &SubWorld, #[resource] map: &mut ConstructionMap) {
             }
         });
 
-    // Build the Dijkstra Map
-    let mut open_list: VecDeque<(usize, f32)> = VecDeque::with_capacity(REGION_TILES_COUNT);
-
-    for start in &starts {
-        open_list.push_back((*start, 0.0));
-        map.dijkstra[*start] = 0.0;
-    }
-
-    const MAX_DEPTH: f32 = 2048.0;
-    while let Some((tile_idx, depth)) = open_list.pop_front() {
-        let exits = rlock.get_available_exits(tile_idx);
-        for (new_idx, add_depth) in exits {
-            let new_depth = depth + add_depth;
-            let prev_depth = map.dijkstra[new_idx];
-            if new_depth >= prev_depth {
-                continue;
-            }
-            if new_depth >= MAX_DEPTH {
-                continue;
-            }
-            map.dijkstra[new_idx] = new_depth;
-            open_list.push_back((new_idx, new_depth));
-        }
-    }
+    // Update the Dijkstra Map
+    map.dijkstra.set_goals(&rlock, &starts);
 
     println!("Construction Dijkstra Created");
 
@@ -1,14 +1,19 @@
-use super::RNG;
+use bengine::random::RandomNumberGenerator;
 use legion::*;
 use nox_components::*;
 use nox_utils::attribute_modifier;
 
 #[system(for_each)]
-pub fn initiative(i: &mut Initiative, t: &mut MyTurn, attrib: &Attributes) {
+pub fn initiative(
+    i: &mut Initiative,
+    t: &mut MyTurn,
+    attrib: &Attributes,
+    #[resource] rng: &mut RandomNumberGenerator,
+) {
     i.initiative -= 1;
     if i.initiative + i.modifier < 1 {
         // Re-roll initiative
-        i.initiative = RNG.lock().roll_dice(2, 6) - attribute_modifier(attrib.dex);
+        i.initiative = rng.roll_dice(2, 6) - attribute_modifier(attrib.dex);
         // TODO: Add modifiers from equipment etc.
 
         // Reset modifiers
@@ -0,0 +1,340 @@
+use legion::world::SubWorld;
+use legion::*;
+use nox_components::*;
+use nox_planet::{LumberMap, MiningMap};
+use nox_raws::RAWS;
+use std::collections::{BTreeMap, HashMap};
+
+/// What a job on the board is for. Mining and lumber work come from the job
+/// maps, so there is one standing job for each, which any number of settlers
+/// may take; everything else is a single piece of work for a single settler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum JobSource {
+    Mining,
+    Lumber,
+    /// An item with a `RequestHaul`, by id.
+    Haul(usize),
+    /// A building whose components have all arrived.
+    Building(usize),
+    /// A `ReactionJob` whose components have all arrived.
+    Reaction(usize),
+    /// A construction whose blocks have all arrived.
+    Construction(usize),
+}
+
+impl JobSource {
+    /// The job a settler is working on, if it came from the board.
+    pub fn of(job: &JobType) -> Option<Self> {
+        match job {
+            JobType::Mining { .. } => Some(JobSource::Mining),
+            JobType::FellTree { .. } => Some(JobSource::Lumber),
+            JobType::Haul { item_id, .. } => Some(JobSource::Haul(*item_id)),
+            JobType::ConstructBuilding { building_id, .. } => {
+                Some(JobSource::Building(*building_id))
+            }
+            JobType::Reaction { reaction_id, .. } => Some(JobSource::Reaction(*reaction_id)),
+            JobType::Construct { building_id, .. } => Some(JobSource::Construction(*building_id)),
+            JobType::None | JobType::CollectTool { .. } => None,
+        }
+    }
+
+    fn is_shared(&self) -> bool {
+        match self {
+            JobSource::Mining | JobSource::Lumber => true,
+            _ => false,
+        }
+    }
+
+    /// Work whose materials are already in place is finished before new work
+    /// is started.
+    fn default_priority(&self) -> u8 {
+        match self {
+            JobSource::Building(_) | JobSource::Reaction(_) | JobSource::Construction(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Job {
+    /// What a settler taking the job starts doing.
+    pub job: JobType,
+    /// Where the work is; standing jobs use their job map instead.
+    pub position: Option<usize>,
+    /// Where hauled items are going.
+    pub destination: Option<usize>,
+    /// Higher priorities are taken first, however far away they are.
+    pub priority: u8,
+    /// The settler who has taken the job.
+    pub reserved_by: Option<usize>,
+    /// The kind of work it is, so that only settlers set to do it take it.
+    pub labor: Labor,
+}
+
+/// All of the work waiting to be done, so that idle settlers can pick from a
+/// list rather than searching the world for something to do. Whatever changes
+/// what work there is marks the board dirty, and the `job_board` system then
+/// brings it up to date. It isn't saved; it is worked out again from the
+/// designations and whoever is working on them.
+pub struct JobBoard {
+    pub is_dirty: bool,
+    /// Whether the board was brought up to date this tick, so that systems
+    /// running after `job_board` can tell that something has changed.
+    pub refreshed: bool,
+    jobs: BTreeMap<JobSource, Job>,
+}
+
+impl JobBoard {
+    pub fn new() -> Self {
+        Self {
+            is_dirty: true,
+            refreshed: false,
+            jobs: BTreeMap::new(),
+        }
+    }
+
+    /// Jobs nobody has taken yet.
+    pub fn available(&self) -> impl Iterator<Item = (&JobSource, &Job)> {
+        self.jobs
+            .iter()
+            .filter(|(_, job)| job.reserved_by.is_none())
+    }
+
+    /// Hands a job to a settler. Standing jobs are never reserved.
+    pub fn reserve(&mut self, source: JobSource, settler_id: usize) {
+        if source.is_shared() {
+            return;
+        }
+        if let Some(job) = self.jobs.get_mut(&source) {
+            job.reserved_by = Some(settler_id);
+        }
+    }
+
+    fn post(
+        &mut self,
+        source: JobSource,
+        job: JobType,
+        labor: Labor,
+        position: usize,
+        destination: Option<usize>,
+    ) {
+        self.jobs.insert(
+            source,
+            Job {
+                job,
+                position: Some(position),
+                destination,
+                priority: source.default_priority(),
+                reserved_by: None,
+                labor,
+            },
+        );
+    }
+
+    fn post_standing(&mut self, source: JobSource, job: JobType, labor: Labor, available: bool) {
+        if !available {
+            self.jobs.remove(&source);
+        } else if !self.jobs.contains_key(&source) {
+            self.jobs.insert(
+                source,
+                Job {
+                    job,
+                    position: None,
+                    destination: None,
+                    priority: source.default_priority(),
+                    reserved_by: None,
+                    labor,
+                },
+            );
+        }
+    }
+}
+
+#[system]
+#[read_component(IdentityTag)]
+#[read_component(MyTurn)]
+#[read_component(Position)]
+#[read_component(Building)]
+#[read_component(Blueprint)]
+#[read_component(Claimed)]
+#[write_component(RequestHaul)]
+#[write_component(ReactionJob)]
+#[write_component(Construction)]
+pub fn job_board(
+    ecs: &mut SubWorld,
+    #[resource] board: &mut JobBoard,
+    #[resource] mining: &MiningMap,
+    #[resource] lumber: &LumberMap,
+) {
+    let workers: HashMap<usize, Option<JobSource>> = <(&IdentityTag, &MyTurn)>::query()
+        .iter(ecs)
+        .map(|(id, turn)| (id.0, JobSource::of(&turn.job)))
+        .collect();
+
+    board.refreshed = board.is_dirty;
+    if board.is_dirty {
+        post_jobs(ecs, board);
+        // Pick up whoever is already working, e.g. in a game that was just loaded
+        for (worker, source) in workers.iter() {
+            if let Some(source) = source {
+                board.reserve(*source, *worker);
+            }
+        }
+        board.is_dirty = false;
+    }
+
+    board.post_standing(
+        JobSource::Mining,
+        JobType::Mining {
+            step: MiningSteps::FindPick,
+            tool_id: None,
+        },
+        Labor::Mining,
+        !mining.dijkstra.goals().is_empty(),
+    );
+    board.post_standing(
+        JobSource::Lumber,
+        JobType::FellTree {
+            step: LumberjackSteps::FindAxe,
+            tool_id: None,
+        },
+        Labor::Lumber,
+        !lumber.dijkstra.goals().is_empty(),
+    );
+
+    // Settlers who have given up on their job (or are gone) let it go
+    let mut released = Vec::new();
+    for (source, job) in board.jobs.iter_mut() {
+        if let Some(worker) = job.reserved_by {
+            if workers.get(&worker) != Some(&Some(*source)) {
+                job.reserved_by = None;
+                released.push(*source);
+            }
+        }
+    }
+    for source in released {
+        clear_in_progress(ecs, source);
+    }
+}
+
+/// Brings the board's discrete jobs into line with the world.
+fn post_jobs(ecs: &SubWorld, board: &mut JobBoard) {
+    let mut posted = JobBoard::new();
+
+    <(&RequestHaul, &Position, &IdentityTag)>::query()
+        .iter(ecs)
+        .for_each(|(rh, pos, id)| {
+            posted.post(
+                JobSource::Haul(id.0),
+                JobType::Haul {
+                    item_id: id.0,
+                    step: HaulSteps::FindItem,
+                },
+                Labor::Hauling,
+                pos.get_idx(),
+                Some(rh.destination),
+            );
+        });
+
+    <(&Building, &Blueprint, &Position, &IdentityTag)>::query()
+        .iter(ecs)
+        .filter(|(building, blueprint, _, _)| !building.complete && blueprint.ready_to_build)
+        .for_each(|(_, _, pos, id)| {
+            posted.post(
+                JobSource::Building(id.0),
+                JobType::ConstructBuilding {
+                    building_id: id.0,
+                    step: BuildingSteps::FindBuilding,
+                },
+                Labor::Construction,
+                pos.get_idx(),
+                None,
+            );
+        });
+
+    <(&ReactionJob, &IdentityTag, &Blueprint, &Position)>::query()
+        .filter(!component::<Claimed>())
+        .iter(ecs)
+        .filter(|(_, _, bp, _)| bp.ready_to_build)
+        .for_each(|(rj, id, _, pos)| {
+            let location = pos.effective_location_sw(ecs);
+            posted.post(
+                JobSource::Reaction(id.0),
+                JobType::Reaction {
+                    reaction_id: id.0,
+                    reaction_location: location,
+                    step: ReactionSteps::FindReaction,
+                },
+                reaction_labor(&rj.reaction_tag),
+                location,
+                None,
+            );
+        });
+
+    <(&Construction, &Blueprint, &Position, &IdentityTag)>::query()
+        .iter(ecs)
+        .filter(|(_, bp, _, _)| bp.ready_to_build)
+        .for_each(|(_, _, pos, id)| {
+            posted.post(
+                JobSource::Construction(id.0),
+                JobType::Construct {
+                    building_id: id.0,
+                    step: ConstructionSteps::FindBuilding,
+                },
+                Labor::Construction,
+                pos.get_idx(),
+                None,
+            );
+        });
+
+    // Keep the priorities and reservations of jobs that are still there
+    for (source, job) in posted.jobs.iter_mut() {
+        if let Some(previous) = board.jobs.get(source) {
+            job.priority = previous.priority;
+            job.reserved_by = previous.reserved_by;
+        }
+    }
+    // Standing jobs are looked after separately
+    for (source, job) in std::mem::take(&mut board.jobs) {
+        if source.is_shared() {
+            posted.jobs.insert(source, job);
+        }
+    }
+    board.jobs = posted.jobs;
+}
+
+/// Reactions are done by settlers with the workshop skill they call for.
+fn reaction_labor(reaction_tag: &str) -> Labor {
+    RAWS.read()
+        .reactions
+        .reactions
+        .iter()
+        .find(|r| r.name == reaction_tag)
+        .map(|r| Labor::of_skill(&r.skill))
+        .unwrap_or_else(|| Labor::Workshop(reaction_tag.to_string()))
+}
+
+/// Clears the marker that says someone is working on a job.
+fn clear_in_progress(ecs: &mut SubWorld, source: JobSource) {
+    match source {
+        JobSource::Haul(item_id) => {
+            <(&IdentityTag, &mut RequestHaul)>::query()
+                .iter_mut(ecs)
+                .filter(|(id, _)| id.0 == item_id)
+                .for_each(|(_, rh)| rh.in_progress = None);
+        }
+        JobSource::Reaction(reaction_id) => {
+            <(&IdentityTag, &mut ReactionJob)>::query()
+                .iter_mut(ecs)
+                .filter(|(id, _)| id.0 == reaction_id)
+                .for_each(|(_, rj)| rj.in_progress = None);
+        }
+        JobSource::Construction(building_id) => {
+            <(&IdentityTag, &mut Construction)>::query()
+                .iter_mut(ecs)
+                .filter(|(id, _)| id.0 == building_id)
+                .for_each(|(_, c)| c.in_progress = None);
+        }
+        JobSource::Mining | JobSource::Lumber | JobSource::Building(_) => {}
+    }
+}
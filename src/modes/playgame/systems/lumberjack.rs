@@ -1,11 +1,10 @@
 use super::super::messaging;
-use super::utils::{am_i_carrying_tool, ToolCarrying};
-use crate::modes::playgame::systems::REGION;
+use super::utils::{am_i_carrying_tool, is_tool_free, ToolCarrying};
+use crate::modes::playgame::systems::{PathRequests, REGION};
 use bengine::geometry::Point3;
 use legion::world::SubWorld;
 use legion::*;
 use nox_components::*;
-use nox_planet::pathfinding::a_star_search;
 use nox_planet::LumberMap;
 use nox_spatial::idxmap;
 
@@ -16,7 +15,11 @@ use nox_spatial::idxmap;
 #[read_component(Claimed)]
 #[read_component(Tool)]
 #[read_component(Position)]
-pub fn lumberjack(ecs: &SubWorld, #[resource] lumber: &LumberMap) {
+pub fn lumberjack(
+    ecs: &SubWorld,
+    #[resource] lumber: &LumberMap,
+    #[resource] paths: &mut PathRequests,
+) {
     let mut lquery = <(&MyTurn, &Position, &IdentityTag, &Settler)>::query();
     lquery.iter(ecs).for_each(|(turn, pos, id, settler)| {
         if turn.active
@@ -30,7 +33,7 @@ pub fn lumberjack(ecs: &SubWorld, #[resource] lumber: &LumberMap) {
                 println!("Loc at step: {:?}", pos);
                 match step {
                     LumberjackSteps::FindAxe => {
-                        find_axe(ecs, id.0, pos.get_idx());
+                        find_axe(ecs, paths, id.0, pos.get_idx(), &turn.job);
                     }
                     LumberjackSteps::FindTree {} => {
                         println!("Step: FindTree");
@@ -40,7 +43,7 @@ pub fn lumberjack(ecs: &SubWorld, #[resource] lumber: &LumberMap) {
                         println!("Step: ChopTree");
                         messaging::chop_tree(id.0, pos.get_idx());
                         messaging::conclude_job(id.0);
-                        if !settler.lumberjack {
+                        if !settler.labors.needs_tool(ToolType::Chopping) {
                             messaging::drop_item(tool_id.unwrap(), pos.get_idx());
                             messaging::relinquish_claim(tool_id.unwrap(), pos.get_idx());
                         }
@@ -53,28 +56,38 @@ pub fn lumberjack(ecs: &SubWorld, #[resource] lumber: &LumberMap) {
     });
 }
 
-fn find_axe(ecs: &SubWorld, settler_id: usize, settler_pos: usize) {
+fn find_axe(
+    ecs: &SubWorld,
+    paths: &mut PathRequests,
+    settler_id: usize,
+    settler_pos: usize,
+    job: &JobType,
+) {
     // Do I have an axe?
     let axe_status = am_i_carrying_tool(ecs, settler_id, ToolType::Chopping);
     match axe_status {
-        ToolCarrying::NoTool => messaging::cancel_job(settler_id),
-        ToolCarrying::AtLocation { idx, tool_id } => {
-            println!("Tool located - travel mode");
-            let rlock = REGION.read();
-            let path = a_star_search(settler_pos, idx, &rlock);
-            if path.success {
-                messaging::job_changed(
-                    settler_id,
-                    JobType::CollectTool {
-                        step: CollectToolSteps::TravelToTool { path: path.steps },
-                        tool_id,
-                    },
-                );
+        ToolCarrying::NoTool => {
+            if is_tool_free(ecs, ToolType::Chopping) {
+                // The claim made when they took the job is still on its way
+                messaging::claim_tool(settler_id, ToolType::Chopping);
             } else {
-                println!("I can't get to the axe");
                 messaging::cancel_job(settler_id);
             }
         }
+        ToolCarrying::AtLocation { idx, tool_id } => {
+            println!("Tool located - travel mode");
+            paths.request(
+                settler_id,
+                settler_pos,
+                idx,
+                job,
+                JobType::CollectTool {
+                    step: CollectToolSteps::TravelToTool { path: Vec::new() },
+                    tool_id,
+                },
+                true,
+            );
+        }
         ToolCarrying::Carried { tool_id } => {
             println!("I have an axe!");
             messaging::job_changed(
@@ -91,7 +104,7 @@ fn find_axe(ecs: &SubWorld, settler_id: usize, settler_pos: usize) {
 fn travel_to_tree(settler_id: usize, pos: &Position, lumber: &LumberMap, tool_id: usize) {
     println!("Step: Travel to tree");
     let idx = pos.get_idx();
-    let distance = lumber.dijkstra[idx];
+    let distance = lumber.dijkstra.distance(idx);
     if distance < f32::MAX {
         println!("Distance to tree target: {}", distance);
         if distance < 1.0 {
@@ -105,7 +118,7 @@ fn travel_to_tree(settler_id: usize, pos: &Position, lumber: &LumberMap, tool_id
         } else {
             // Walk the Dijkstra
             let rlock = REGION.read();
-            if let Some(exit) = lumber.find_lowest_exit(idx, &rlock) {
+            if let Some(exit) = lumber.dijkstra.find_lowest_exit(idx, &rlock) {
                 let (nx, ny, nz) = idxmap(exit);
                 messaging::entity_moved(settler_id, &Point3::new(nx, ny, nz));
             } else {
@@ -1,13 +1,12 @@
 use super::{
     super::messaging,
-    utils::{am_i_carrying_tool, ToolCarrying},
+    utils::{am_i_carrying_tool, is_tool_free, ToolCarrying},
 };
-use crate::modes::playgame::systems::REGION;
+use crate::modes::playgame::systems::{PathRequests, REGION};
 use bengine::geometry::Point3;
 use legion::world::SubWorld;
 use legion::*;
 use nox_components::*;
-use nox_planet::pathfinding::a_star_search;
 use nox_planet::MiningMap;
 use nox_spatial::idxmap;
 
@@ -18,7 +17,11 @@ use nox_spatial::idxmap;
 #[read_component(Settler)]
 #[read_component(Claimed)]
 #[read_component(Tool)]
-pub fn mining(ecs: &SubWorld, #[resource] mining: &MiningMap) {
+pub fn mining(
+    ecs: &SubWorld,
+    #[resource] mining: &MiningMap,
+    #[resource] paths: &mut PathRequests,
+) {
     let mut mquery = <(&MyTurn, &Position, &IdentityTag, &Settler)>::query();
     mquery.iter(ecs).for_each(|(turn, pos, id, settler)| {
         if turn.active
@@ -32,12 +35,12 @@ pub fn mining(ecs: &SubWorld, #[resource] mining: &MiningMap) {
                 println!("Loc at step: {:?}", pos);
                 match step {
                     MiningSteps::FindPick => {
-                        find_pick(ecs, id.0, pos.get_idx());
+                        find_pick(ecs, paths, id.0, pos.get_idx(), &turn.job);
                     }
                     MiningSteps::TravelToMine => {
                         println!("Step: Travel to mine");
                         let idx = pos.get_idx();
-                        let distance = mining.dijkstra[idx];
+                        let distance = mining.dijkstra.distance(idx);
                         if distance < f32::MAX {
                             println!("Distance to mine target: {}", distance);
                             if distance < 1.0 {
@@ -51,7 +54,7 @@ pub fn mining(ecs: &SubWorld, #[resource] mining: &MiningMap) {
                             } else {
                                 // Walk the Dijkstra
                                 let rlock = REGION.read();
-                                if let Some(exit) = mining.find_lowest_exit(idx, &rlock) {
+                                if let Some(exit) = mining.dijkstra.find_lowest_exit(idx, &rlock) {
                                     let (nx, ny, nz) = idxmap(exit);
                                     messaging::entity_moved(id.0, &Point3::new(nx, ny, nz));
                                 } else {
@@ -69,7 +72,7 @@ pub fn mining(ecs: &SubWorld, #[resource] mining: &MiningMap) {
                         println!("Step: Dig");
                         messaging::dig_at(id.0, pos.get_idx());
                         messaging::conclude_job(id.0);
-                        if !settler.miner {
+                        if !settler.labors.needs_tool(ToolType::Digging) {
                             messaging::drop_item(tool_id.unwrap(), pos.get_idx());
                             messaging::relinquish_claim(tool_id.unwrap(), pos.get_idx());
                         }
@@ -80,28 +83,38 @@ pub fn mining(ecs: &SubWorld, #[resource] mining: &MiningMap) {
     });
 }
 
-fn find_pick(ecs: &SubWorld, settler_id: usize, settler_pos: usize) {
+fn find_pick(
+    ecs: &SubWorld,
+    paths: &mut PathRequests,
+    settler_id: usize,
+    settler_pos: usize,
+    job: &JobType,
+) {
     // Do I have an axe?
     let axe_status = am_i_carrying_tool(ecs, settler_id, ToolType::Digging);
     match axe_status {
-        ToolCarrying::NoTool => messaging::cancel_job(settler_id),
-        ToolCarrying::AtLocation { idx, tool_id } => {
-            println!("Tool located - travel mode");
-            let rlock = REGION.read();
-            let path = a_star_search(settler_pos, idx, &rlock);
-            if path.success {
-                messaging::job_changed(
-                    settler_id,
-                    JobType::CollectTool {
-                        step: CollectToolSteps::TravelToTool { path: path.steps },
-                        tool_id,
-                    },
-                );
+        ToolCarrying::NoTool => {
+            if is_tool_free(ecs, ToolType::Digging) {
+                // The claim made when they took the job is still on its way
+                messaging::claim_tool(settler_id, ToolType::Digging);
             } else {
-                println!("I can't get to the pick");
                 messaging::cancel_job(settler_id);
             }
         }
+        ToolCarrying::AtLocation { idx, tool_id } => {
+            println!("Tool located - travel mode");
+            paths.request(
+                settler_id,
+                settler_pos,
+                idx,
+                job,
+                JobType::CollectTool {
+                    step: CollectToolSteps::TravelToTool { path: Vec::new() },
+                    tool_id,
+                },
+                true,
+            );
+        }
         ToolCarrying::Carried { tool_id } => {
             println!("I have a pick!");
             messaging::job_changed(
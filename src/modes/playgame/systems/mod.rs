@@ -1,8 +1,7 @@
-use bengine::random::RandomNumberGenerator;
 use legion::*;
 use nox_planet::Region;
-use parking_lot::{Mutex, RwLock};
-mod automatic_reactions;
+use parking_lot::RwLock;
+mod autosave;
 mod calendar;
 mod camera_control;
 mod component_hauling;
@@ -12,19 +11,27 @@ mod construction_designator;
 mod construction_map;
 mod end_turn;
 mod initiative;
+mod job_board;
+pub use job_board::JobBoard;
 mod leisure_shift;
 mod lumber_map;
 mod lumberjack;
 mod mining;
 mod mining_map;
 mod move_randomly;
+mod path_solver;
+pub use path_solver::PathRequests;
 mod pause_control;
+mod rally;
+mod rally_map;
 mod reactions;
 mod settler_scheduler;
 mod sleep_shift;
+mod stockpiles;
 mod tool_collection;
 mod utils;
 mod viewshed;
+mod work_orders;
 mod work_shift;
 
 use super::messaging;
@@ -33,17 +40,17 @@ lazy_static! {
     pub static ref REGION: RwLock<Region> = RwLock::new(Region::initial());
 }
 
-lazy_static! {
-    pub static ref RNG: Mutex<RandomNumberGenerator> = Mutex::new(RandomNumberGenerator::new());
-}
-
 pub fn build_scheduler() -> Schedule {
     Schedule::builder()
         .add_system(mining_map::mining_map_system())
         .add_system(lumber_map::lumber_map_system())
         .add_system(construction_map::construction_map_system())
-        .add_system(automatic_reactions::automatic_reactions_system())
+        .add_system(rally_map::rally_map_system())
+        .add_system(job_board::job_board_system())
+        .add_system(work_orders::work_orders_system())
+        .add_system(stockpiles::stockpiles_system())
         .add_system(calendar::calendar_system())
+        .add_system(autosave::autosave_system())
         .add_system(viewshed::viewshed_system())
         .add_system(camera_control::camera_control_system())
         .add_system(pause_control::pause_control_system())
@@ -54,6 +61,7 @@ pub fn build_scheduler() -> Schedule {
         .flush()
         .add_system(leisure_shift::leisure_shift_system())
         .add_system(sleep_shift::sleep_shift_system())
+        .add_system(rally::rally_system())
         .add_system(work_shift::work_shift_system())
         .flush()
         .add_system(tool_collection::tool_collection_system())
@@ -65,10 +73,22 @@ pub fn build_scheduler() -> Schedule {
         .add_system(reactions::reactions_system())
         .add_system(move_randomly::move_randomly_system())
         .flush()
+        .add_system(path_solver::path_solver_system())
         .add_system(end_turn::end_turn_system())
         .build()
 }
 
+/// Rebuilds the cached job maps, so a freshly loaded game starts with them ready.
+pub fn map_scheduler() -> Schedule {
+    Schedule::builder()
+        .add_system(mining_map::mining_map_system())
+        .add_system(lumber_map::lumber_map_system())
+        .add_system(construction_map::construction_map_system())
+        .add_system(rally_map::rally_map_system())
+        .add_system(job_board::job_board_system())
+        .build()
+}
+
 pub fn paused_scheduler() -> Schedule {
     Schedule::builder()
         .add_system(viewshed::viewshed_system())
@@ -1,13 +1,19 @@
-use super::{REGION, RNG};
+use super::REGION;
+use bengine::random::RandomNumberGenerator;
 use legion::*;
 use nox_components::*;
 use nox_planet::Region;
 
 #[system(for_each)]
-pub fn move_randomly(pos: &mut Position, turn: &MyTurn, id: &IdentityTag) {
+pub fn move_randomly(
+    pos: &mut Position,
+    turn: &MyTurn,
+    id: &IdentityTag,
+    #[resource] rng: &mut RandomNumberGenerator,
+) {
     if turn.active && turn.order == WorkOrder::MoveRandomly {
         let idx = pos.get_idx();
-        let delta = random_move(idx);
+        let delta = random_move(idx, rng);
         let mut destination = pos.as_point3();
         destination.x += delta.0;
         destination.y += delta.1;
@@ -16,8 +22,8 @@ pub fn move_randomly(pos: &mut Position, turn: &MyTurn, id: &IdentityTag) {
     }
 }
 
-fn random_move(idx: usize) -> (i32, i32, i32) {
-    let roll = RNG.lock().range(1, 7);
+fn random_move(idx: usize, rng: &mut RandomNumberGenerator) -> (i32, i32, i32) {
+    let roll = rng.range(1, 7);
     match roll {
         1 => {
             if REGION.read().flag(idx, Region::CAN_GO_NORTH) {
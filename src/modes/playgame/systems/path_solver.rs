@@ -0,0 +1,169 @@
+use super::messaging;
+use super::REGION;
+use legion::world::SubWorld;
+use legion::*;
+use nox_components::*;
+use nox_planet::pathfinding::{a_star_search, PathCache};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// At most this many paths are worked out per tick; the rest wait their turn.
+const MAX_PATHS_PER_TICK: usize = 256;
+
+/// Every path thread keeps A* buffers covering the whole region (about 200MB
+/// once a search has been everywhere), so only use a few cores.
+const MAX_PATH_THREADS: usize = 4;
+
+lazy_static! {
+    /// Paths are independent of one another, so they are solved side by side.
+    static ref PATH_POOL: rayon::ThreadPool = rayon::ThreadPoolBuilder::new()
+        .num_threads(
+            std::thread::available_parallelism()
+                .map_or(1, |cores| usize::min(cores.get(), MAX_PATH_THREADS)),
+        )
+        .build()
+        .unwrap();
+}
+
+struct PathRequest {
+    requester: usize,
+    start: usize,
+    goal: usize,
+    /// The job the requester was doing when they asked. If it has changed by
+    /// the time the path is ready, the path is thrown away.
+    waiting_job: JobType,
+    /// The job to switch to once the path is found. Its travel step's path is
+    /// filled in with the result, from the first step to take.
+    next_job: JobType,
+    cancel_on_failure: bool,
+}
+
+/// Path searches that job systems have asked for. Rather than searching while
+/// they work through the settlers, systems queue up what they need; the
+/// `path_solver` system then solves the lot in parallel, and hands out the
+/// results as job changes in time for the next tick. Trips that have been made
+/// before come straight out of the `PathCache`.
+#[derive(Default)]
+pub struct PathRequests {
+    queue: Vec<PathRequest>,
+    pending: HashSet<usize>,
+}
+
+impl PathRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks for a path from `start` to `goal` for a settler doing `waiting_job`.
+    /// When it is found, they switch to `next_job` (which should be a travel
+    /// step) following it. If there is no path, their job is cancelled if
+    /// `cancel_on_failure` is set; otherwise they will carry on and ask again.
+    /// Settlers can only wait for one path at a time.
+    pub fn request(
+        &mut self,
+        requester: usize,
+        start: usize,
+        goal: usize,
+        waiting_job: &JobType,
+        next_job: JobType,
+        cancel_on_failure: bool,
+    ) {
+        if self.pending.insert(requester) {
+            self.queue.push(PathRequest {
+                requester,
+                start,
+                goal,
+                waiting_job: waiting_job.clone(),
+                next_job,
+                cancel_on_failure,
+            });
+        }
+    }
+
+    /// Whether a settler is still waiting for a path.
+    pub fn is_pending(&self, requester: usize) -> bool {
+        self.pending.contains(&requester)
+    }
+}
+
+#[system]
+#[read_component(MyTurn)]
+#[read_component(IdentityTag)]
+pub fn path_solver(
+    ecs: &SubWorld,
+    #[resource] requests: &mut PathRequests,
+    #[resource] cache: &mut PathCache,
+) {
+    if requests.queue.is_empty() {
+        return;
+    }
+
+    let count = usize::min(requests.queue.len(), MAX_PATHS_PER_TICK);
+    let batch: Vec<PathRequest> = requests.queue.drain(..count).collect();
+    for request in batch.iter() {
+        requests.pending.remove(&request.requester);
+    }
+
+    // Skip anyone who has moved on to something else in the meantime
+    let jobs: HashMap<usize, &JobType> = <(&MyTurn, &IdentityTag)>::query()
+        .iter(ecs)
+        .map(|(turn, id)| (id.0, &turn.job))
+        .collect();
+    let batch: Vec<PathRequest> = batch
+        .into_iter()
+        .filter(|r| jobs.get(&r.requester) == Some(&&r.waiting_job))
+        .collect();
+
+    // Cached paths are only as good as what the cache has been told about the
+    // map, so make sure they can still be walked before handing them out
+    let region = REGION.read();
+    let cached: Vec<_> = batch
+        .iter()
+        .map(|r| {
+            let path = cache.get(r.start, r.goal)?;
+            if PathCache::is_still_walkable(&region, r.start, &path.steps) {
+                Some(path)
+            } else {
+                cache.forget(r.start, r.goal);
+                None
+            }
+        })
+        .collect();
+
+    // Results come back in the order they were asked for, so the outcome
+    // doesn't depend on which thread finishes first.
+    let searched: Vec<_> = PATH_POOL.install(|| {
+        batch
+            .par_iter()
+            .zip(cached.par_iter())
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(r, _)| a_star_search(r.start, r.goal, &region))
+            .collect()
+    });
+    std::mem::drop(region);
+
+    let mut searched = searched.into_iter();
+    for (request, cached) in batch.into_iter().zip(cached) {
+        let path = match cached {
+            Some(path) => path,
+            None => {
+                let path = searched.next().unwrap();
+                cache.insert(request.start, request.goal, &path);
+                path
+            }
+        };
+        if path.success {
+            let mut job = request.next_job;
+            if let Some(steps) = job.path_mut() {
+                // They are already standing on the first step
+                *steps = path.steps;
+                if steps.first() == Some(&request.start) {
+                    steps.remove(0);
+                }
+            }
+            messaging::job_changed(request.requester, job);
+        } else if request.cancel_on_failure {
+            messaging::cancel_job(request.requester);
+        }
+    }
+}
@@ -89,11 +89,22 @@ pub fn pause_control(
                     },
                 };
             }
+            VirtualKeyCode::G => {
+                *run_state = RunState::Design {
+                    mode: DesignMode::Rally,
+                };
+            }
+            VirtualKeyCode::P => {
+                *run_state = RunState::Design {
+                    mode: DesignMode::Stockpiles,
+                };
+            }
             VirtualKeyCode::S => {
                 *run_state = RunState::Design {
                     mode: DesignMode::SettlerList,
                 };
             }
+            VirtualKeyCode::F5 => state.save_requested = true,
             _ => {}
         }
     }
@@ -0,0 +1,44 @@
+use super::messaging;
+use super::REGION;
+use bengine::geometry::*;
+use legion::*;
+use nox_components::*;
+use nox_planet::RallyMap;
+use nox_spatial::idxmap;
+
+/// While a rally point is set, settlers drop whatever they are doing and make
+/// their way to it, whatever the time of day. Everyone follows the same Dijkstra
+/// map, so there is no path searching however many of them there are.
+#[system(for_each)]
+pub fn rally(
+    turn: &mut MyTurn,
+    pos: &Position,
+    id: &IdentityTag,
+    _settler: &Settler,
+    #[resource] rally: &RallyMap,
+) {
+    if !turn.active {
+        return;
+    }
+    if rally.rally_point.is_none() {
+        if turn.order == WorkOrder::Rally {
+            turn.order = WorkOrder::None;
+        }
+        return;
+    }
+
+    turn.order = WorkOrder::Rally;
+    if turn.job != JobType::None {
+        messaging::cancel_job(id.0);
+        return;
+    }
+
+    let idx = pos.get_idx();
+    if rally.dijkstra.distance(idx) > 0.0 {
+        // Settlers with no way there stay put
+        if let Some(exit) = rally.dijkstra.find_lowest_exit(idx, &REGION.read()) {
+            let (x, y, z) = idxmap(exit);
+            messaging::entity_moved(id.0, &Point3::new(x, y, z));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use super::REGION;
+use legion::*;
+use nox_planet::RallyMap;
+
+#[system]
+pub fn rally_map(#[resource] map: &mut RallyMap) {
+    if !map.is_dirty {
+        return;
+    }
+
+    let goals: Vec<usize> = map.rally_point.iter().cloned().collect();
+    map.dijkstra.set_goals(&REGION.read(), &goals);
+
+    // Clear dirty flag
+    map.is_dirty = false;
+}
@@ -1,9 +1,8 @@
 use super::super::messaging;
-use crate::modes::playgame::systems::REGION;
+use crate::modes::playgame::systems::PathRequests;
 use legion::world::SubWorld;
 use legion::*;
 use nox_components::*;
-use nox_planet::pathfinding::a_star_search;
 use nox_spatial::idxmap;
 
 #[system]
@@ -12,7 +11,7 @@ use nox_spatial::idxmap;
 #[read_component(IdentityTag)]
 #[read_component(Settler)]
 #[read_component(ReactionJob)]
-pub fn reactions(ecs: &SubWorld) {
+pub fn reactions(ecs: &SubWorld, #[resource] paths: &mut PathRequests) {
     <(&MyTurn, &Position, &IdentityTag)>::query()
         .iter(ecs)
         .for_each(|(turn, pos, id)| {
@@ -31,24 +30,18 @@ pub fn reactions(ecs: &SubWorld) {
                 {
                     match step {
                         ReactionSteps::FindReaction => {
-                            let start = pos.get_idx();
-                            let end = *reaction_location;
-
-                            let rlock = REGION.read();
-                            let path = a_star_search(start, end, &rlock);
-                            if path.success {
-                                messaging::job_changed(
-                                    id.0,
-                                    JobType::Reaction {
-                                        reaction_id: *reaction_id,
-                                        reaction_location: *reaction_location,
-                                        step: ReactionSteps::TravelToReaction { path: path.steps },
-                                    },
-                                );
-                            } else {
-                                // Abandon job
-                                println!("Failed to find path to perform reaction");
-                            }
+                            paths.request(
+                                id.0,
+                                pos.get_idx(),
+                                *reaction_location,
+                                &turn.job,
+                                JobType::Reaction {
+                                    reaction_id: *reaction_id,
+                                    reaction_location: *reaction_location,
+                                    step: ReactionSteps::TravelToReaction { path: Vec::new() },
+                                },
+                                true,
+                            );
                         }
                         ReactionSteps::TravelToReaction { path } => {
                             if path.len() > 1 {
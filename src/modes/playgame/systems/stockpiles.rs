@@ -0,0 +1,154 @@
+use super::{JobBoard, REGION};
+use bengine::geometry::*;
+use legion::*;
+use legion::{systems::CommandBuffer, world::SubWorld};
+use nox_components::*;
+use nox_planet::Region;
+use nox_raws::{ItemDefType, RAWS};
+use nox_spatial::idxmap;
+use std::collections::{HashMap, HashSet};
+
+/// Sends loose items to the closest free stockpile tile that takes them. Items
+/// already sitting in a stockpile that wants them stay put.
+///
+/// Hauls to a stockpile are the only ones without a `Claimed`, so reactions
+/// and constructions can still take the item until someone sets off with it.
+/// Ones nobody has set off on yet are called off if the stockpile is removed,
+/// or no longer takes the item.
+///
+/// Anything that could change where an item belongs (items turning up or being
+/// put down, the map changing, stockpiles being edited) marks the job board
+/// dirty, so this only runs on ticks when the board has been brought up to date.
+#[system]
+#[read_component(Stockpile)]
+#[read_component(Item)]
+#[read_component(Tag)]
+#[read_component(Material)]
+#[read_component(Position)]
+#[read_component(Claimed)]
+#[read_component(RequestHaul)]
+pub fn stockpiles(ecs: &SubWorld, commands: &mut CommandBuffer, #[resource] board: &mut JobBoard) {
+    if !board.refreshed {
+        return;
+    }
+
+    let stockpiles: Vec<&Stockpile> = <&Stockpile>::query().iter(ecs).collect();
+    let owners: HashMap<usize, usize> = stockpiles
+        .iter()
+        .enumerate()
+        .flat_map(|(i, sp)| sp.tiles.iter().map(move |idx| (*idx, i)))
+        .collect();
+    let raws = RAWS.read();
+    let item_types: HashMap<&str, &[ItemDefType]> = raws
+        .items
+        .items
+        .iter()
+        .map(|def| (def.tag.as_str(), def.item_type.as_slice()))
+        .collect();
+    let accepts = |sp: usize, tag: &str, material: usize| {
+        let types = item_types.get(tag).copied().unwrap_or(&[]);
+        stockpiles[sp].filter.accepts(tag, types, material)
+    };
+    let accepted = |idx: usize, tag: &str, material: usize| {
+        owners
+            .get(&idx)
+            .map(|sp| accepts(*sp, tag, material))
+            .unwrap_or(false)
+    };
+
+    // Tiles with something on them, or on its way to them
+    let mut occupied: HashSet<usize> = <(&Item, &Position)>::query()
+        .iter(ecs)
+        .filter_map(|(_, pos)| match pos.loc {
+            Location::Tile { idx } => Some(idx),
+            _ => None,
+        })
+        .collect();
+    <(Entity, &RequestHaul, &Tag, &Material)>::query()
+        .filter(!component::<Claimed>())
+        .iter(ecs)
+        .for_each(|(e, rh, tag, material)| {
+            if rh.in_progress.is_some() || accepted(rh.destination, &tag.0, material.0) {
+                occupied.insert(rh.destination);
+            } else {
+                commands.remove_component::<RequestHaul>(*e);
+                board.is_dirty = true;
+            }
+        });
+
+    if stockpiles.is_empty() {
+        return;
+    }
+
+    // The stockpile tiles that can be reached from each connected part of the
+    // map, worked out for the first item found there
+    let region = REGION.read();
+    let mut reachable: HashMap<Option<u32>, Vec<(usize, usize)>> = HashMap::new();
+    <(Entity, &Item, &Tag, &Material, &Position)>::query()
+        .filter(!component::<Claimed>() & !component::<RequestHaul>())
+        .iter(ecs)
+        .for_each(|(e, _, tag, material, pos)| {
+            let item_idx = match pos.loc {
+                Location::Tile { idx } => idx,
+                _ => return,
+            };
+            if accepted(item_idx, &tag.0, material.0) {
+                return;
+            }
+
+            let tiles = reachable
+                .entry(region.component_id(item_idx))
+                .or_insert_with(|| reachable_tiles(&stockpiles, &region, item_idx));
+            let destination = closest(
+                tiles
+                    .iter()
+                    .filter(|(idx, sp)| !occupied.contains(idx) && accepts(*sp, &tag.0, material.0))
+                    .map(|(idx, _)| *idx),
+                item_idx,
+            );
+            if let Some(destination) = destination {
+                commands.add_component(
+                    *e,
+                    RequestHaul {
+                        destination,
+                        in_progress: None,
+                    },
+                );
+                occupied.insert(destination);
+                board.is_dirty = true;
+            }
+        });
+}
+
+/// Every stockpile tile, with the stockpile it is in, that a settler could
+/// carry an item at `item_idx` to.
+fn reachable_tiles(
+    stockpiles: &[&Stockpile],
+    region: &Region,
+    item_idx: usize,
+) -> Vec<(usize, usize)> {
+    stockpiles
+        .iter()
+        .enumerate()
+        .flat_map(|(i, sp)| sp.tiles.iter().map(move |idx| (*idx, i)))
+        .filter(|(idx, _)| {
+            region.flag(*idx, Region::CAN_STAND_HERE) && region.are_connected(item_idx, *idx)
+        })
+        .collect()
+}
+
+/// The tile closest to an item.
+fn closest(tiles: impl Iterator<Item = usize>, item_idx: usize) -> Option<usize> {
+    let (x, y, z) = idxmap(item_idx);
+    let item_pos = Point3::new(x, y, z);
+    tiles
+        .map(|idx| {
+            let (x, y, z) = idxmap(idx);
+            (
+                idx,
+                DistanceAlg::Pythagoras.distance3d(item_pos, Point3::new(x, y, z)),
+            )
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .map(|(idx, _)| idx)
+}
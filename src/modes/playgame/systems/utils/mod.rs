@@ -1,2 +1,4 @@
 mod toolfinder;
 pub use toolfinder::*;
+mod reaction_inputs;
+pub use reaction_inputs::*;
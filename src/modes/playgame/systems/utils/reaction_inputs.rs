@@ -0,0 +1,55 @@
+use bengine::geometry::*;
+use legion::world::SubWorld;
+use legion::*;
+use nox_components::*;
+use nox_planet::Region;
+use nox_raws::ReactionItem;
+use nox_spatial::idxmap;
+use std::collections::HashSet;
+
+/// Picks the closest free items to a workshop that make up a reaction's
+/// inputs, skipping any already `taken` for another job. Items that have been
+/// put away in a stockpile are used before ones lying about. Returns `None`
+/// unless every input can be found.
+pub fn select_components(
+    ecs: &SubWorld,
+    requires: &[ReactionItem],
+    region: &Region,
+    workshop_pos: &Position,
+    taken: &HashSet<usize>,
+) -> Option<Vec<usize>> {
+    let workshop_idx = workshop_pos.get_idx();
+    let workshop_pos = workshop_pos.as_point3();
+    let stockpiled = stockpiled_tiles(<&Stockpile>::query().iter(ecs));
+    let mut selected_components = Vec::new();
+    for ri in requires.iter() {
+        let mut available: Vec<(usize, (bool, f32))> =
+            <(&Tag, &Position, &IdentityTag, Option<&RequestHaul>)>::query()
+                .filter(!component::<Claimed>())
+                .iter(ecs)
+                .filter(|(tag, _pos, id, haul)| {
+                    tag.0 == ri.tag && !taken.contains(&id.0) && !is_being_hauled(*haul)
+                })
+                .map(|(_tag, pos, id, _haul)| (id.0, pos.effective_location_sw(ecs)))
+                // Nobody could fetch components from somewhere walled off
+                .filter(|(_id, pos)| region.are_connected(workshop_idx, *pos))
+                .map(|(id, pos)| {
+                    let (x, y, z) = idxmap(pos);
+                    let distance =
+                        DistanceAlg::Pythagoras.distance3d(workshop_pos, Point3::new(x, y, z));
+                    (id, (!stockpiled.contains(&pos), distance))
+                })
+                .collect();
+
+        if available.len() < ri.qty as usize {
+            return None;
+        }
+        available.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        available
+            .iter()
+            .map(|(id, _)| *id)
+            .take(ri.qty as usize)
+            .for_each(|id| selected_components.push(id));
+    }
+    Some(selected_components)
+}
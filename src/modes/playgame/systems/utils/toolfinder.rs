@@ -31,3 +31,11 @@ pub fn am_i_carrying_tool(ecs: &SubWorld, holder: usize, usage: ToolType) -> Too
         .nth(0)
         .unwrap_or(ToolCarrying::NoTool)
 }
+
+/// Whether there is a tool of a kind that nobody has claimed yet.
+pub fn is_tool_free(ecs: &SubWorld, usage: ToolType) -> bool {
+    <&Tool>::query()
+        .filter(!component::<Claimed>())
+        .iter(ecs)
+        .any(|tool| tool.usage == usage)
+}
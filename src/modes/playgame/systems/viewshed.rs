@@ -101,6 +101,6 @@ fn line_func_3d<F: FnMut(Vec3)>(start: Vec3, end: Vec3, mut func: F) {
 }
 
 fn reveal(idx: usize, view: &mut FieldOfView) {
-    REGION.write().revealed[idx] = true; // TODO: Make conditional
+    REGION.write().reveal(idx); // TODO: Make conditional
     view.visible_tiles.insert(idx);
 }
@@ -0,0 +1,138 @@
+use super::messaging;
+use super::utils::select_components;
+use super::REGION;
+use legion::world::SubWorld;
+use legion::*;
+use nox_components::*;
+use nox_raws::*;
+use std::collections::HashSet;
+
+/// Workshops carry out one reaction at a time. When one is free, it starts on
+/// the next of its work orders that wants doing and has all of its inputs to
+/// hand; failing that, on one of its automatic reactions. Orders take turns,
+/// so that one set to repeat doesn't keep the others waiting.
+#[system]
+#[read_component(Workshop)]
+#[read_component(Tag)]
+#[read_component(Item)]
+#[read_component(Building)]
+#[read_component(ReactionJob)]
+#[read_component(Position)]
+#[read_component(IdentityTag)]
+#[read_component(Claimed)]
+#[read_component(RequestHaul)]
+#[read_component(Stockpile)]
+#[write_component(ReactionOrders)]
+pub fn work_orders(ecs: &mut SubWorld) {
+    let busy: HashSet<usize> = <&ReactionJob>::query()
+        .iter(ecs)
+        .map(|rj| rj.workshop_id)
+        .collect();
+
+    let workshops: Vec<(usize, String, Position, bool, Vec<ReactionOrder>)> =
+        <(&Workshop, &Tag, &Building, &Position, &IdentityTag)>::query()
+            .filter(!component::<Claimed>())
+            .iter(ecs)
+            .filter(|(_ws, _tag, building, _pos, id)| building.complete && !busy.contains(&id.0))
+            .map(|(ws, tag, _building, pos, id)| {
+                (
+                    id.0,
+                    tag.0.clone(),
+                    *pos,
+                    ws.has_automatic_jobs,
+                    orders_of(ecs, id.0),
+                )
+            })
+            .collect();
+
+    let region = REGION.read();
+    let rlock = RAWS.read();
+    // Components picked for one workshop this turn aren't claimed until the
+    // job is created, so keep track of them here
+    let mut taken = HashSet::new();
+    for (workshop_id, tag, pos, automatic, orders) in workshops {
+        let reaction = |name: &str| {
+            rlock
+                .reactions
+                .reactions
+                .iter()
+                .find(|r| r.name == name && r.workshop == tag)
+        };
+
+        let mut started = None;
+        for (i, order) in orders.iter().enumerate() {
+            if let Some(r) = reaction(&order.reaction_tag) {
+                if !wants_more(ecs, order, r) {
+                    continue;
+                }
+                if let Some(components) = select_components(ecs, &r.inputs, &region, &pos, &taken) {
+                    taken.extend(components.iter().cloned());
+                    messaging::create_reaction_job(workshop_id, &r.name, &components);
+                    started = Some(i);
+                    break;
+                }
+            }
+        }
+
+        if let Some(i) = started {
+            order_started(ecs, workshop_id, i);
+        } else if automatic {
+            if let Some((r, components)) = rlock
+                .reactions
+                .reactions
+                .iter()
+                .filter(|r| r.workshop == tag && r.automatic)
+                .find_map(|r| {
+                    select_components(ecs, &r.inputs, &region, &pos, &taken).map(|c| (r, c))
+                })
+            {
+                taken.extend(components.iter().cloned());
+                messaging::create_reaction_job(workshop_id, &r.name, &components);
+            }
+        }
+    }
+}
+
+fn orders_of(ecs: &SubWorld, workshop_id: usize) -> Vec<ReactionOrder> {
+    <(&ReactionOrders, &IdentityTag)>::query()
+        .iter(ecs)
+        .find(|(_, id)| id.0 == workshop_id)
+        .map(|(orders, _)| orders.0.clone())
+        .unwrap_or_default()
+}
+
+fn wants_more(ecs: &SubWorld, order: &ReactionOrder, reaction: &ReactionDef) -> bool {
+    match order.goal {
+        OrderGoal::Make(n) => n > 0,
+        OrderGoal::Repeat => true,
+        OrderGoal::KeepInStock(n) => {
+            if let Some(output) = reaction.outputs.first() {
+                let in_stock = <(&Item, &Tag)>::query()
+                    .iter(ecs)
+                    .filter(|(_, tag)| tag.0 == output.tag)
+                    .count();
+                (in_stock as i32) < n
+            } else {
+                false
+            }
+        }
+    }
+}
+
+/// Counts a job towards its order, which then goes to the back of the queue;
+/// orders to make a number of something are done once they have all started.
+fn order_started(ecs: &mut SubWorld, workshop_id: usize, index: usize) {
+    <(&mut ReactionOrders, &IdentityTag)>::query()
+        .iter_mut(ecs)
+        .filter(|(_, id)| id.0 == workshop_id)
+        .for_each(|(orders, _)| {
+            let mut order = orders.0.remove(index);
+            if let OrderGoal::Make(n) = &mut order.goal {
+                *n -= 1;
+                if *n <= 0 {
+                    return;
+                }
+            }
+            orders.0.push(order);
+        });
+}
@@ -1,10 +1,12 @@
+use super::job_board::{Job, JobSource};
+use super::{JobBoard, REGION};
 use crate::modes::playgame::messaging;
 
 use bengine::geometry::*;
 use legion::world::SubWorld;
 use legion::*;
 use nox_components::*;
-use nox_planet::{LumberMap, MiningMap};
+use nox_planet::{LumberMap, MiningMap, Region};
 use nox_spatial::*;
 
 #[system]
@@ -12,268 +14,145 @@ use nox_spatial::*;
 #[read_component(Settler)]
 #[read_component(Position)]
 #[read_component(IdentityTag)]
-#[read_component(RequestHaul)]
-#[read_component(Blueprint)]
-#[read_component(Building)]
-#[read_component(ReactionJob)]
-#[read_component(Construction)]
+#[read_component(Tool)]
+#[read_component(Claimed)]
 pub fn work_shift(
     ecs: &mut SubWorld,
     #[resource] mining: &MiningMap,
     #[resource] lumber: &LumberMap,
+    #[resource] board: &mut JobBoard,
 ) {
-    let mut haulables = haulage_list(ecs);
-    let buildables = building_list(ecs);
-    let mut reactions = reactions_list(ecs);
-    let mut construction = construction_list(ecs);
+    let mut tools = Tools::new(ecs);
+    let region = REGION.read();
     <(&mut MyTurn, &Settler, &Position, &IdentityTag)>::query()
         .iter_mut(ecs)
         .for_each(|(turn, settler, pos, id)| {
-            if turn.active && turn.shift == ScheduleTime::Work && turn.job == JobType::None {
+            if turn.active
+                && turn.shift == ScheduleTime::Work
+                && turn.job == JobType::None
+                && turn.order != WorkOrder::Rally
+            {
                 turn.order = WorkOrder::None;
-                let settler_pos = pos.get_idx();
 
-                let mut possible_jobs: Vec<(f32, JobType)> = Vec::new();
-
-                if let Some(mining_cost) = consider_mining(settler, mining, settler_pos) {
-                    possible_jobs.push((
-                        mining_cost,
-                        JobType::Mining {
-                            step: MiningSteps::FindPick,
-                            tool_id: None,
-                        },
-                    ));
-                }
-                if let Some(lumber_cost) = consider_lumber(settler, lumber, settler_pos) {
-                    possible_jobs.push((
-                        lumber_cost,
-                        JobType::FellTree {
-                            step: LumberjackSteps::FindAxe,
-                            tool_id: None,
-                        },
-                    ));
-                }
-                if let Some(haul_cost) = consider_hauling(&haulables, pos.as_point3()) {
-                    possible_jobs.push((
-                        haul_cost.0,
-                        JobType::Haul {
-                            item_id: haul_cost.1,
-                            step: HaulSteps::FindItem,
-                        },
-                    ));
-                }
-                if let Some(build_cost) = consider_building(&buildables, pos.as_point3()) {
-                    possible_jobs.push((
-                        build_cost.0,
-                        JobType::ConstructBuilding {
-                            building_id: build_cost.1,
-                            step: BuildingSteps::FindBuilding,
-                        },
-                    ));
-                }
-                if let Some(reaction_cost) = consider_reactions(&reactions, pos.as_point3()) {
-                    println!(
-                        "Picked reaction {} at {:?}",
-                        reaction_cost.1, reaction_cost.2
-                    );
-                    possible_jobs.push((
-                        reaction_cost.0,
-                        JobType::Reaction {
-                            reaction_id: reaction_cost.1,
-                            reaction_location: reaction_cost.2,
-                            step: ReactionSteps::FindReaction,
-                        },
-                    ));
-                }
-                if let Some(build_cost) = consider_construction(&construction, pos.as_point3()) {
-                    println!("Considering a life in construction");
-                    possible_jobs.push((
-                        build_cost.0,
-                        JobType::Construct {
-                            building_id: build_cost.1,
-                            step: ConstructionSteps::FindBuilding,
-                        },
-                    ));
-                }
-
-                if possible_jobs.is_empty() {
-                    turn.order = WorkOrder::MoveRandomly;
-                } else {
-                    possible_jobs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-                    turn.job = possible_jobs[0].1.clone();
-
-                    match turn.job {
-                        JobType::Haul { item_id, .. } => {
-                            // Remove from haulables list
-                            haulables.retain(|(_, iid)| *iid != item_id);
-                            messaging::haul_in_progress(item_id, id.0);
+                // The settler's favourite kind of work first, then the most
+                // important job, then the closest
+                let best = board
+                    .available()
+                    .filter(|(_, job)| tools.can_work(id.0, &job.labor))
+                    .filter_map(|(source, job)| {
+                        let preference = settler.labors.priority(&job.labor);
+                        if preference == 0 {
+                            return None;
                         }
-                        JobType::Reaction { reaction_id, .. } => {
-                            reactions.retain(|(_, rid)| *rid != reaction_id);
-                            messaging::reaction_in_progress(reaction_id, id.0);
+                        job_cost(pos, source, job, &region, mining, lumber)
+                            .map(|cost| (preference, job.priority, cost, *source, job.clone()))
+                    })
+                    .min_by(|a, b| {
+                        b.0.cmp(&a.0)
+                            .then(b.1.cmp(&a.1))
+                            .then(a.2.partial_cmp(&b.2).unwrap())
+                    });
+
+                if let Some((_, _, _, source, job)) = best {
+                    turn.job = job.job;
+                    board.reserve(source, id.0);
+                    tools.claim(id.0, &job.labor);
+                    match source {
+                        JobSource::Haul(item_id) => messaging::haul_in_progress(item_id, id.0),
+                        JobSource::Reaction(reaction_id) => {
+                            messaging::reaction_in_progress(reaction_id, id.0)
                         }
-                        JobType::Construct { building_id, .. } => {
-                            construction.retain(|(_, bid)| building_id != *bid);
-                            messaging::construction_in_progress(building_id, id.0);
+                        JobSource::Construction(building_id) => {
+                            messaging::construction_in_progress(building_id, id.0)
                         }
                         _ => {}
                     }
+                } else {
+                    turn.order = WorkOrder::MoveRandomly;
                 }
             }
         });
 }
 
-fn consider_mining(settler: &Settler, mining: &MiningMap, pos: usize) -> Option<f32> {
-    if settler.miner && mining.dijkstra[pos] < f32::MAX {
-        Some(mining.dijkstra[pos])
-    } else {
-        None
-    }
+/// Who has which tools, and how many are lying around free, so that settlers
+/// only take work they can get a tool for.
+struct Tools {
+    claimed: Vec<(usize, ToolType)>,
+    free: Vec<ToolType>,
 }
 
-fn consider_lumber(settler: &Settler, lumber: &LumberMap, pos: usize) -> Option<f32> {
-    if settler.lumberjack && lumber.dijkstra[pos] < f32::MAX {
-        Some(lumber.dijkstra[pos])
-    } else {
-        None
+impl Tools {
+    fn new(ecs: &SubWorld) -> Self {
+        let mut tools = Self {
+            claimed: Vec::new(),
+            free: Vec::new(),
+        };
+        <(&Tool, Option<&Claimed>)>::query()
+            .iter(ecs)
+            .for_each(|(tool, claim)| match claim {
+                Some(claim) => tools.claimed.push((claim.by, tool.usage)),
+                None => tools.free.push(tool.usage),
+            });
+        tools
     }
-}
-
-fn haulage_list(ecs: &SubWorld) -> Vec<(usize, usize)> {
-    <(&RequestHaul, &Position, &IdentityTag)>::query()
-        .iter(ecs)
-        .filter(|(rh, _, _)| rh.in_progress.is_none())
-        .map(|(_, pos, id)| (pos.get_idx(), id.0))
-        .collect()
-}
 
-fn consider_hauling(haulables: &[(usize, usize)], settler_pos: Point3) -> Option<(f32, usize)> {
-    let mut hsort: Vec<(f32, usize)> = haulables
-        .iter()
-        .map(|(pos, id)| {
-            let (x, y, z) = idxmap(*pos);
-            (
-                DistanceAlg::Pythagoras.distance3d(Point3::new(x, y, z), settler_pos),
-                *id,
-            )
-        })
-        .collect();
-    if hsort.is_empty() {
-        None
-    } else {
-        hsort.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        Some(hsort[0])
-    }
-}
-
-fn building_list(ecs: &SubWorld) -> Vec<(usize, usize)> {
-    <(&Building, &Blueprint, &Position, &IdentityTag)>::query()
-        .iter(ecs)
-        .filter(|(building, blueprint, _, _)| !building.complete && blueprint.ready_to_build)
-        .map(|(_, _, bpos, bid)| (bpos.get_idx(), bid.0))
-        .collect()
-}
-
-fn consider_building(buildables: &[(usize, usize)], settler_pos: Point3) -> Option<(f32, usize)> {
-    if buildables.is_empty() {
-        return None;
-    }
-    let mut hsort: Vec<(f32, usize)> = buildables
-        .iter()
-        .map(|(pos, id)| {
-            let (x, y, z) = idxmap(*pos);
-            (
-                DistanceAlg::Pythagoras.distance3d(Point3::new(x, y, z), settler_pos),
-                *id,
-            )
-        })
-        .collect();
-    if hsort.is_empty() {
-        None
-    } else {
-        hsort.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        Some(hsort[0])
+    fn can_work(&self, settler_id: usize, labor: &Labor) -> bool {
+        match labor.tool() {
+            None => true,
+            Some(usage) => {
+                self.claimed.contains(&(settler_id, usage)) || self.free.contains(&usage)
+            }
+        }
     }
-}
-
-fn reactions_list(ecs: &SubWorld) -> Vec<(usize, usize)> {
-    <(&ReactionJob, &IdentityTag, &Blueprint)>::query()
-        .filter(!component::<Claimed>())
-        .iter(ecs)
-        .filter(|(rj, _id, bp)| bp.ready_to_build && rj.in_progress.is_none())
-        .map(|(_rj, id, _bp)| {
-            let bpos = <(&IdentityTag, &Position)>::query()
-                .iter(ecs)
-                .filter(|(wid, _)| wid.0 == id.0)
-                .map(|(_, pos)| pos.effective_location_sw(ecs))
-                .nth(0)
-                .unwrap();
-
-            (bpos, id.0)
-        })
-        .collect()
-}
 
-fn consider_reactions(
-    reactions: &[(usize, usize)],
-    settler_pos: Point3,
-) -> Option<(f32, usize, usize)> {
-    if reactions.is_empty() {
-        return None;
-    }
-    let mut hsort: Vec<(f32, usize, usize)> = reactions
-        .iter()
-        .map(|(pos, id)| {
-            let (x, y, z) = idxmap(*pos);
-            (
-                DistanceAlg::Pythagoras.distance3d(Point3::new(x, y, z), settler_pos),
-                *id,
-                *pos,
-            )
-        })
-        .collect();
-    if hsort.is_empty() {
-        None
-    } else {
-        hsort.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        Some(hsort[0])
+    /// Has a settler claim a tool for their new job, if they need one and
+    /// don't have it yet.
+    fn claim(&mut self, settler_id: usize, labor: &Labor) {
+        if let Some(usage) = labor.tool() {
+            if !self.claimed.contains(&(settler_id, usage)) {
+                if let Some(i) = self.free.iter().position(|t| *t == usage) {
+                    self.free.remove(i);
+                    self.claimed.push((settler_id, usage));
+                    messaging::claim_tool(settler_id, usage);
+                }
+            }
+        }
     }
 }
 
-fn construction_list(ecs: &SubWorld) -> Vec<(usize, usize)> {
-    let cons: Vec<(usize, usize)> = <(&Construction, &Blueprint, &Position, &IdentityTag)>::query()
-        .iter(ecs)
-        .filter(|(c, bp, _, _)| bp.ready_to_build && c.in_progress.is_none())
-        .map(|(_, _, pos, id)| (pos.get_idx(), id.0))
-        .collect();
-    if !cons.is_empty() {
-        println!("{:?}", cons);
+/// How far a settler would have to go to do a job, if they can get there.
+fn job_cost(
+    settler_pos: &Position,
+    source: &JobSource,
+    job: &Job,
+    region: &Region,
+    mining: &MiningMap,
+    lumber: &LumberMap,
+) -> Option<f32> {
+    let start = settler_pos.get_idx();
+    match source {
+        JobSource::Mining => reachable(mining.dijkstra.distance(start)),
+        JobSource::Lumber => reachable(lumber.dijkstra.distance(start)),
+        _ => {
+            let position = job.position?;
+            if !region.are_connected(start, position) {
+                return None;
+            }
+            if let Some(destination) = job.destination {
+                if !region.are_connected(start, destination) {
+                    return None;
+                }
+            }
+            let (x, y, z) = idxmap(position);
+            Some(DistanceAlg::Pythagoras.distance3d(Point3::new(x, y, z), settler_pos.as_point3()))
+        }
     }
-    cons
 }
 
-fn consider_construction(
-    construction: &Vec<(usize, usize)>,
-    settler_pos: Point3,
-) -> Option<(f32, usize)> {
-    if construction.is_empty() {
-        return None;
-    }
-    let mut hsort: Vec<(f32, usize)> = construction
-        .iter()
-        .map(|(pos, id)| {
-            let (x, y, z) = idxmap(*pos);
-            (
-                DistanceAlg::Pythagoras.distance3d(Point3::new(x, y, z), settler_pos),
-                *id,
-            )
-        })
-        .collect();
-    if hsort.is_empty() {
-        None
+fn reachable(distance: f32) -> Option<f32> {
+    if distance < f32::MAX {
+        Some(distance)
     } else {
-        hsort.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-        println!("{:?}", hsort);
-        Some(hsort[0])
+        None
     }
 }
@@ -1,9 +1,7 @@
-use bengine::geometry::*;
 use bengine::gui::*;
 use legion::*;
 use nox_components::*;
-use nox_raws::{ReactionItem, RAWS};
-use nox_spatial::idxmap;
+use nox_raws::RAWS;
 use parking_lot::RwLock;
 
 use crate::modes::playgame::messaging;
@@ -37,8 +35,6 @@ struct BuildingReaction {
     auto: bool,
     mode: usize,
     qty: i32,
-    reaction_def: nox_raws::ReactionDef,
-    workshop_pos: Point3,
 }
 
 lazy_static! {
@@ -50,29 +46,21 @@ pub fn setup_building_info(id: usize, ecs: &World) {
     *bl = BuildingInfo::new(); // Clear it
     bl.id = id;
 
-    let (name, description, building, entity, btag, pos) = <(
-        &IdentityTag,
-        &Name,
-        &Description,
-        &Building,
-        Entity,
-        &Tag,
-        &Position,
-    )>::query()
-    .iter(ecs)
-    .filter(|(bid, _, _, _, _, _, _)| bid.0 == id)
-    .map(|(_, n, d, b, e, tag, pos)| {
-        (
-            ImString::new(&n.name),
-            ImString::new(&d.desc),
-            b,
-            *e,
-            tag.0.clone(),
-            pos.as_point3(),
-        )
-    })
-    .nth(0)
-    .unwrap();
+    let (name, description, building, entity, btag) =
+        <(&IdentityTag, &Name, &Description, &Building, Entity, &Tag)>::query()
+            .iter(ecs)
+            .filter(|(bid, _, _, _, _, _)| bid.0 == id)
+            .map(|(_, n, d, b, e, tag)| {
+                (
+                    ImString::new(&n.name),
+                    ImString::new(&d.desc),
+                    b,
+                    *e,
+                    tag.0.clone(),
+                )
+            })
+            .nth(0)
+            .unwrap();
 
     bl.name = name;
     bl.description = description;
@@ -100,8 +88,6 @@ pub fn setup_building_info(id: usize, ecs: &World) {
                         auto: r.automatic,
                         mode: 0,
                         qty: 1,
-                        reaction_def: r.clone(),
-                        workshop_pos: pos,
                     };
                     bl.reactions.push(br);
                 });
@@ -110,11 +96,16 @@ pub fn setup_building_info(id: usize, ecs: &World) {
 }
 
 pub fn show_building_info(imgui: &Ui, ecs: &World, _id: &usize) {
-    let reaction_modes = [im_str!("Make"), im_str!("Until You Have")];
+    let reaction_modes = [im_str!("Make"), im_str!("Repeat"), im_str!("Keep in stock")];
 
     let mut bl = BUILDING_INFO.write();
     let tmp_name = bl.name.clone();
     let tmp_id = bl.id;
+    let orders = <(&ReactionOrders, &IdentityTag)>::query()
+        .iter(ecs)
+        .find(|(_, id)| id.0 == tmp_id)
+        .map(|(orders, _)| orders.0.clone())
+        .unwrap_or_default();
     let window = Window::new(&tmp_name);
     window
         .size([600.0, 400.0], Condition::FirstUseEver)
@@ -155,68 +146,47 @@ pub fn show_building_info(imgui: &Ui, ecs: &World, _id: &usize) {
                         &mut r.mode,
                         &reaction_modes,
                     );
-                    imgui.same_line(0.0);
-                    imgui.set_next_item_width(75.0);
-                    imgui
-                        .input_int(&ImString::new(&format!("##qty{}", r.name)), &mut r.qty)
-                        .step(1)
-                        .step_fast(1)
-                        .build();
+                    if r.mode != 1 {
+                        imgui.same_line(0.0);
+                        imgui.set_next_item_width(75.0);
+                        imgui
+                            .input_int(&ImString::new(&format!("##qty{}", r.name)), &mut r.qty)
+                            .step(1)
+                            .step_fast(1)
+                            .build();
+                        r.qty = i32::max(r.qty, 1);
+                    }
                     imgui.same_line(0.0);
                     if imgui.button(
                         &ImString::new(&format!("Queue##doit{}", r.name)),
                         [50.0, 20.0],
                     ) {
-                        for _ in 0..r.qty {
-                            // Make the job
-                            if let Some(components) =
-                                select_components(ecs, &r.reaction_def.inputs, r.workshop_pos)
-                            {
-                                messaging::create_reaction_job(
-                                    tmp_id,
-                                    &r.reaction_def.name,
-                                    &components,
-                                );
-                            }
-                        }
+                        let goal = match r.mode {
+                            0 => OrderGoal::Make(r.qty),
+                            1 => OrderGoal::Repeat,
+                            _ => OrderGoal::KeepInStock(r.qty),
+                        };
+                        messaging::add_reaction_order(tmp_id, r.name.to_str(), goal);
                     }
                 });
             }
-        });
-}
 
-// TODO: Make this a library function
-fn select_components(
-    ecs: &World,
-    requires: &[ReactionItem],
-    workshop_pos: Point3,
-) -> Option<Vec<usize>> {
-    let mut selected_components = Vec::new();
-    for ri in requires.iter() {
-        let mut available: Vec<(usize, f32)> = <(&Tag, &Position, &IdentityTag)>::query()
-            .filter(!component::<Claimed>())
-            .iter(ecs)
-            .filter(|(tag, _pos, _id)| tag.0 == ri.tag)
-            .map(|(_tag, pos, id)| (id.0, pos.effective_location(ecs)))
-            .map(|(id, pos)| {
-                let (x, y, z) = idxmap(pos);
-                (
-                    id,
-                    DistanceAlg::Pythagoras.distance3d(workshop_pos, Point3::new(x, y, z)),
-                )
-            })
-            .collect();
-
-        if available.len() < ri.qty as usize {
-            // return None;
-        } else {
-            available.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-            available
-                .iter()
-                .map(|(id, _)| *id)
-                .take(ri.qty as usize)
-                .for_each(|id| selected_components.push(id));
-        }
-    }
-    Some(selected_components)
+            if !orders.is_empty() {
+                imgui.text_colored([1.0, 1.0, 0.0, 1.0], im_str!("Work Orders:"));
+                orders.iter().enumerate().for_each(|(i, order)| {
+                    imgui.text(ImString::new(&format!(
+                        "{}: {}",
+                        order.reaction_tag,
+                        order.goal.describe()
+                    )));
+                    imgui.same_line(260.0);
+                    if imgui.button(
+                        &ImString::new(&format!("\u{f05e} Cancel##order{}", i)),
+                        [100.0, 20.0],
+                    ) {
+                        messaging::remove_reaction_order(tmp_id, i);
+                    }
+                });
+            }
+        });
 }
@@ -1,4 +1,4 @@
-use crate::modes::playgame::systems::REGION;
+use crate::modes::playgame::systems::{JobBoard, REGION};
 use bengine::geometry::*;
 use bengine::gui::*;
 use legion::*;
@@ -20,6 +20,7 @@ pub fn building_display(
     ecs: &mut World,
     mouse_world_pos: &(usize, usize, usize),
     bidx: i32,
+    job_board: &mut JobBoard,
 ) -> (i32, Option<usize>) {
     let mut available_buildings = Vec::<AvailableBuilding>::new();
 
@@ -99,7 +100,7 @@ pub fn building_display(
             if !region.flag(*idx, Region::CAN_STAND_HERE) {
                 can_build = false;
             }
-            match &region.tile_types[*idx] {
+            match &region.tile_type(*idx) {
                 TileType::Stairs { .. } => {
                     can_build = false;
                 }
@@ -157,6 +158,7 @@ pub fn building_display(
                             });
                         }
                     });
+                job_board.is_dirty = true;
             }
 
             (bid, Some(btag))
@@ -175,29 +177,36 @@ fn select_components(
     let mut result = Vec::new();
     let building_pos = Point3::new(mouse_world_pos.0, mouse_world_pos.1, mouse_world_pos.2);
     let binfo = raws.buildings.building_by_tag(rtag).unwrap();
+    let stockpiled = stockpiled_tiles(<&Stockpile>::query().iter(ecs));
     binfo.components.iter().for_each(|req_comp| {
-        let mut available_components: Vec<(f32, usize, Entity)> =
-            <(Entity, Read<Position>, Read<Tag>, Read<IdentityTag>)>::query()
-                .filter(component::<Item>() & !component::<Claimed>())
-                .iter(ecs)
-                .filter(|(_, _, tag, _)| tag.0 == req_comp.item)
-                .map(|(e, pos, _tag, id)| {
-                    (
-                        DistanceAlg::Pythagoras.distance3d(building_pos, pos.as_point3()),
-                        id.0,
-                        *e,
-                    )
-                })
-                .collect();
-
-        // Sort by closest
-        available_components.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let mut available_components: Vec<(f32, usize, Entity, bool)> = <(
+            Entity,
+            Read<Position>,
+            Read<Tag>,
+            Read<IdentityTag>,
+            Option<&RequestHaul>,
+        )>::query()
+        .filter(component::<Item>() & !component::<Claimed>())
+        .iter(ecs)
+        .filter(|(_, _, tag, _, haul)| tag.0 == req_comp.item && !is_being_hauled(*haul))
+        .map(|(e, pos, _tag, id, _)| {
+            (
+                DistanceAlg::Pythagoras.distance3d(building_pos, pos.as_point3()),
+                id.0,
+                *e,
+                stockpiled.contains(&pos.get_idx()),
+            )
+        })
+        .collect();
+
+        // Sort by closest, taking from stockpiles first
+        available_components.sort_by(|a, b| b.3.cmp(&a.3).then(a.0.partial_cmp(&b.0).unwrap()));
 
         // Take the first n
         available_components
             .iter()
             .take(req_comp.qty as usize)
-            .for_each(|cc| result.push(cc.clone()));
+            .for_each(|(distance, id, e, _)| result.push((*distance, *id, *e)));
     });
 
     result
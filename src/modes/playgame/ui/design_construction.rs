@@ -1,4 +1,4 @@
-use crate::modes::playgame::systems::REGION;
+use crate::modes::playgame::systems::{JobBoard, REGION};
 use bengine::{geometry::Point3, gui::*};
 use legion::*;
 use nox_components::*;
@@ -31,6 +31,7 @@ pub fn show_construction(
     ecs: &mut World,
     mouse_world_pos: &(usize, usize, usize),
     construction_map: &mut ConstructionMap,
+    job_board: &mut JobBoard,
 ) {
     let construct_modes = [
         im_str!("Wall (W)"),
@@ -85,6 +86,7 @@ pub fn show_construction(
                         for e in to_remove.iter() {
                             ecs.remove(*e);
                         }
+                        job_board.is_dirty = true;
                     } else {
                         if can_build_here(ecs, &mouse_world_pos, &cp.construct_mode) {
                             let new_id = IdentityTag::new();
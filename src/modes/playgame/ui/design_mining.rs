@@ -232,16 +232,16 @@ pub fn validate_mining(
     let idx = mapidx(mouse_world_pos.0, mouse_world_pos.1, camera_pos.z as usize);
     let rlock = REGION.read();
     match mining_mode {
-        MiningMode::Dig => match rlock.tile_types[idx] {
+        MiningMode::Dig => match rlock.tile_type(idx) {
             TileType::Empty => false,
             TileType::Floor => false,
             _ => true,
         },
-        MiningMode::Down | MiningMode::UpDown | MiningMode::Up => match rlock.tile_types[idx] {
+        MiningMode::Down | MiningMode::UpDown | MiningMode::Up => match rlock.tile_type(idx) {
             TileType::Empty => false,
             _ => true,
         },
-        MiningMode::Channel => rlock.tile_types[idx] == TileType::Floor,
+        MiningMode::Channel => rlock.tile_type(idx) == TileType::Floor,
         _ => false,
     }
 }
@@ -0,0 +1,34 @@
+use crate::modes::playgame::systems::REGION;
+use bengine::gui::*;
+use nox_planet::{RallyMap, Region};
+use nox_spatial::mapidx;
+
+pub fn rally_display(
+    imgui: &Ui,
+    mouse_world_pos: &(usize, usize, usize),
+    rally_map: &mut RallyMap,
+) {
+    let title = format!(
+        "Rally Mode. Click to call everyone to a spot, right click to stand them down. ### Rally",
+    );
+    let title_tmp = ImString::new(title);
+    let window = Window::new(&title_tmp);
+    window
+        .collapsed(true, Condition::FirstUseEver)
+        .no_inputs()
+        .size([420.0, 100.0], Condition::FirstUseEver)
+        .movable(false)
+        .position([0.0, 20.0], Condition::FirstUseEver)
+        .build(imgui, || {});
+
+    if imgui.io().mouse_down[0] {
+        let idx = mapidx(mouse_world_pos.0, mouse_world_pos.1, mouse_world_pos.2);
+        if REGION.read().flag(idx, Region::CAN_STAND_HERE) {
+            rally_map.set_rally_point(Some(idx));
+        }
+    }
+
+    if imgui.io().mouse_down[1] {
+        rally_map.set_rally_point(None);
+    }
+}
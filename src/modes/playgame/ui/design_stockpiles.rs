@@ -0,0 +1,254 @@
+use crate::modes::playgame::systems::{JobBoard, REGION};
+use bengine::gui::*;
+use legion::*;
+use nox_components::*;
+use nox_planet::Region;
+use nox_raws::{ItemDefType, RAWS};
+use nox_spatial::mapidx;
+use parking_lot::RwLock;
+
+pub struct StockpileParams {
+    /// The stockpile being painted and edited, by id.
+    pub selected: Option<usize>,
+    add_tag: usize,
+    add_material: usize,
+}
+
+impl StockpileParams {
+    fn new() -> Self {
+        Self {
+            selected: None,
+            add_tag: 0,
+            add_material: 0,
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref STOCKPILE_PARAMS: RwLock<StockpileParams> = RwLock::new(StockpileParams::new());
+}
+
+const ITEM_TYPES: [(ItemDefType, &str); 6] = [
+    (ItemDefType::Component, "Components"),
+    (ItemDefType::Food, "Food"),
+    (ItemDefType::Drink, "Drink"),
+    (ItemDefType::ToolDigging, "Digging Tools"),
+    (ItemDefType::ToolChopping, "Chopping Tools"),
+    (ItemDefType::ToolFarming, "Farming Tools"),
+];
+
+pub fn stockpile_display(
+    imgui: &Ui,
+    ecs: &mut World,
+    mouse_world_pos: &(usize, usize, usize),
+    job_board: &mut JobBoard,
+) {
+    let mut sp = STOCKPILE_PARAMS.write();
+    let stockpiles: Vec<(usize, usize)> = <(&Stockpile, &IdentityTag)>::query()
+        .iter(ecs)
+        .map(|(stockpile, id)| (id.0, stockpile.tiles.len()))
+        .collect();
+    if !stockpiles.iter().any(|(id, _)| Some(*id) == sp.selected) {
+        sp.selected = stockpiles.first().map(|(id, _)| *id);
+    }
+
+    let rlock = RAWS.read();
+    let item_names: Vec<ImString> = rlock
+        .items
+        .items
+        .iter()
+        .map(|i| ImString::new(&i.name))
+        .collect();
+    let item_refs: Vec<&ImStr> = item_names.iter().map(|s| s.as_ref()).collect();
+    let material_names: Vec<ImString> = rlock
+        .materials
+        .materials
+        .iter()
+        .map(|m| ImString::new(&m.name))
+        .collect();
+    let material_refs: Vec<&ImStr> = material_names.iter().map(|s| s.as_ref()).collect();
+
+    let mut filter = sp.selected.and_then(|selected| {
+        <(&Stockpile, &IdentityTag)>::query()
+            .iter(ecs)
+            .find(|(_, id)| id.0 == selected)
+            .map(|(stockpile, _)| stockpile.filter.clone())
+    });
+    let mut new_stockpile = false;
+    let mut delete_stockpile = false;
+
+    let title = format!(
+        "Stockpiles. Click to add tiles to a stockpile, right click to remove them. ### Stockpiles",
+    );
+    let title_tmp = ImString::new(title);
+    let window = Window::new(&title_tmp);
+    window
+        .size([420.0, 400.0], Condition::FirstUseEver)
+        .movable(true)
+        .position([0.0, 20.0], Condition::FirstUseEver)
+        .build(imgui, || {
+            if imgui.button(im_str!("\u{f067} New Stockpile"), [150.0, 20.0]) {
+                new_stockpile = true;
+            }
+            stockpiles.iter().for_each(|(id, tiles)| {
+                let label = format!("Stockpile #{} ({} tiles)##sp{}", id, tiles, id);
+                if Selectable::new(&ImString::new(label))
+                    .selected(sp.selected == Some(*id))
+                    .build(imgui)
+                {
+                    sp.selected = Some(*id);
+                }
+            });
+
+            let filter = match &mut filter {
+                Some(filter) => filter,
+                None => return,
+            };
+            imgui.separator();
+            if imgui.button(im_str!("\u{f05e} Remove Stockpile"), [150.0, 20.0]) {
+                delete_stockpile = true;
+            }
+
+            imgui.text_colored(
+                [1.0, 1.0, 0.0, 1.0],
+                im_str!("Takes (leave a list empty to take anything):"),
+            );
+            imgui.text(im_str!("Item types:"));
+            ITEM_TYPES.iter().for_each(|(item_type, name)| {
+                let mut checked = filter.item_types.contains(item_type);
+                if imgui.checkbox(&ImString::new(*name), &mut checked) {
+                    if checked {
+                        filter.item_types.push(*item_type);
+                    } else {
+                        filter.item_types.retain(|it| it != item_type);
+                    }
+                }
+            });
+
+            imgui.text(im_str!("Items:"));
+            let mut remove_tag = None;
+            filter.tags.iter().enumerate().for_each(|(i, tag)| {
+                let name = rlock
+                    .items
+                    .items
+                    .iter()
+                    .find(|it| it.tag == *tag)
+                    .map(|it| it.name.as_str())
+                    .unwrap_or(tag);
+                imgui.text(ImString::new(name));
+                imgui.same_line(260.0);
+                if imgui.button(&ImString::new(format!("Remove##tag{}", i)), [75.0, 20.0]) {
+                    remove_tag = Some(i);
+                }
+            });
+            if let Some(i) = remove_tag {
+                filter.tags.remove(i);
+            }
+            imgui.set_next_item_width(250.0);
+            ComboBox::new(im_str!("##add_tag")).build_simple_string(
+                &imgui,
+                &mut sp.add_tag,
+                &item_refs,
+            );
+            imgui.same_line(260.0);
+            if imgui.button(im_str!("Add##tag"), [75.0, 20.0]) {
+                if let Some(item) = rlock.items.items.get(sp.add_tag) {
+                    if !filter.tags.contains(&item.tag) {
+                        filter.tags.push(item.tag.clone());
+                    }
+                }
+            }
+
+            imgui.text(im_str!("Materials:"));
+            let mut remove_material = None;
+            filter
+                .materials
+                .iter()
+                .enumerate()
+                .for_each(|(i, material)| {
+                    imgui.text(&material_names[*material]);
+                    imgui.same_line(260.0);
+                    if imgui.button(&ImString::new(format!("Remove##mat{}", i)), [75.0, 20.0]) {
+                        remove_material = Some(i);
+                    }
+                });
+            if let Some(i) = remove_material {
+                filter.materials.remove(i);
+            }
+            imgui.set_next_item_width(250.0);
+            ComboBox::new(im_str!("##add_material")).build_simple_string(
+                &imgui,
+                &mut sp.add_material,
+                &material_refs,
+            );
+            imgui.same_line(260.0);
+            if imgui.button(im_str!("Add##mat"), [75.0, 20.0]) {
+                if !filter.materials.contains(&sp.add_material) {
+                    filter.materials.push(sp.add_material);
+                }
+            }
+        });
+    std::mem::drop(rlock);
+
+    if new_stockpile {
+        let id = IdentityTag::new();
+        sp.selected = Some(id.0);
+        ecs.push((
+            id,
+            Stockpile {
+                tiles: Vec::new(),
+                filter: StockpileFilter::default(),
+            },
+        ));
+        return;
+    }
+
+    let selected = match sp.selected {
+        Some(selected) => selected,
+        None => return,
+    };
+    if delete_stockpile {
+        let to_delete: Vec<Entity> = <(Entity, &IdentityTag)>::query()
+            .filter(component::<Stockpile>())
+            .iter(ecs)
+            .filter(|(_, id)| id.0 == selected)
+            .map(|(e, _)| *e)
+            .collect();
+        to_delete.iter().for_each(|e| {
+            ecs.remove(*e);
+        });
+        sp.selected = None;
+        job_board.is_dirty = true;
+        return;
+    }
+
+    let idx = mapidx(mouse_world_pos.0, mouse_world_pos.1, mouse_world_pos.2);
+    let paint = !imgui.io().want_capture_mouse
+        && imgui.io().mouse_down[0]
+        && REGION.read().flag(idx, Region::CAN_STAND_HERE);
+    let erase = !imgui.io().want_capture_mouse && imgui.io().mouse_down[1];
+    let in_another = <(&Stockpile, &IdentityTag)>::query()
+        .iter(ecs)
+        .any(|(stockpile, id)| id.0 != selected && stockpile.tiles.contains(&idx));
+
+    <(&mut Stockpile, &IdentityTag)>::query()
+        .iter_mut(ecs)
+        .for_each(|(stockpile, id)| {
+            if erase && stockpile.tiles.contains(&idx) {
+                stockpile.tiles.retain(|t| *t != idx);
+                job_board.is_dirty = true;
+            }
+            if id.0 == selected {
+                if paint && !in_another && !stockpile.tiles.contains(&idx) {
+                    stockpile.tiles.push(idx);
+                    job_board.is_dirty = true;
+                }
+                if let Some(filter) = filter.take() {
+                    if stockpile.filter != filter {
+                        stockpile.filter = filter;
+                        job_board.is_dirty = true;
+                    }
+                }
+            }
+        });
+}
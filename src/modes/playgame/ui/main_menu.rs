@@ -1,10 +1,16 @@
-use super::super::RunState;
+use super::super::{AutosaveSettings, GameStateResource, RunState};
 use crate::modes::playgame::DesignMode;
 use bengine::gui::*;
 use legion::*;
 use nox_components::*;
 
-pub fn draw_main_menu(ecs: &World, run_state: &mut RunState, imgui: &Ui) {
+pub fn draw_main_menu(
+    ecs: &World,
+    run_state: &mut RunState,
+    game_state: &mut GameStateResource,
+    autosave: &mut AutosaveSettings,
+    imgui: &Ui,
+) {
     if let Some(menu_bar) = imgui.begin_main_menu_bar() {
         let running_str = match run_state {
             RunState::SlowMo => im_str!("\u{f051} Slow Motion ### RunMenu"),
@@ -19,7 +25,19 @@ pub fn draw_main_menu(ecs: &World, run_state: &mut RunState, imgui: &Ui) {
             hud_time = c.get_date_time();
         }
 
-        MenuItem::new(im_str!("\u{f135} Nox Futura ### NFMain")).build(imgui);
+        if let Some(menu) = imgui.begin_menu(im_str!("\u{f135} Nox Futura ### NFMain"), true) {
+            if MenuItem::new(im_str!("\u{f0c7} Save Game"))
+                .shortcut(im_str!("F5"))
+                .build(imgui)
+            {
+                game_state.save_requested = true;
+            }
+            if let Some(autosave_menu) = imgui.begin_menu(im_str!("\u{f017} Autosave"), true) {
+                draw_autosave_menu(autosave, imgui);
+                autosave_menu.end(imgui);
+            }
+            menu.end(imgui);
+        }
 
         if let Some(menu) = imgui.begin_menu(running_str, true) {
             if MenuItem::new(im_str!("\u{f017} Pause"))
@@ -96,6 +114,22 @@ pub fn draw_main_menu(ecs: &World, run_state: &mut RunState, imgui: &Ui) {
                     mode: DesignMode::Construction,
                 };
             }
+            if MenuItem::new(im_str!("\u{f024} Rally Point"))
+                .shortcut(im_str!("G"))
+                .build(imgui)
+            {
+                *run_state = RunState::Design {
+                    mode: DesignMode::Rally,
+                };
+            }
+            if MenuItem::new(im_str!("\u{f187} Stockpiles"))
+                .shortcut(im_str!("P"))
+                .build(imgui)
+            {
+                *run_state = RunState::Design {
+                    mode: DesignMode::Stockpiles,
+                };
+            }
             menu.end(imgui);
         }
 
@@ -107,3 +141,47 @@ pub fn draw_main_menu(ecs: &World, run_state: &mut RunState, imgui: &Ui) {
         menu_bar.end(imgui);
     }
 }
+
+fn draw_autosave_menu(autosave: &mut AutosaveSettings, imgui: &Ui) {
+    if MenuItem::new(im_str!("Enabled"))
+        .selected(autosave.enabled)
+        .build(imgui)
+    {
+        autosave.enabled = !autosave.enabled;
+        autosave.next_due = None;
+    }
+    imgui.separator();
+
+    let intervals = [
+        (im_str!("Every hour"), 60),
+        (im_str!("Every 6 hours"), 6 * 60),
+        (im_str!("Every day"), 24 * 60),
+        (im_str!("Every week"), 7 * 24 * 60),
+    ];
+    for (label, minutes) in intervals.iter() {
+        if MenuItem::new(label)
+            .selected(autosave.interval_minutes == *minutes)
+            .enabled(autosave.enabled)
+            .build(imgui)
+        {
+            autosave.interval_minutes = *minutes;
+            autosave.next_due = None;
+        }
+    }
+    imgui.separator();
+
+    let keeps = [
+        (im_str!("Keep 1 autosave"), 1),
+        (im_str!("Keep 3 autosaves"), 3),
+        (im_str!("Keep 5 autosaves"), 5),
+    ];
+    for (label, keep) in keeps.iter() {
+        if MenuItem::new(label)
+            .selected(autosave.keep == *keep)
+            .enabled(autosave.enabled)
+            .build(imgui)
+        {
+            autosave.keep = *keep;
+        }
+    }
+}
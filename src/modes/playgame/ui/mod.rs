@@ -3,6 +3,8 @@ mod design_buildings;
 mod design_construction;
 mod design_lumberjack;
 mod design_mining;
+mod design_rally;
+mod design_stockpiles;
 mod main_menu;
 mod settlers_list;
 mod tables;
@@ -13,6 +15,8 @@ pub use design_buildings::*;
 pub use design_construction::*;
 pub use design_lumberjack::*;
 pub use design_mining::*;
+pub use design_rally::*;
+pub use design_stockpiles::*;
 pub use main_menu::*;
 pub use settlers_list::settler_list_display;
 pub use tooltips::*;
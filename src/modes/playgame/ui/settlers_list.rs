@@ -3,18 +3,24 @@ use bengine::gui::*;
 use legion::*;
 use nox_components::*;
 
+/// The labor matrix: a row per settler, and a column per labor. Clicking a
+/// cell cycles how keen the settler is on that work, from off ("-") up to
+/// the highest priority and back.
 pub fn settler_list_display(imgui: &Ui, ecs: &World) {
-    let mut available_picks = 0;
-    let mut available_axes = 0;
-    <&Tool>::query()
-        .filter(!component::<Claimed>())
+    let mut free_tools = Vec::new();
+    let mut claimed_tools = Vec::new();
+    <(&Tool, Option<&Claimed>)>::query()
         .iter(ecs)
-        .for_each(|tool| match tool.usage {
-            ToolType::Chopping => available_axes += 1,
-            ToolType::Digging => available_picks += 1,
-            _ => {}
+        .for_each(|(tool, claim)| match claim {
+            Some(claim) => claimed_tools.push((claim.by, tool.usage)),
+            None => free_tools.push(tool.usage),
         });
 
+    let labors = Labor::all();
+    let mut headings = vec!["Name", "Profession"];
+    headings.extend(labors.iter().map(|l| l.name()));
+    headings.push("Options");
+
     let size = bengine::get_window_size();
     let title = format!("All Settlers. ### SettlerList",);
     let title_tmp = ImString::new(title);
@@ -27,14 +33,8 @@ pub fn settler_list_display(imgui: &Ui, ecs: &World) {
         .movable(true)
         .position([20.0, 20.0], Condition::FirstUseEver)
         .build(imgui, || {
-            begin_table(
-                &["Name", "Profession", "Mining", "Lumber", "Options"],
-                imgui,
-                "settler_list",
-                true,
-            );
+            begin_table(&headings, imgui, "settler_list", true);
 
-            // Make something here
             <(&Name, &Tagline, &Settler, &IdentityTag)>::query()
                 .iter(ecs)
                 .for_each(|(n, t, settler, id)| {
@@ -44,38 +44,31 @@ pub fn settler_list_display(imgui: &Ui, ecs: &World) {
                     imgui.text(ImString::new(&t.name));
                     imgui.next_column();
 
-                    if settler.miner {
-                        let label = format!("\u{f05e} Mining");
-                        if imgui.button(&ImString::new(label), [100.0, 20.0]) {
-                            crate::modes::playgame::fire_miner(id.0);
-                        }
-                    } else {
-                        if available_picks > 0 {
-                            let label = format!("\u{f1b3} Miner##{}", id.0);
-                            if imgui.button(&ImString::new(label), [100.0, 20.0]) {
-                                crate::modes::playgame::become_miner(id.0);
+                    for labor in labors.iter() {
+                        let priority = settler.labors.priority(labor);
+                        let has_tool = match labor.tool() {
+                            None => true,
+                            Some(usage) => {
+                                claimed_tools.contains(&(id.0, usage))
+                                    || free_tools.contains(&usage)
                             }
+                        };
+                        if priority == 0 && !has_tool {
+                            imgui.text_disabled(im_str!("No tools"));
                         } else {
-                            imgui.text(im_str!("Not Miner"));
-                        }
-                    }
-                    imgui.next_column();
-                    if settler.lumberjack {
-                        let label = format!("\u{f05e} Lumberjack");
-                        if imgui.button(&ImString::new(label), [100.0, 20.0]) {
-                            crate::modes::playgame::fire_lumberjack(id.0);
-                        }
-                    } else {
-                        if available_axes > 0 {
-                            let label = format!("\u{f1bb} Lumberjack##{}", id.0);
-                            if imgui.button(&ImString::new(label), [100.0, 20.0]) {
-                                crate::modes::playgame::become_lumberjack(id.0);
+                            let text = if priority == 0 {
+                                "-".to_string()
+                            } else {
+                                priority.to_string()
+                            };
+                            let label = format!("{}##{}_{}", text, id.0, labor.name());
+                            if imgui.button(&ImString::new(label), [40.0, 20.0]) {
+                                let next = (priority + 1) % (Labors::MAX_PRIORITY + 1);
+                                crate::modes::playgame::set_labor(id.0, labor.clone(), next);
                             }
-                        } else {
-                            imgui.text(im_str!("Not Lumberjack"));
                         }
+                        imgui.next_column();
                     }
-                    imgui.next_column();
 
                     let label = format!("\u{f00e} View##{}", id.0);
                     imgui.button(&ImString::new(label), [100.0, 20.0]);
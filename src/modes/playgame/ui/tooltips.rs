@@ -31,17 +31,17 @@ pub fn draw_tooltips(
     {
         let idx = mapidx(mouse_world_pos.0, mouse_world_pos.1, mouse_world_pos.2);
         let r = REGION.read();
-        if !r.revealed[idx] {
+        if !r.is_revealed(idx) {
             return ZoomRequest::None;
         }
 
         // Type info
-        let mi = r.material_idx[idx];
+        let mi = r.material(idx);
         lines.push((
             true,
             format!(
                 "{} ({})",
-                match r.tile_types[idx] {
+                match r.tile_type(idx) {
                     TileType::Empty => "Empty Space",
                     TileType::Floor => "Floor",
                     TileType::SemiMoltenRock => "Semi-Molten Rock",
@@ -82,6 +82,24 @@ pub fn draw_tooltips(
         if r.flag(idx, Region::CAN_GO_DOWN) {
             l += "D|";
         }
+        if r.flag(idx, Region::CAN_GO_NORTH_EAST) {
+            l += "NE|";
+        }
+        if r.flag(idx, Region::CAN_GO_NORTH_WEST) {
+            l += "NW|";
+        }
+        if r.flag(idx, Region::CAN_GO_SOUTH_EAST) {
+            l += "SE|";
+        }
+        if r.flag(idx, Region::CAN_GO_SOUTH_WEST) {
+            l += "SW|";
+        }
+        if r.flag(idx, Region::CAN_GO_UP_RAMP) {
+            l += "RU|";
+        }
+        if r.flag(idx, Region::CAN_GO_DOWN_RAMP) {
+            l += "RD|";
+        }
         if !l.is_empty() {
             lines.push((false, l));
         }